@@ -1,6 +1,17 @@
-use std::{collections::HashSet, env};
+use std::{collections::HashSet, env, fs, num::NonZeroUsize, path::PathBuf, time::Duration};
 
+use anyhow::Context;
 use clap::{Parser, ValueEnum};
+use globset::Glob;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    tls,
+};
+
+use crate::{
+    client::{AppCredentials, ClientOptions, RetryPolicy, is_managed_header},
+    server::ServerOptions,
+};
 
 /// Command-line arguments for configuring the MCP server.
 #[derive(Parser, Debug)]
@@ -21,9 +32,125 @@ pub struct Args {
     #[arg(long, default_value = "GITHUB_AUTH_TOKEN")]
     pub token_env: String,
 
+    /// GitHub App ID; authenticates as an app installation instead of using a token.
+    #[arg(long, requires_all = ["private_key_path", "installation_id"])]
+    pub app_id: Option<String>,
+
+    /// Path to the GitHub App's PEM private key, used with --app-id.
+    #[arg(long, value_name = "PATH", requires = "app_id")]
+    pub private_key_path: Option<PathBuf>,
+
+    /// Installation of the GitHub App to mint tokens for, used with --app-id.
+    #[arg(long, requires = "app_id")]
+    pub installation_id: Option<u64>,
+
     /// Restrict which tools are exposed; defaults to all.
     #[arg(long, value_enum, value_delimiter = ',', num_args = 1..)]
     pub tools: Option<Vec<ToolSelection>>,
+
+    /// Retries a single tool call may spend across all of its GitHub requests.
+    #[arg(long, default_value_t = 3)]
+    pub retry_budget: u32,
+
+    /// Total seconds a single tool call may spend waiting between retries.
+    #[arg(long, default_value_t = 30)]
+    pub retry_budget_secs: u64,
+
+    /// Extra `Name: Value` header sent with every GitHub request; repeatable.
+    #[arg(long = "header", value_name = "NAME: VALUE", value_parser = parse_header)]
+    pub headers: Vec<(HeaderName, HeaderValue)>,
+
+    /// PEM file of extra root certificates to trust, e.g. a TLS-inspecting proxy's private CA.
+    #[arg(long, value_name = "PATH")]
+    pub ca_cert: Option<PathBuf>,
+
+    /// Oldest TLS version to accept when connecting to GitHub.
+    #[arg(long, value_enum, value_name = "VERSION")]
+    pub min_tls: Option<MinTls>,
+
+    /// Most GitHub requests in flight at once across all tool calls, to stay clear of
+    /// secondary rate limits; unlimited by default.
+    #[arg(long, value_name = "N")]
+    pub max_concurrency: Option<NonZeroUsize>,
+
+    /// Most files `bundle`, `grep_repo`, and `get_file_with_imports` fetch at once, across
+    /// all of their calls together; a call's `concurrency` can only lower it.
+    #[arg(long, value_name = "N", default_value_t = NonZeroUsize::new(8).unwrap())]
+    pub batch_concurrency: NonZeroUsize,
+
+    /// Directory every `path` argument is resolved under, e.g. `packages/foo` in a monorepo.
+    #[arg(long, value_name = "PREFIX")]
+    pub path_prefix: Option<String>,
+
+    /// Glob of repository paths no tool may read, e.g. `.env` or `**/secrets/**`; repeatable.
+    #[arg(long = "deny-path", value_name = "GLOB", value_parser = parse_glob)]
+    pub deny_paths: Vec<Glob>,
+
+    /// Add a `meta` object (server version, API base, effective ref, timestamp) to every tool result.
+    #[arg(long)]
+    pub include_meta: bool,
+
+    /// Text appended to `get_file` content that was cut short, e.g. '\n... [truncated {n} chars]';
+    /// `{n}` becomes the number of chars left out and `\n` a newline. Off by default.
+    #[arg(long, value_name = "TEXT", value_parser = parse_marker)]
+    pub truncation_marker: Option<String>,
+
+    /// Ref read by file, tree, and stats tools when a call names none, pinning the server to a
+    /// snapshot; a call's own `ref` still wins. Defaults to each repository's default branch.
+    #[arg(long, value_name = "REF")]
+    pub default_ref: Option<String>,
+
+    /// Let tool calls pass `api_base` to reach another GitHub host (e.g. an Enterprise
+    /// instance) for that call only; the configured token is sent there too. Off by default.
+    #[arg(long)]
+    pub allow_base_override: bool,
+
+    /// Most pages any auto-paginating list (tags, branches, `list_repos` with `all_pages`) fetches.
+    #[arg(long, default_value_t = 10)]
+    pub max_list_pages: usize,
+
+    /// Bytes of GitHub responses cached for conditional requests, which don't count against
+    /// the rate limit when unchanged; 0 disables the cache.
+    #[arg(long, default_value_t = 8 * 1024 * 1024)]
+    pub etag_cache_bytes: usize,
+
+    /// Deepest nesting of `tree` output; subtrees below it are returned as flat, path-named lists.
+    #[arg(long, default_value_t = 64)]
+    pub max_tree_nesting: usize,
+
+    /// Deepest `tree` expansion a single call may request; larger depths are clamped.
+    #[arg(long, default_value_t = 20)]
+    pub max_tree_depth: usize,
+
+    /// Invoke a single tool, print its JSON result to stdout, and exit instead of serving MCP.
+    #[arg(long, value_enum, value_name = "TOOL")]
+    pub oneshot: Option<ToolSelection>,
+
+    /// JSON object of arguments for --oneshot.
+    #[arg(
+        long = "args",
+        value_name = "JSON",
+        default_value = "{}",
+        requires = "oneshot"
+    )]
+    pub oneshot_args: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum MinTls {
+    #[value(name = "1.2")]
+    Tls12,
+    #[value(name = "1.3")]
+    Tls13,
+}
+
+impl MinTls {
+    pub fn version(self) -> tls::Version {
+        match self {
+            MinTls::Tls12 => tls::Version::TLS_1_2,
+            MinTls::Tls13 => tls::Version::TLS_1_3,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, ValueEnum)]
@@ -37,10 +164,45 @@ pub enum ToolSelection {
     ListRepos,
     Search,
     GetStats,
+    ListIssues,
+    DiffFiles,
+    GetLicense,
+    ResolveRefs,
+    ProjectType,
+    GetMainFile,
+    GetCommitDiff,
+    Bundle,
+    GetCommunityFile,
+    ListPulls,
+    GetArchiveUrl,
+    ListCommits,
+    ListPullFiles,
+    TreeEstimate,
+    GetFileWithImports,
+    GrepRepo,
+    DirSize,
+    RefreshRepoCache,
+    LicenseCheck,
+    ListDeployments,
+    GetGist,
+    ListMilestones,
+    CommitActivity,
+    ListCollaborators,
+    GetBlob,
+    WebUrl,
+    PathExists,
+    GetRepos,
+    DirProfile,
+    ListTagsSemver,
+    GetDependencies,
+    IssueCounts,
+    GetFileAtMergeBase,
+    DescribeTools,
+    TreeGraph,
 }
 
 impl ToolSelection {
-    pub const ALL: [ToolSelection; 8] = [
+    pub const ALL: [ToolSelection; 43] = [
         ToolSelection::GetRepo,
         ToolSelection::ListTags,
         ToolSelection::ListBranches,
@@ -49,6 +211,41 @@ impl ToolSelection {
         ToolSelection::ListRepos,
         ToolSelection::Search,
         ToolSelection::GetStats,
+        ToolSelection::ListIssues,
+        ToolSelection::DiffFiles,
+        ToolSelection::GetLicense,
+        ToolSelection::ResolveRefs,
+        ToolSelection::ProjectType,
+        ToolSelection::GetMainFile,
+        ToolSelection::GetCommitDiff,
+        ToolSelection::Bundle,
+        ToolSelection::GetCommunityFile,
+        ToolSelection::ListPulls,
+        ToolSelection::GetArchiveUrl,
+        ToolSelection::ListCommits,
+        ToolSelection::ListPullFiles,
+        ToolSelection::TreeEstimate,
+        ToolSelection::GetFileWithImports,
+        ToolSelection::GrepRepo,
+        ToolSelection::DirSize,
+        ToolSelection::RefreshRepoCache,
+        ToolSelection::LicenseCheck,
+        ToolSelection::ListDeployments,
+        ToolSelection::GetGist,
+        ToolSelection::ListMilestones,
+        ToolSelection::CommitActivity,
+        ToolSelection::ListCollaborators,
+        ToolSelection::GetBlob,
+        ToolSelection::WebUrl,
+        ToolSelection::PathExists,
+        ToolSelection::GetRepos,
+        ToolSelection::DirProfile,
+        ToolSelection::ListTagsSemver,
+        ToolSelection::GetDependencies,
+        ToolSelection::IssueCounts,
+        ToolSelection::GetFileAtMergeBase,
+        ToolSelection::DescribeTools,
+        ToolSelection::TreeGraph,
     ];
 
     pub fn as_str(&self) -> &'static str {
@@ -61,6 +258,41 @@ impl ToolSelection {
             ToolSelection::ListRepos => "list_repos",
             ToolSelection::Search => "search",
             ToolSelection::GetStats => "get_stats",
+            ToolSelection::ListIssues => "list_issues",
+            ToolSelection::DiffFiles => "diff_files",
+            ToolSelection::GetLicense => "get_license",
+            ToolSelection::ResolveRefs => "resolve_refs",
+            ToolSelection::ProjectType => "project_type",
+            ToolSelection::GetMainFile => "get_main_file",
+            ToolSelection::GetCommitDiff => "get_commit_diff",
+            ToolSelection::Bundle => "bundle",
+            ToolSelection::GetCommunityFile => "get_community_file",
+            ToolSelection::ListPulls => "list_pulls",
+            ToolSelection::GetArchiveUrl => "get_archive_url",
+            ToolSelection::ListCommits => "list_commits",
+            ToolSelection::ListPullFiles => "list_pull_files",
+            ToolSelection::TreeEstimate => "tree_estimate",
+            ToolSelection::GetFileWithImports => "get_file_with_imports",
+            ToolSelection::GrepRepo => "grep_repo",
+            ToolSelection::DirSize => "dir_size",
+            ToolSelection::RefreshRepoCache => "refresh_repo_cache",
+            ToolSelection::LicenseCheck => "license_check",
+            ToolSelection::ListDeployments => "list_deployments",
+            ToolSelection::GetGist => "get_gist",
+            ToolSelection::ListMilestones => "list_milestones",
+            ToolSelection::CommitActivity => "commit_activity",
+            ToolSelection::ListCollaborators => "list_collaborators",
+            ToolSelection::GetBlob => "get_blob",
+            ToolSelection::WebUrl => "web_url",
+            ToolSelection::PathExists => "path_exists",
+            ToolSelection::GetRepos => "get_repos",
+            ToolSelection::DirProfile => "dir_profile",
+            ToolSelection::ListTagsSemver => "list_tags_semver",
+            ToolSelection::GetDependencies => "get_dependencies",
+            ToolSelection::IssueCounts => "issue_counts",
+            ToolSelection::GetFileAtMergeBase => "get_file_at_merge_base",
+            ToolSelection::DescribeTools => "describe_tools",
+            ToolSelection::TreeGraph => "tree_graph",
         }
     }
 }
@@ -76,6 +308,59 @@ impl Args {
         })
     }
 
+    pub fn client_options(&self) -> anyhow::Result<ClientOptions> {
+        Ok(ClientOptions {
+            retry: RetryPolicy {
+                max_retries: self.retry_budget,
+                max_wait: Duration::from_secs(self.retry_budget_secs),
+                ..RetryPolicy::default()
+            },
+            headers: self.headers.iter().cloned().collect::<HeaderMap>(),
+            app: self.app_credentials()?,
+            max_list_pages: self.max_list_pages,
+            max_tree_nesting: self.max_tree_nesting,
+            etag_cache_bytes: self.etag_cache_bytes,
+            ca_cert: self.ca_cert.clone(),
+            min_tls: self.min_tls.map(MinTls::version),
+            max_concurrency: self.max_concurrency.map(NonZeroUsize::get),
+            ..ClientOptions::default()
+        })
+    }
+
+    fn app_credentials(&self) -> anyhow::Result<Option<AppCredentials>> {
+        let (Some(app_id), Some(key_path), Some(installation_id)) =
+            (&self.app_id, &self.private_key_path, self.installation_id)
+        else {
+            return Ok(None);
+        };
+
+        let private_key = fs::read(key_path).with_context(|| {
+            format!(
+                "Failed to read GitHub App private key from {}",
+                key_path.display()
+            )
+        })?;
+
+        Ok(Some(AppCredentials {
+            app_id: app_id.clone(),
+            installation_id,
+            private_key,
+        }))
+    }
+
+    pub fn server_options(&self) -> ServerOptions {
+        ServerOptions {
+            path_prefix: self.path_prefix.clone(),
+            max_tree_depth: self.max_tree_depth,
+            deny_paths: self.deny_paths.clone(),
+            include_meta: self.include_meta,
+            truncation_marker: self.truncation_marker.clone(),
+            default_ref: self.default_ref.clone(),
+            allow_base_override: self.allow_base_override,
+            batch_concurrency: self.batch_concurrency.get(),
+        }
+    }
+
     pub fn allowed_tools(&self) -> HashSet<ToolSelection> {
         self.tools
             .as_ref()
@@ -83,3 +368,67 @@ impl Args {
             .unwrap_or_else(|| ToolSelection::ALL.into_iter().collect())
     }
 }
+
+fn parse_header(raw: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = raw
+        .split_once(':')
+        .ok_or_else(|| format!("expected `Name: Value`, got `{raw}`"))?;
+
+    let name = HeaderName::try_from(name.trim())
+        .map_err(|err| format!("invalid header name `{}`: {err}", name.trim()))?;
+    if is_managed_header(&name) {
+        return Err(format!(
+            "header `{name}` is managed by the server and cannot be overridden"
+        ));
+    }
+
+    let value = HeaderValue::try_from(value.trim())
+        .map_err(|err| format!("invalid value for header `{name}`: {err}"))?;
+
+    Ok((name, value))
+}
+
+/// Expands `\n` so a multi-line marker can be passed on the command line.
+fn parse_marker(raw: &str) -> Result<String, String> {
+    Ok(raw.replace("\\n", "\n"))
+}
+
+fn parse_glob(raw: &str) -> Result<Glob, String> {
+    Glob::new(raw.trim_matches('/')).map_err(|err| format!("invalid glob `{raw}`: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_header, parse_marker};
+
+    #[test]
+    fn parses_name_value_headers() {
+        let (name, value) = parse_header("X-Routing-Key:  tenant-a ").unwrap();
+
+        assert_eq!(name.as_str(), "x-routing-key");
+        assert_eq!(value, "tenant-a");
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        assert!(parse_header("no-colon").is_err());
+        assert!(parse_header(": value").is_err());
+        assert!(parse_header("bad name: value").is_err());
+        assert!(parse_header("X-Ok: line\nbreak").is_err());
+    }
+
+    #[test]
+    fn expands_newline_escapes_in_markers() {
+        assert_eq!(
+            parse_marker(r"\n... [truncated {n} chars]").unwrap(),
+            "\n... [truncated {n} chars]"
+        );
+    }
+
+    #[test]
+    fn rejects_managed_headers() {
+        let err = parse_header("Authorization: Bearer other").unwrap_err();
+
+        assert!(err.contains("managed by the server"));
+    }
+}
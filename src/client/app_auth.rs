@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::error::ApiErrorBody;
+
+/// Refresh installation tokens this long before GitHub expires them.
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+/// Lifetime of the app JWT; GitHub rejects anything longer than ten minutes.
+const JWT_LIFETIME: Duration = Duration::from_secs(9 * 60);
+/// Backdates `iat` to tolerate clock drift between this host and GitHub.
+const JWT_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// GitHub App identity used to mint installation tokens instead of sending a PAT.
+#[derive(Debug, Clone)]
+pub struct AppCredentials {
+    pub app_id: String,
+    pub installation_id: u64,
+    /// PEM-encoded RSA private key downloaded from the app's settings.
+    pub private_key: Vec<u8>,
+}
+
+/// Mints and caches installation tokens for one GitHub App installation.
+pub struct InstallationAuth {
+    app_id: String,
+    /// `POST /app/installations/{id}/access_tokens` on the configured API base.
+    token_url: Url,
+    key: EncodingKey,
+    cached: Mutex<Option<InstallationToken>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iat: i64,
+    exp: i64,
+    iss: &'a str,
+}
+
+impl InstallationAuth {
+    pub fn new(credentials: AppCredentials, base_url: &Url) -> anyhow::Result<Self> {
+        let key = EncodingKey::from_rsa_pem(&credentials.private_key)
+            .context("GitHub App private key is not a valid RSA PEM key")?;
+
+        let mut token_url = base_url.clone();
+        token_url
+            .path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("API base URL is not valid for paths"))?
+            .pop_if_empty()
+            .extend([
+                "app",
+                "installations",
+                &credentials.installation_id.to_string(),
+                "access_tokens",
+            ]);
+
+        Ok(Self {
+            app_id: credentials.app_id,
+            token_url,
+            key,
+            cached: Mutex::new(None),
+        })
+    }
+
+    /// Returns a valid installation token, minting a new one when the cached token is
+    /// missing or close to expiry. Concurrent callers wait for a single refresh.
+    pub async fn token(&self, http: &Client) -> Result<String, ApiErrorBody> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some(token) = cached.as_ref()
+            && !needs_refresh(token.expires_at, Utc::now())
+        {
+            return Ok(token.token.clone());
+        }
+
+        let token = self.mint(http).await?;
+        let value = token.token.clone();
+        *cached = Some(token);
+        Ok(value)
+    }
+
+    async fn mint(&self, http: &Client) -> Result<InstallationToken, ApiErrorBody> {
+        let response = http
+            .post(self.token_url.clone())
+            .bearer_auth(self.jwt()?)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .send()
+            .await
+            .map_err(ApiErrorBody::from_reqwest)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let mut err = ApiErrorBody::from_response(status, response).await;
+            err.message = format!("Failed to mint installation token: {}", err.message);
+            return Err(err);
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))
+    }
+
+    fn jwt(&self) -> Result<String, ApiErrorBody> {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            iat: now - JWT_CLOCK_SKEW.as_secs() as i64,
+            exp: now + JWT_LIFETIME.as_secs() as i64,
+            iss: &self.app_id,
+        };
+
+        jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+            .map_err(|err| ApiErrorBody::new(format!("Failed to sign app JWT: {}", err), "0"))
+    }
+}
+
+/// Whether a token expiring at `expires_at` should be replaced at `now`.
+fn needs_refresh(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    expires_at - now <= chrono::Duration::from_std(REFRESH_MARGIN).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header_exists, method, path},
+    };
+
+    use super::*;
+
+    fn credentials() -> AppCredentials {
+        AppCredentials {
+            app_id: "1234".to_string(),
+            installation_id: 42,
+            private_key: include_bytes!("testdata/app-key.pem").to_vec(),
+        }
+    }
+
+    fn mock_auth(server: &MockServer) -> InstallationAuth {
+        InstallationAuth::new(credentials(), &Url::parse(&server.uri()).unwrap()).unwrap()
+    }
+
+    fn token_body(token: &str, expires_at: DateTime<Utc>) -> serde_json::Value {
+        json!({ "token": token, "expires_at": expires_at.to_rfc3339() })
+    }
+
+    #[test]
+    fn refreshes_only_within_the_margin() {
+        let now = Utc::now();
+
+        assert!(!needs_refresh(now + chrono::Duration::minutes(60), now));
+        assert!(!needs_refresh(now + chrono::Duration::minutes(6), now));
+        assert!(needs_refresh(now + chrono::Duration::minutes(5), now));
+        assert!(needs_refresh(now - chrono::Duration::minutes(1), now));
+    }
+
+    #[test]
+    fn rejects_invalid_private_keys() {
+        let result = InstallationAuth::new(
+            AppCredentials {
+                private_key: b"not a key".to_vec(),
+                ..credentials()
+            },
+            &Url::parse("https://api.github.com").unwrap(),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn reuses_token_until_it_nears_expiry() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/app/installations/42/access_tokens"))
+            .and(header_exists("authorization"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(token_body(
+                "ghs_fresh",
+                Utc::now() + chrono::Duration::hours(1),
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let auth = mock_auth(&server);
+        let http = Client::new();
+
+        assert_eq!(auth.token(&http).await.unwrap(), "ghs_fresh");
+        assert_eq!(auth.token(&http).await.unwrap(), "ghs_fresh");
+    }
+
+    #[tokio::test]
+    async fn mints_again_when_cached_token_is_about_to_expire() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/app/installations/42/access_tokens"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(token_body(
+                "ghs_short",
+                Utc::now() + chrono::Duration::minutes(2),
+            )))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let auth = mock_auth(&server);
+        let http = Client::new();
+
+        auth.token(&http).await.unwrap();
+        auth.token(&http).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reports_mint_failures() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/app/installations/42/access_tokens"))
+            .respond_with(
+                ResponseTemplate::new(401).set_body_json(json!({ "message": "Bad credentials" })),
+            )
+            .mount(&server)
+            .await;
+
+        let err = mock_auth(&server).token(&Client::new()).await.unwrap_err();
+
+        assert_eq!(err.code, "401");
+        assert!(err.message.contains("Bad credentials"), "{}", err.message);
+    }
+}
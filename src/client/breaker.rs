@@ -0,0 +1,199 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// When to stop sending requests to GitHub because it appears to be down.
+#[derive(Debug, Clone)]
+pub struct BreakerPolicy {
+    /// Consecutive upstream failures that open the circuit.
+    pub failure_threshold: u32,
+    /// Failures further apart than this no longer count as consecutive.
+    pub window: Duration,
+    /// How long the circuit stays open before a single probe request is let through.
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Circuit breaker shared by every clone of a client.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    policy: BreakerPolicy,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed {
+        failures: u32,
+        last_failure: Option<Instant>,
+    },
+    Open {
+        until: Instant,
+    },
+    /// One probe is in flight; others fail fast until it reports back or goes stale.
+    HalfOpen {
+        probe_started: Instant,
+    },
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: BreakerPolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(State::Closed {
+                failures: 0,
+                last_failure: None,
+            }),
+        }
+    }
+
+    /// A closed breaker with the same policy, for another host.
+    pub fn fresh(&self) -> Self {
+        Self::new(self.policy.clone())
+    }
+
+    /// Admits a request, or returns how long the caller should wait before trying again.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    pub fn record_success(&self) {
+        *self.lock() = State::Closed {
+            failures: 0,
+            last_failure: None,
+        };
+    }
+
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now());
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.lock();
+
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now < until => Err(until - now),
+            State::HalfOpen { probe_started }
+                if now.duration_since(probe_started) < self.policy.cooldown =>
+            {
+                Err(self.policy.cooldown - now.duration_since(probe_started))
+            }
+            // Cooldown elapsed, or the previous probe never reported back.
+            State::Open { .. } | State::HalfOpen { .. } => {
+                *state = State::HalfOpen { probe_started: now };
+                Ok(())
+            }
+        }
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        let mut state = self.lock();
+
+        let failures = match *state {
+            State::Closed {
+                failures,
+                last_failure: Some(last),
+            } if now.duration_since(last) <= self.policy.window => failures + 1,
+            State::Closed { .. } => 1,
+            State::HalfOpen { .. } => self.policy.failure_threshold,
+            State::Open { .. } => return,
+        };
+
+        *state = if failures >= self.policy.failure_threshold {
+            State::Open {
+                until: now + self.policy.cooldown,
+            }
+        } else {
+            State::Closed {
+                failures,
+                last_failure: Some(now),
+            }
+        };
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(BreakerPolicy {
+            failure_threshold: 3,
+            window: Duration::from_secs(10),
+            cooldown: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let breaker = breaker();
+        let now = Instant::now();
+
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now);
+        assert!(breaker.try_acquire_at(now).is_ok());
+
+        breaker.record_failure_at(now);
+        assert_eq!(
+            breaker.try_acquire_at(now + Duration::from_secs(10)),
+            Err(Duration::from_secs(20))
+        );
+    }
+
+    #[test]
+    fn success_or_a_quiet_window_resets_the_count() {
+        let breaker = breaker();
+        let now = Instant::now();
+
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now);
+        breaker.record_success();
+        breaker.record_failure_at(now);
+        assert!(breaker.try_acquire_at(now).is_ok());
+
+        breaker.record_failure_at(now);
+        breaker.record_failure_at(now + Duration::from_secs(11));
+        assert!(breaker.try_acquire_at(now).is_ok());
+    }
+
+    #[test]
+    fn half_open_admits_one_probe_and_reopens_on_failure() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(now);
+        }
+
+        let later = now + Duration::from_secs(30);
+        assert!(breaker.try_acquire_at(later).is_ok());
+        assert!(breaker.try_acquire_at(later).is_err());
+
+        breaker.record_failure_at(later);
+        assert!(
+            breaker
+                .try_acquire_at(later + Duration::from_secs(1))
+                .is_err()
+        );
+
+        let recovered = later + Duration::from_secs(30);
+        assert!(breaker.try_acquire_at(recovered).is_ok());
+        breaker.record_success();
+        assert!(breaker.try_acquire_at(recovered).is_ok());
+        assert!(breaker.try_acquire_at(recovered).is_ok());
+    }
+}
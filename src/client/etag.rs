@@ -0,0 +1,221 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use reqwest::{
+    ResponseBuilderExt, StatusCode, Url,
+    header::{ACCEPT, HeaderMap},
+};
+
+/// ETags remembered at most, independently of how many bodies fit in the byte budget.
+const MAX_ETAGS: usize = 4096;
+
+/// A successful response kept so a later `304 Not Modified` can be answered locally.
+///
+/// `url` is the final URL the body came from, so callers comparing it against the
+/// requested one (to spot redirects) see the same answer on a cache hit.
+#[derive(Debug, Clone)]
+pub struct CachedBody {
+    pub etag: String,
+    pub url: Url,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl CachedBody {
+    pub fn into_response(self) -> reqwest::Response {
+        let mut response = http::Response::builder()
+            .status(self.status)
+            .url(self.url)
+            .body(self.body)
+            .expect("status and URL are already valid");
+        *response.headers_mut() = self.headers;
+        reqwest::Response::from(response)
+    }
+}
+
+/// Conditional-request cache: ETags per request, plus bodies within a byte budget.
+///
+/// Bodies are evicted oldest first while their ETags stay, so a `304` can arrive for a
+/// body that is no longer held; callers must then fetch again unconditionally.
+#[derive(Debug)]
+pub struct EtagCache {
+    max_bytes: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    etags: HashMap<String, String>,
+    etag_order: VecDeque<String>,
+    bodies: HashMap<String, CachedBody>,
+    body_order: VecDeque<String>,
+    body_bytes: usize,
+}
+
+impl EtagCache {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Identifies a request by media type and URL; GET is the only method cached.
+    pub fn key(request: &reqwest::Request) -> String {
+        let accept = request
+            .headers()
+            .get(ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        format!("{} {}", accept, request.url())
+    }
+
+    pub fn etag(&self, key: &str) -> Option<String> {
+        self.lock().etags.get(key).cloned()
+    }
+
+    /// The stored body for `key`, if it is still held and matches the ETag GitHub validated.
+    pub fn body(&self, key: &str, etag: &str) -> Option<CachedBody> {
+        self.lock()
+            .bodies
+            .get(key)
+            .filter(|cached| cached.etag == etag)
+            .cloned()
+    }
+
+    pub fn insert(&self, key: String, cached: CachedBody) {
+        let mut state = self.lock();
+
+        if state
+            .etags
+            .insert(key.clone(), cached.etag.clone())
+            .is_none()
+        {
+            state.etag_order.push_back(key.clone());
+            while state.etag_order.len() > MAX_ETAGS {
+                if let Some(oldest) = state.etag_order.pop_front() {
+                    state.etags.remove(&oldest);
+                    state.remove_body(&oldest);
+                }
+            }
+        }
+
+        state.remove_body(&key);
+        let size = cached.body.len();
+        if size > self.max_bytes {
+            return;
+        }
+        while state.body_bytes + size > self.max_bytes {
+            let Some(oldest) = state.body_order.pop_front() else {
+                break;
+            };
+            state.remove_body(&oldest);
+        }
+        state.body_bytes += size;
+        state.body_order.push_back(key.clone());
+        state.bodies.insert(key, cached);
+    }
+
+    /// Drops every entry whose URL is `url` or lies under it, returning how many went.
+    pub fn forget(&self, url: &str) -> usize {
+        let mut state = self.lock();
+        let stale: Vec<String> = state
+            .etags
+            .keys()
+            .filter(|key| {
+                key.rsplit_once(' ')
+                    .is_some_and(|(_, cached)| within_url(cached, url))
+            })
+            .cloned()
+            .collect();
+
+        for key in &stale {
+            state.etags.remove(key);
+            state.remove_body(key);
+        }
+        state.etag_order.retain(|key| !stale.contains(key));
+        stale.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn within_url(candidate: &str, url: &str) -> bool {
+    candidate
+        .strip_prefix(url)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+}
+
+impl CacheState {
+    fn remove_body(&mut self, key: &str) {
+        if let Some(removed) = self.bodies.remove(key) {
+            self.body_bytes -= removed.body.len();
+            self.body_order.retain(|queued| queued != key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(etag: &str, body: &str) -> CachedBody {
+        CachedBody {
+            etag: etag.to_string(),
+            url: Url::parse("https://api.github.com/repos/o/r").unwrap(),
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn evicts_oldest_bodies_but_keeps_their_etags() {
+        let cache = EtagCache::new(8);
+        cache.insert("a".to_string(), cached("\"1\"", "aaaa"));
+        cache.insert("b".to_string(), cached("\"2\"", "bbbb"));
+        cache.insert("c".to_string(), cached("\"3\"", "cccc"));
+
+        assert!(cache.body("a", "\"1\"").is_none());
+        assert_eq!(cache.etag("a").as_deref(), Some("\"1\""));
+        assert_eq!(cache.body("b", "\"2\"").unwrap().body, b"bbbb");
+        assert_eq!(cache.body("c", "\"3\"").unwrap().body, b"cccc");
+    }
+
+    #[test]
+    fn forgets_a_url_and_everything_under_it() {
+        let cache = EtagCache::new(1024);
+        for url in [
+            "https://api.github.com/repos/octo/repo",
+            "https://api.github.com/repos/octo/repo/tags?per_page=100",
+            "https://api.github.com/repos/octo/repo-two",
+        ] {
+            cache.insert(format!("application/json {url}"), cached("\"1\"", "body"));
+        }
+
+        assert_eq!(cache.forget("https://api.github.com/repos/octo/repo"), 2);
+        assert!(
+            cache
+                .etag("application/json https://api.github.com/repos/octo/repo")
+                .is_none()
+        );
+        assert!(
+            cache
+                .etag("application/json https://api.github.com/repos/octo/repo-two")
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn ignores_bodies_for_a_different_etag() {
+        let cache = EtagCache::new(1024);
+        cache.insert("a".to_string(), cached("\"1\"", "old"));
+
+        assert!(cache.body("a", "\"2\"").is_none());
+    }
+}
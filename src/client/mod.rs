@@ -1,650 +1,4594 @@
-use std::collections::{HashMap, VecDeque};
+mod app_auth;
+mod breaker;
+mod etag;
+mod ratelimit;
+mod retry;
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    env,
+    ffi::OsString,
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Context;
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
-use reqwest::{Client, StatusCode, Url};
+use chrono::DateTime;
+use reqwest::{
+    Certificate, Client, ResponseBuilderExt, StatusCode, Url,
+    header::{
+        ACCEPT, AUTHORIZATION, ETAG, HeaderMap, HeaderName, HeaderValue, IF_MODIFIED_SINCE,
+        IF_NONE_MATCH, LINK, LOCATION, USER_AGENT,
+    },
+    tls,
+};
 use serde::{Deserialize, de::DeserializeOwned};
+use tokio::{sync::Semaphore, time::Instant};
 
 use crate::{
-    error::ApiErrorBody,
-    models::{EntryType, LicenseInfo, RepoInfo, RepoSummary, SearchResult, Stats, TreeEntry},
+    error::{self, ApiErrorBody, ErrorKind},
+    models::{
+        Affiliation, BranchDetail, ChangedFile, Collaborator, CollaboratorPermissions,
+        CommitSummary, Deployment, DiffFormat, EntryType, Gist, GistFile, IssueState, IssueSummary,
+        LicenseInfo, LicenseText, Milestone, OwnerType, PullSummary, RepoInfo, RepoSummary,
+        SearchResult, Stats, TagDetail, TreeEntry, WeeklyCommits,
+    },
 };
 
+pub use app_auth::AppCredentials;
+use app_auth::InstallationAuth;
+pub use breaker::BreakerPolicy;
+use breaker::CircuitBreaker;
+use etag::{CachedBody, EtagCache};
+use ratelimit::{RateLimiter, ResourceCategory};
+use retry::RetryBudget;
+pub use retry::{RetryPolicy, RetryStats};
+
+/// Results the search API serves for one query, however many pages are requested.
+const SEARCH_RESULT_CAP: usize = 1000;
+/// Largest page size the search API accepts.
+const SEARCH_MAX_PER_PAGE: usize = 100;
+/// Pages `search_code_all` requests at most; enough to reach the result cap.
+const SEARCH_MAX_PAGES: usize = SEARCH_RESULT_CAP / SEARCH_MAX_PER_PAGE;
+/// Page size requested when walking every page of a list endpoint.
+const LIST_PER_PAGE: usize = 100;
+/// Requests made for a repository statistic while GitHub is still computing it (202).
+const STATS_POLL_ATTEMPTS: usize = 4;
+/// Endpoints that may be POSTed to, matched at the end of the path so an Enterprise
+/// `/api/v3` prefix does not matter: they render markdown and change nothing on GitHub.
+const READ_ONLY_POST_ENDPOINTS: &[&str] = &["/markdown", "/markdown/raw"];
+
+/// Tunables for [`GithubClient`] beyond the API base and token.
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    pub retry: RetryPolicy,
+    pub breaker: BreakerPolicy,
+    /// Extra headers sent with every request; they never replace headers the client manages.
+    pub headers: HeaderMap,
+    /// Minimum spacing between search requests, which have a much lower rate limit than core.
+    pub search_interval: Duration,
+    /// Authenticate as a GitHub App installation; takes precedence over the token.
+    pub app: Option<AppCredentials>,
+    /// Most pages any auto-paginating list (tags, branches, repos) fetches per call.
+    pub max_list_pages: usize,
+    /// Deepest nesting `tree` output may have; deeper subtrees are returned flat.
+    pub max_tree_nesting: usize,
+    /// Bytes of response bodies kept for conditional (`If-None-Match`) requests; 0 disables it.
+    pub etag_cache_bytes: usize,
+    /// PEM file of extra root certificates to trust, e.g. a TLS-inspecting proxy's CA.
+    pub ca_cert: Option<PathBuf>,
+    /// Oldest TLS version the client will negotiate.
+    pub min_tls: Option<tls::Version>,
+    /// Wait between requests for a repository statistic GitHub is still computing.
+    pub stats_poll_interval: Duration,
+    /// Most GitHub requests in flight at once across every tool call; `None` is unlimited.
+    pub max_concurrency: Option<usize>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            retry: RetryPolicy::default(),
+            breaker: BreakerPolicy::default(),
+            headers: HeaderMap::new(),
+            search_interval: Duration::from_secs(2),
+            app: None,
+            max_list_pages: 10,
+            max_tree_nesting: 64,
+            etag_cache_bytes: 8 * 1024 * 1024,
+            ca_cert: None,
+            min_tls: None,
+            stats_poll_interval: Duration::from_secs(2),
+            max_concurrency: None,
+        }
+    }
+}
+
+/// What a contents path holds when read as a file.
+#[derive(Debug)]
+pub enum PathContent {
+    /// The file's bytes, not yet checked to be UTF-8; see [`utf8_text`].
+    File(Vec<u8>),
+    /// GitHub describes the link itself when its target is not a regular file.
+    Symlink { target: String },
+}
+
+impl PathContent {
+    /// The file's bytes, refusing a symlink rather than following it.
+    pub fn into_file(self) -> Result<Vec<u8>, ApiErrorBody> {
+        match self {
+            PathContent::File(content) => Ok(content),
+            PathContent::Symlink { target } => Err(ApiErrorBody::new(
+                format!("Requested path is a symlink to `{}`, not a file.", target),
+                "400",
+            )),
+        }
+    }
+}
+
+/// Items gathered from every page of a list endpoint, up to the page cap, or from the
+/// single page a caller asked for.
+#[derive(Debug)]
+pub struct Listing<T> {
+    pub items: Vec<T>,
+    /// More pages remained when the `max_list_pages` cap was reached.
+    pub truncated: bool,
+    /// Where a single requested page sits among the others; empty when every page was read.
+    pub links: PageLinks,
+}
+
+impl<T> Listing<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Listing<U> {
+        Listing {
+            items: self.items.into_iter().map(f).collect(),
+            truncated: self.truncated,
+            links: self.links,
+        }
+    }
+}
+
+/// Page numbers GitHub advertised in a response's `Link` header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageLinks {
+    pub next: Option<usize>,
+    pub last: Option<usize>,
+}
+
+/// When a tree crawl stops opening directories before reaching its depth.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrawlLimits {
+    pub deadline: Option<Instant>,
+    /// Entries listed after which no further directory is opened; the listing that crosses
+    /// it is still kept whole.
+    pub max_entries: Option<usize>,
+}
+
+/// Entries directly under a path, counted without descending into directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelCounts {
+    pub files: usize,
+    pub directories: usize,
+}
+
+/// Totals accumulated while walking a directory, without keeping the entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirSize {
+    pub bytes: u64,
+    pub files: usize,
+    pub directories: usize,
+}
+
+/// Code search results together with the total number of matches GitHub reported.
+#[derive(Debug)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    pub total_count: u64,
+    /// GitHub timed out on at least one page and returned partial matches.
+    pub incomplete: bool,
+}
+
+/// Repository metadata plus where GitHub redirected the lookup, if anywhere.
+#[derive(Debug)]
+pub struct FetchedRepo {
+    pub info: RepoInfo,
+    /// Canonical `owner/repo` when the requested name redirected to a renamed repo.
+    pub redirected_to: Option<String>,
+    pub default_branch: String,
+    /// GitHub's ETag for the repository, to send back as `If-None-Match` when polling.
+    pub etag: Option<String>,
+}
+
+/// A repository lookup made with the caller's ETag.
+#[derive(Debug)]
+pub enum RepoLookup {
+    /// GitHub answered `304 Not Modified`: the repository still has that ETag.
+    NotModified,
+    Found(Box<FetchedRepo>),
+    Missing,
+}
+
+/// Whether a path still matches the ETag or date its caller holds.
+#[derive(Debug)]
+pub enum Freshness {
+    Unchanged,
+    /// The path changed (or the caller had no ETag); `etag` is GitHub's current one, and
+    /// `stats` and `content` describe the version that came with it, so callers need not
+    /// fetch a possibly newer one. `content` is only there for files GitHub inlined (up to
+    /// 1 MB); everything is `None` for a missing path.
+    Changed {
+        etag: Option<String>,
+        stats: Option<Stats>,
+        content: Option<PathContent>,
+    },
+}
+
+impl Freshness {
+    /// Counts a missing path as changed, leaving the caller's regular read to report it.
+    pub fn missing() -> Self {
+        Freshness::Changed {
+            etag: None,
+            stats: None,
+            content: None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct GithubClient {
     http: Client,
+    /// Same as `http` but hands redirects back instead of following them.
+    no_redirect: Client,
     base_url: Url,
     token: Option<String>,
+    retry: RetryPolicy,
+    breaker: Arc<CircuitBreaker>,
+    extra_headers: HeaderMap,
+    rate_limits: Arc<RateLimiter>,
+    app_auth: Option<Arc<InstallationAuth>>,
+    max_list_pages: usize,
+    max_tree_nesting: usize,
+    stats_poll_interval: Duration,
+    /// Shared by clones, so the cap holds server-wide.
+    in_flight: Option<Arc<Semaphore>>,
+    etags: Option<Arc<EtagCache>>,
 }
 
 impl GithubClient {
     pub fn new(api_base: String, token: Option<String>) -> anyhow::Result<Self> {
+        Self::with_options(api_base, token, ClientOptions::default())
+    }
+
+    /// The GitHub API base URL requests are sent to.
+    pub fn api_base(&self) -> &str {
+        self.base_url.as_str()
+    }
+
+    pub fn with_options(
+        api_base: String,
+        token: Option<String>,
+        options: ClientOptions,
+    ) -> anyhow::Result<Self> {
         let base_url =
             Url::parse(api_base.trim_end_matches('/')).context("Invalid GitHub API base URL")?;
 
-        let http = Client::builder()
-            .user_agent(format!(
-                "{}/{}",
-                env!("CARGO_PKG_NAME"),
-                env!("CARGO_PKG_VERSION")
-            ))
+        let user_agent = format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        let mut ca_certs = match &options.ca_cert {
+            Some(path) => load_ca_certs(path)?,
+            None => Vec::new(),
+        };
+        ca_certs.extend(env_ca_certs(env::var_os("SSL_CERT_FILE"))?);
+        let builder = || {
+            let mut builder = Client::builder().user_agent(&user_agent);
+            for cert in &ca_certs {
+                builder = builder.add_root_certificate(cert.clone());
+            }
+            if let Some(version) = options.min_tls {
+                builder = builder.min_tls_version(version);
+            }
+            builder
+        };
+        let http = builder().build().context("Failed to build HTTP client")?;
+        let no_redirect = builder()
+            .redirect(reqwest::redirect::Policy::none())
             .build()
             .context("Failed to build HTTP client")?;
 
+        let app_auth = options
+            .app
+            .map(|app| InstallationAuth::new(app, &base_url).map(Arc::new))
+            .transpose()?;
+
         Ok(Self {
             http,
+            no_redirect,
             base_url,
             token,
+            retry: options.retry,
+            breaker: Arc::new(CircuitBreaker::new(options.breaker)),
+            extra_headers: options.headers,
+            rate_limits: Arc::new(RateLimiter::new(options.search_interval)),
+            app_auth,
+            max_list_pages: options.max_list_pages.max(1),
+            max_tree_nesting: options.max_tree_nesting.max(1),
+            stats_poll_interval: options.stats_poll_interval,
+            in_flight: options
+                .max_concurrency
+                .map(|limit| Arc::new(Semaphore::new(limit.max(1)))),
+            etags: (options.etag_cache_bytes > 0)
+                .then(|| Arc::new(EtagCache::new(options.etag_cache_bytes))),
         })
     }
 
-    pub async fn get_repo(
+    /// A client for the same server settings that sends requests to `api_base` instead. It
+    /// shares the token, connection pool, ETag cache, and concurrency cap, but tracks rate
+    /// limits and failures separately, and drops GitHub App auth, whose tokens are minted for
+    /// the configured host.
+    pub fn with_api_base(&self, api_base: &str) -> Result<Self, ApiErrorBody> {
+        let base_url = Url::parse(api_base.trim().trim_end_matches('/'))
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+            .ok_or_else(|| {
+                ApiErrorBody::new(
+                    format!(
+                        "Invalid api_base `{}`: it must be an http(s) URL.",
+                        api_base
+                    ),
+                    "400",
+                )
+            })?;
+
+        Ok(Self {
+            base_url,
+            breaker: Arc::new(self.breaker.fresh()),
+            rate_limits: Arc::new(self.rate_limits.fresh()),
+            app_auth: None,
+            ..self.clone()
+        })
+    }
+
+    /// Web page for `path` in a repository as shown at `ref` (`HEAD`, the default branch, when
+    /// `None`), under `tree` for directories and `blob` otherwise. The web host is derived
+    /// from the API base: `api.github.com` serves `github.com`, and Enterprise's `/api/v3`
+    /// lives under the web host itself.
+    pub fn web_url_for(
         &self,
         owner: &str,
         repo: &str,
-    ) -> Result<Option<RepoInfo>, ApiErrorBody> {
-        let url = self.build_url(&["repos", owner, repo])?;
-        let response = self
-            .base_request(url, None)
-            .send()
-            .await
-            .map_err(ApiErrorBody::from_reqwest)?;
-
-        let status = response.status();
-        if status == StatusCode::NOT_FOUND {
-            return Ok(None);
+        r#type: EntryType,
+        r#ref: Option<&str>,
+        path: &str,
+    ) -> String {
+        let mut url = self.base_url.clone();
+        if url.host_str() == Some("api.github.com") {
+            let _ = url.set_host(Some("github.com"));
         }
-        if !status.is_success() {
-            return Err(ApiErrorBody::from_response(status, response).await);
+        let base_path = url.path().trim_end_matches('/');
+        let web_path = base_path
+            .strip_suffix("/api/v3")
+            .unwrap_or(base_path)
+            .to_string();
+        url.set_path(&web_path);
+        url.set_query(None);
+
+        let path = path.trim_matches('/');
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty().extend([owner, repo]);
+            if r#ref.is_some() || !path.is_empty() {
+                let kind = match r#type {
+                    EntryType::Dir => "tree",
+                    _ => "blob",
+                };
+                segments.push(kind);
+                segments.extend(r#ref.unwrap_or("HEAD").split('/'));
+                segments.extend(path.split('/').filter(|segment| !segment.is_empty()));
+            }
         }
+        url.to_string()
+    }
 
-        let repo: GithubRepo = response
-            .json()
-            .await
-            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
-        Ok(Some(repo.into()))
+    /// Runs one tool call so that all of its requests draw from a single retry budget.
+    pub async fn scoped<F: Future>(&self, fut: F) -> F::Output {
+        self.scoped_with_stats(fut).await.0
     }
 
-    pub async fn list_tags(&self, owner: &str, repo: &str) -> Result<Vec<String>, ApiErrorBody> {
-        let url = self.build_url(&["repos", owner, repo, "tags"])?;
-        self.get_collection::<GithubTag>(url).await
+    /// [`Self::scoped`], also reporting the retries the call ended up making.
+    pub async fn scoped_with_stats<F: Future>(&self, fut: F) -> (F::Output, RetryStats) {
+        let budget = RetryBudget::new(&self.retry);
+        let output = budget.clone().scope(fut).await;
+        (output, budget.spent())
     }
 
-    pub async fn list_branches(
+    pub async fn get_repo(
         &self,
         owner: &str,
         repo: &str,
-    ) -> Result<Vec<String>, ApiErrorBody> {
-        let url = self.build_url(&["repos", owner, repo, "branches"])?;
-        self.get_collection::<GithubBranch>(url).await
+    ) -> Result<Option<FetchedRepo>, ApiErrorBody> {
+        Ok(match self.lookup_repo(owner, repo, None).await? {
+            RepoLookup::Found(fetched) => Some(*fetched),
+            RepoLookup::NotModified | RepoLookup::Missing => None,
+        })
     }
 
-    pub async fn list_repos(
+    /// [`Self::get_repo`] sent with `If-None-Match: etag`, which GitHub answers with a cheap
+    /// `304` while the repository is unchanged. An empty `etag` sends no header.
+    pub async fn get_repo_with_etag(
         &self,
         owner: &str,
-        page: Option<usize>,
-        per_page: Option<usize>,
-    ) -> Result<Vec<RepoSummary>, ApiErrorBody> {
-        let mut last_err: Option<ApiErrorBody> = None;
-
-        for base in ["users", "orgs"] {
-            let url = self.build_url(&[base, owner, "repos"])?;
-
-            let mut request = self.base_request(url, None);
-
-            if let Some(page) = page {
-                request = request.query(&[("page", &page.to_string())]);
-            }
-
-            if let Some(per_page) = per_page {
-                request = request.query(&[("per_page", &per_page.to_string())]);
-            }
-
-            let response = request.send().await.map_err(ApiErrorBody::from_reqwest)?;
+        repo: &str,
+        etag: &str,
+    ) -> Result<RepoLookup, ApiErrorBody> {
+        self.lookup_repo(owner, repo, Some(etag).filter(|etag| !etag.is_empty()))
+            .await
+    }
 
-            let status = response.status();
+    async fn lookup_repo(
+        &self,
+        owner: &str,
+        repo: &str,
+        etag: Option<&str>,
+    ) -> Result<RepoLookup, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo])?;
+        let mut request = self.base_request(url.clone(), None);
+        if let Some(etag) = etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = self.send(request).await?;
 
-            if status == StatusCode::NOT_FOUND {
-                last_err = Some(ApiErrorBody::from_response(status, response).await);
-                continue;
-            }
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok(RepoLookup::NotModified);
+        }
+        if status == StatusCode::NOT_FOUND {
+            return Ok(RepoLookup::Missing);
+        }
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
 
-            if !status.is_success() {
-                return Err(ApiErrorBody::from_response(status, response).await);
-            }
+        // GitHub answers renamed or transferred repos with a 301 that reqwest follows.
+        let redirected = response.url() != &url;
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
 
-            let repos: Vec<GithubRepoSummary> = response
-                .json()
-                .await
-                .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        let body: GithubRepo = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
 
-            return Ok(repos.into_iter().map(Into::into).collect());
-        }
+        let requested = format!("{}/{}", owner, repo);
+        let redirected_to = (redirected && !body.full_name.eq_ignore_ascii_case(&requested))
+            .then(|| body.full_name.clone());
 
-        Err(last_err.unwrap_or_else(|| {
-            ApiErrorBody::new("Failed to list repositories for the requested owner.", "0")
-        }))
+        Ok(RepoLookup::Found(Box::new(FetchedRepo {
+            default_branch: body.default_branch.clone(),
+            info: body.into(),
+            redirected_to,
+            etag,
+        })))
     }
 
-    pub async fn search_code(
-        &self,
-        query: &str,
-        page: Option<usize>,
-        per_page: Option<usize>,
-    ) -> Result<Vec<SearchResult>, ApiErrorBody> {
-        let url = self.build_url(&["search", "code"])?;
+    /// Forgets every cached response for the repository, so the next request for any of its
+    /// URLs goes to GitHub unconditionally; returns how many were dropped.
+    pub fn forget_repo(&self, owner: &str, repo: &str) -> Result<usize, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo])?;
+        Ok(self
+            .etags
+            .as_deref()
+            .map_or(0, |cache| cache.forget(url.as_str())))
+    }
 
-        let mut request = self.base_request(url, None).query(&[("q", query)]);
+    /// Gists live under `/gists` beside `/repos`, so this works against Enterprise API bases
+    /// as well.
+    pub async fn get_gist(&self, id: &str) -> Result<Gist, ApiErrorBody> {
+        let url = self.build_url(&["gists", id])?;
+        let response = self.send(self.base_request(url, None)).await?;
 
-        if let Some(page) = page {
-            request = request.query(&[("page", &page.to_string())]);
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
         }
 
-        if let Some(per_page) = per_page {
-            request = request.query(&[("per_page", &per_page.to_string())]);
-        }
+        let gist: GithubGist = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        Ok(gist.into())
+    }
 
-        let response = request.send().await.map_err(ApiErrorBody::from_reqwest)?;
+    pub async fn default_branch(&self, owner: &str, repo: &str) -> Result<String, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo])?;
+        let response = self.send(self.base_request(url, None)).await?;
 
         let status = response.status();
         if !status.is_success() {
             return Err(ApiErrorBody::from_response(status, response).await);
         }
 
-        let body: GithubSearchResponse = response
+        let repo: GithubRepo = response
             .json()
             .await
             .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
-
-        Ok(body.items.into_iter().map(Into::into).collect())
+        Ok(repo.default_branch)
     }
 
-    pub async fn tree(
+    pub async fn get_license(
         &self,
         owner: &str,
         repo: &str,
-        path: &str,
-        depth: usize,
-        r#ref: Option<&str>,
-    ) -> Result<Vec<TreeEntry>, ApiErrorBody> {
-        let contents = self.fetch_contents(owner, repo, path, r#ref).await?;
+    ) -> Result<Option<LicenseText>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "license"])?;
+        let response = self.send(self.base_request(url, None)).await?;
 
-        let root_parent = match &contents {
-            GithubContents::Directory(_) => normalize_root_path(path),
-            GithubContents::File(file) => parent_path(&file.path),
-        };
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
 
-        self.expand_tree(owner, repo, contents, depth, r#ref, &root_parent)
+        let body: GithubLicenseContent = response
+            .json()
             .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        Ok(Some(LicenseText {
+            spdx_id: body.license.as_ref().and_then(|l| l.spdx_id.clone()),
+            name: body.license.and_then(|l| l.name),
+            path: body.path,
+            content: decode_content(body.encoding, body.content)?,
+        }))
     }
 
-    pub async fn get_stats(
+    /// Resolves a branch, tag, or sha to its commit sha; `None` when the ref does not exist.
+    pub async fn resolve_ref(
         &self,
         owner: &str,
         repo: &str,
-        path: &str,
-        r#ref: Option<&str>,
-    ) -> Result<Stats, ApiErrorBody> {
-        let normalized_path = normalize_root_path(path);
-        let contents = self.fetch_contents(owner, repo, path, r#ref).await?;
+        r#ref: &str,
+    ) -> Result<Option<String>, ApiErrorBody> {
+        let mut segments = vec!["repos", owner, repo, "commits"];
+        segments.extend(r#ref.split('/').filter(|s| !s.is_empty()));
+        let url = self.build_url(&segments)?;
 
-        match contents {
-            GithubContents::File(file) => Ok(file.into_stats()),
-            GithubContents::Directory(_) => Ok(directory_stats(&normalized_path)),
+        let request = self.request_with_accept(url, None, "application/vnd.github.sha");
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        // GitHub answers unknown refs with 404, and malformed ones with 422.
+        if status == StatusCode::NOT_FOUND || status == StatusCode::UNPROCESSABLE_ENTITY {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
         }
+
+        let sha = response
+            .text()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        Ok(Some(sha.trim().to_string()))
     }
 
-    pub async fn get_file(
+    /// Resolves the sha of the commit `base` and `head` last had in common, from GitHub's
+    /// `base...head` comparison.
+    pub async fn merge_base(
         &self,
         owner: &str,
         repo: &str,
-        path: &str,
-        r#ref: Option<&str>,
+        base: &str,
+        head: &str,
     ) -> Result<String, ApiErrorBody> {
-        let contents = self.fetch_contents(owner, repo, path, r#ref).await?;
-
-        match contents {
-            GithubContents::File(file) => {
-                let encoding = file.encoding.unwrap_or_else(|| "base64".to_string());
-                if encoding != "base64" {
-                    return Err(ApiErrorBody::new(
-                        format!("Unsupported encoding: {}", encoding),
-                        "0",
-                    ));
-                }
-
-                let payload = file
-                    .content
-                    .ok_or_else(|| ApiErrorBody::new("File content missing", "0"))?;
-
-                let decoded = STANDARD
-                    .decode(payload.replace('\n', ""))
-                    .map_err(|err| ApiErrorBody::new(err.to_string(), "0"))?;
-
-                String::from_utf8(decoded).map_err(|err| ApiErrorBody::new(err.to_string(), "0"))
-            }
-            GithubContents::Directory(_) => Err(ApiErrorBody::new(
-                "Requested path is a directory, not a file.",
-                "400",
-            )),
-        }
-    }
-
-    fn build_url(&self, segments: &[&str]) -> Result<Url, ApiErrorBody> {
-        let mut url = self.base_url.clone();
-        {
-            let mut parts = url
-                .path_segments_mut()
-                .map_err(|_| ApiErrorBody::new("API base URL is not valid for paths", "0"))?;
-            parts.pop_if_empty();
-            for segment in segments {
-                parts.push(segment);
-            }
-        }
-        Ok(url)
-    }
-
-    fn base_request(&self, url: Url, r#ref: Option<&str>) -> reqwest::RequestBuilder {
-        let mut builder = self
-            .http
-            .get(url)
-            .header("Accept", "application/vnd.github+json")
-            .header("X-GitHub-Api-Version", "2022-11-28");
-
-        if let Some(token) = &self.token {
-            builder = builder.bearer_auth(token);
-        }
-
-        if let Some(r#ref) = r#ref {
-            builder = builder.query(&[("ref", r#ref)]);
-        }
+        let range = format!("{}...{}", normalize_ref(base), normalize_ref(head));
+        let url = self.build_url(&["repos", owner, repo, "compare", &range])?;
 
-        builder
-    }
+        // Only the merge base is needed, so keep the listed commits to one.
+        let request = with_pagination(self.base_request(url, None), None, Some(1));
+        let response = self.send(request).await?;
 
-    async fn get_collection<T>(&self, url: Url) -> Result<Vec<String>, ApiErrorBody>
-    where
-        T: NamedItem + DeserializeOwned,
-    {
-        let response = self
-            .base_request(url, None)
-            .send()
-            .await
-            .map_err(ApiErrorBody::from_reqwest)?;
         let status = response.status();
-
         if !status.is_success() {
             return Err(ApiErrorBody::from_response(status, response).await);
         }
 
-        let items: Vec<T> = response
+        let body: GithubCompare = response
             .json()
             .await
             .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
-
-        Ok(items.into_iter().map(|item| item.name()).collect())
+        Ok(body.merge_base_commit.sha)
     }
 
-    async fn fetch_contents(
+    /// Fetches a commit rendered as a unified diff or patch rather than JSON.
+    pub async fn commit_diff(
         &self,
         owner: &str,
         repo: &str,
-        path: &str,
-        r#ref: Option<&str>,
-    ) -> Result<GithubContents, ApiErrorBody> {
-        let mut segments = vec![
-            "repos".to_string(),
-            owner.to_string(),
-            repo.to_string(),
-            "contents".to_string(),
-        ];
-        segments.extend(
-            path.split('/')
-                .filter(|s| !s.is_empty())
-                .map(|p| p.to_string()),
-        );
+        r#ref: &str,
+        format: DiffFormat,
+    ) -> Result<String, ApiErrorBody> {
+        let mut segments = vec!["repos", owner, repo, "commits"];
+        segments.extend(r#ref.split('/').filter(|s| !s.is_empty()));
+        let url = self.build_url(&segments)?;
 
-        let url = self.build_url(&segments.iter().map(String::as_str).collect::<Vec<_>>())?;
+        let request = self.request_with_accept(url, None, format.media_type());
+        let response = self.send(request).await?;
 
-        let response = self
-            .base_request(url, r#ref)
-            .send()
-            .await
-            .map_err(ApiErrorBody::from_reqwest)?;
         let status = response.status();
-
         if !status.is_success() {
             return Err(ApiErrorBody::from_response(status, response).await);
         }
 
-        let body = response
+        response
             .text()
             .await
-            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))
+    }
+
+    pub async fn list_tags(
+        &self,
+        owner: &str,
+        repo: &str,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Listing<String>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "tags"])?;
+        self.get_collection::<GithubTag>(url, page, per_page).await
+    }
+
+    /// Tags with the sha of the commit each points at; `date` is left for
+    /// [`Self::commit_date`] to fill in.
+    pub async fn list_tag_details(
+        &self,
+        owner: &str,
+        repo: &str,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Listing<TagDetail>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "tags"])?;
+        let tags = self
+            .get_listing::<GithubTagDetail>(url, page, per_page)
+            .await?;
+        Ok(tags.map(Into::into))
+    }
+
+    /// Committer date of a commit, falling back to its author date.
+    pub async fn commit_date(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Option<String>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "commits", sha])?;
+        let response = self.send(self.base_request(url, None)).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
 
-        let value: serde_json::Value = serde_json::from_str(&body)
+        let commit: GithubCommit = response
+            .json()
+            .await
             .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        Ok(commit
+            .commit
+            .committer
+            .or(commit.commit.author)
+            .map(|person| person.date))
+    }
 
-        if value.is_array() {
-            let entries: Vec<GithubDirectoryEntry> = serde_json::from_value(value)
-                .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
-            Ok(GithubContents::Directory(entries))
-        } else {
-            let file: GithubFile = serde_json::from_value(value)
+    pub async fn list_branches(
+        &self,
+        owner: &str,
+        repo: &str,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Listing<String>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "branches"])?;
+        self.get_collection::<GithubBranch>(url, page, per_page)
+            .await
+    }
+
+    pub async fn list_branch_details(
+        &self,
+        owner: &str,
+        repo: &str,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Listing<BranchDetail>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "branches"])?;
+        let branches = self
+            .get_listing::<GithubBranchDetail>(url, page, per_page)
+            .await?;
+        Ok(branches.map(Into::into))
+    }
+
+    /// Lists every repository of a user or organization, up to the `max_list_pages` cap.
+    pub async fn list_all_repos(
+        &self,
+        owner: &str,
+        owner_type: Option<OwnerType>,
+    ) -> Result<Listing<RepoSummary>, ApiErrorBody> {
+        let mut last_err = None;
+
+        for base in owner_bases(owner_type) {
+            let url = self.build_url(&[base, owner, "repos"])?;
+            match self.get_all_pages::<GithubRepoSummary>(url).await {
+                Err(err) if err.is_not_found() => last_err = Some(err),
+                result => {
+                    return result.map(|repos| repos.map(Into::into));
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            ApiErrorBody::new("Failed to list repositories for the requested owner.", "0")
+        }))
+    }
+
+    pub async fn list_repos(
+        &self,
+        owner: &str,
+        owner_type: Option<OwnerType>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Vec<RepoSummary>, ApiErrorBody> {
+        let mut last_err: Option<ApiErrorBody> = None;
+
+        for base in owner_bases(owner_type) {
+            let url = self.build_url(&[base, owner, "repos"])?;
+
+            let request = with_pagination(self.base_request(url, None), page, per_page);
+            let response = self.send(request).await?;
+
+            let status = response.status();
+
+            if status == StatusCode::NOT_FOUND {
+                last_err = Some(ApiErrorBody::from_response(status, response).await);
+                continue;
+            }
+
+            if !status.is_success() {
+                return Err(ApiErrorBody::from_response(status, response).await);
+            }
+
+            let repos: Vec<GithubRepoSummary> = response
+                .json()
+                .await
                 .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
-            Ok(GithubContents::File(file))
+
+            return Ok(repos.into_iter().map(Into::into).collect());
         }
+
+        Err(last_err.unwrap_or_else(|| {
+            ApiErrorBody::new("Failed to list repositories for the requested owner.", "0")
+        }))
     }
 
-    async fn expand_tree(
+    pub async fn list_issues(
         &self,
         owner: &str,
         repo: &str,
-        contents: GithubContents,
-        depth: usize,
-        r#ref: Option<&str>,
-        root_parent: &str,
-    ) -> Result<Vec<TreeEntry>, ApiErrorBody> {
-        let mut queue: VecDeque<(GithubContents, usize)> = VecDeque::new();
-        let mut children_by_parent: HashMap<String, Vec<TreeEntry>> = HashMap::new();
+        state: Option<IssueState>,
+        labels: &[String],
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Vec<IssueSummary>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "issues"])?;
 
-        queue.push_back((contents, depth));
+        let mut request = with_pagination(self.base_request(url, None), page, per_page);
 
-        while let Some((node, remaining_depth)) = queue.pop_front() {
-            match node {
-                GithubContents::File(file) => {
-                    let parent = parent_path(&file.path);
-                    children_by_parent
-                        .entry(parent)
-                        .or_default()
-                        .push(file.into_tree_entry(Vec::new()));
-                }
-                GithubContents::Directory(entries) => {
-                    for entry in entries {
-                        let parent = parent_path(&entry.path);
-                        let is_dir = matches!(entry.r#type, GithubContentType::Dir);
-                        let path = entry.path.clone();
+        if let Some(state) = state {
+            request = request.query(&[("state", state.as_str())]);
+        }
 
-                        children_by_parent
-                            .entry(parent)
-                            .or_default()
-                            .push(entry.into_tree_entry(Vec::new()));
+        if !labels.is_empty() {
+            request = request.query(&[("labels", labels.join(","))]);
+        }
 
-                        if is_dir && remaining_depth > 1 {
-                            let nested_contents =
-                                self.fetch_contents(owner, repo, &path, r#ref).await?;
-                            queue.push_back((nested_contents, remaining_depth - 1));
-                        }
-                    }
-                }
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+
+        let issues: Vec<GithubIssue> = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        Ok(issues.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn list_pulls(
+        &self,
+        owner: &str,
+        repo: &str,
+        state: Option<IssueState>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Vec<PullSummary>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "pulls"])?;
+
+        let mut request = with_pagination(self.base_request(url, None), page, per_page);
+
+        if let Some(state) = state {
+            request = request.query(&[("state", state.as_str())]);
+        }
+
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+
+        let pulls: Vec<GithubPull> = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        Ok(pulls.into_iter().map(Into::into).collect())
+    }
+
+    /// Lists a repository's collaborators; GitHub only shows them to tokens with push access.
+    pub async fn list_collaborators(
+        &self,
+        owner: &str,
+        repo: &str,
+        affiliation: Option<Affiliation>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Vec<Collaborator>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "collaborators"])?;
+
+        let mut request = with_pagination(self.base_request(url, None), page, per_page);
+
+        if let Some(affiliation) = affiliation {
+            request = request.query(&[("affiliation", affiliation.as_str())]);
+        }
+
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let mut err = ApiErrorBody::from_response(status, response).await;
+            if status == StatusCode::FORBIDDEN && err.kind.is_none() {
+                err.message = format!(
+                    "Listing collaborators of {}/{} needs a token with push access to it (admin for the full list). ({})",
+                    owner, repo, err.message
+                );
             }
+            return Err(err);
         }
 
-        Ok(assemble_tree(&mut children_by_parent, root_parent))
+        let collaborators: Vec<GithubCollaborator> = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        Ok(collaborators.into_iter().map(Into::into).collect())
     }
-}
 
-#[derive(Debug, Deserialize)]
-struct GithubRepo {
-    description: Option<String>,
-    stargazers_count: u64,
-    forks_count: u64,
-    license: Option<GithubLicense>,
-}
+    /// Lists a repository's milestones, or `None` when it has issues disabled (GitHub
+    /// answers 410 Gone rather than an empty list).
+    pub async fn list_milestones(
+        &self,
+        owner: &str,
+        repo: &str,
+        state: Option<IssueState>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Option<Vec<Milestone>>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "milestones"])?;
 
-#[derive(Debug, Deserialize)]
-struct GithubLicense {
-    key: Option<String>,
-    name: Option<String>,
-    spdx_id: Option<String>,
-    url: Option<String>,
-}
+        let mut request = with_pagination(self.base_request(url, None), page, per_page);
 
-#[derive(Debug, Deserialize)]
-struct GithubTag {
-    name: String,
-}
+        if let Some(state) = state {
+            request = request.query(&[("state", state.as_str())]);
+        }
 
-#[derive(Debug, Deserialize)]
-struct GithubBranch {
-    name: String,
-}
+        let response = self.send(request).await?;
 
-#[derive(Debug, Deserialize)]
-struct GithubSearchResponse {
-    items: Vec<GithubSearchItem>,
-}
+        let status = response.status();
+        if status == StatusCode::GONE {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
 
-#[derive(Debug, Deserialize)]
-struct GithubSearchItem {
-    name: String,
-    path: String,
-    repository: GithubSearchRepo,
-}
+        let milestones: Vec<GithubMilestone> = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
 
-#[derive(Debug, Deserialize)]
-struct GithubSearchRepo {
-    full_name: String,
-}
+        Ok(Some(milestones.into_iter().map(Into::into).collect()))
+    }
 
-#[derive(Debug, Deserialize)]
-struct GithubRepoSummary {
-    name: String,
-    full_name: String,
-    private: bool,
-    html_url: String,
-    description: Option<String>,
-}
+    pub async fn list_deployments(
+        &self,
+        owner: &str,
+        repo: &str,
+        environment: Option<&str>,
+        r#ref: Option<&str>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Vec<Deployment>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "deployments"])?;
+
+        let mut request = with_pagination(self.base_request(url, None), page, per_page);
+
+        for (name, value) in [("environment", environment), ("ref", r#ref)] {
+            if let Some(value) = value {
+                request = request.query(&[(name, value)]);
+            }
+        }
+
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+
+        let deployments: Vec<GithubDeployment> = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        Ok(deployments.into_iter().map(Into::into).collect())
+    }
+
+    /// Lists the files a pull request changes: just the requested page when `page` or
+    /// `per_page` is given, otherwise every page up to the `max_list_pages` cap.
+    pub async fn list_pull_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Listing<ChangedFile>, ApiErrorBody> {
+        let number = number.to_string();
+        let url = self.build_url(&["repos", owner, repo, "pulls", &number, "files"])?;
+
+        let files = self
+            .get_listing::<GithubPullFile>(url, page, per_page)
+            .await?;
+        Ok(files.map(Into::into))
+    }
+
+    /// Lists commits reachable from `ref`, newest first, optionally within `since`..`until`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn list_commits(
+        &self,
+        owner: &str,
+        repo: &str,
+        r#ref: Option<&str>,
+        since: Option<&str>,
+        until: Option<&str>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Vec<CommitSummary>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "commits"])?;
+
+        let mut request = with_pagination(self.base_request(url, None), page, per_page);
+
+        for (name, value) in [("sha", r#ref), ("since", since), ("until", until)] {
+            if let Some(value) = value {
+                request = request.query(&[(name, value)]);
+            }
+        }
+
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+
+        let commits: Vec<GithubCommit> = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        Ok(commits.into_iter().map(Into::into).collect())
+    }
+
+    /// Weekly commit counts for the last year, oldest week first.
+    pub async fn commit_activity(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<WeeklyCommits>, ApiErrorBody> {
+        let weeks: Vec<GithubWeeklyCommits> = self
+            .get_repo_stat(owner, repo, "commit_activity")
+            .await?
+            .unwrap_or_default();
+        Ok(weeks.into_iter().map(Into::into).collect())
+    }
+
+    /// Reads one of the `/repos/{owner}/{repo}/stats/*` endpoints. GitHub computes these in
+    /// the background and answers 202 until they are ready, so this polls a few times before
+    /// asking the caller to try again; `None` is GitHub's 204 for a repository with no commits.
+    async fn get_repo_stat<T: DeserializeOwned>(
+        &self,
+        owner: &str,
+        repo: &str,
+        stat: &str,
+    ) -> Result<Option<T>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "stats", stat])?;
+
+        for attempt in 1..=STATS_POLL_ATTEMPTS {
+            let response = self.send(self.base_request(url.clone(), None)).await?;
+
+            let status = response.status();
+            match status {
+                StatusCode::ACCEPTED => {
+                    if attempt < STATS_POLL_ATTEMPTS {
+                        tokio::time::sleep(self.stats_poll_interval).await;
+                    }
+                    continue;
+                }
+                StatusCode::NO_CONTENT => return Ok(None),
+                _ if !status.is_success() => {
+                    return Err(ApiErrorBody::from_response(status, response).await);
+                }
+                _ => {}
+            }
+
+            return response
+                .json()
+                .await
+                .map(Some)
+                .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()));
+        }
+
+        Err(ApiErrorBody::new(
+            format!(
+                "GitHub is still computing {} statistics for {}/{}; try again shortly.",
+                stat, owner, repo
+            ),
+            StatusCode::ACCEPTED.as_u16(),
+        ))
+    }
+
+    /// Resolves where GitHub serves the zip archive of `ref` (the default branch when `None`)
+    /// without downloading it.
+    pub async fn archive_url(
+        &self,
+        owner: &str,
+        repo: &str,
+        r#ref: Option<&str>,
+    ) -> Result<String, ApiErrorBody> {
+        let mut segments = vec!["repos", owner, repo, "zipball"];
+        if let Some(r#ref) = r#ref {
+            segments.extend(r#ref.split('/').filter(|s| !s.is_empty()));
+        }
+        let url = self.build_url(&segments)?;
+
+        let response = self
+            .send_with(&self.no_redirect, self.base_request(url, None))
+            .await?;
+        let status = response.status();
+        if !status.is_redirection() {
+            if status.is_success() {
+                return Err(ApiErrorBody::new(
+                    "GitHub served the archive directly instead of redirecting to it.",
+                    status.as_u16(),
+                ));
+            }
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+
+        response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                ApiErrorBody::new(
+                    "GitHub redirected without a Location header.",
+                    status.as_u16(),
+                )
+            })
+    }
+
+    /// Runs a code search; `extra_params` are added to the query string as given, for search
+    /// parameters the tool does not model.
+    pub async fn search_code(
+        &self,
+        query: &str,
+        extra_params: &BTreeMap<String, String>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<SearchPage, ApiErrorBody> {
+        let url = self.build_url(&["search", "code"])?;
+
+        let request = with_pagination(
+            self.base_request(url, None)
+                .query(&[("q", query)])
+                .query(extra_params),
+            page,
+            per_page,
+        );
+
+        let (page, _) = self.fetch_search_page(request).await?;
+        Ok(page)
+    }
+
+    /// Counts issues and pull requests matching `query` from the search API's `total_count`,
+    /// fetching a single one-item page.
+    pub async fn search_issues_count(&self, query: &str) -> Result<u64, ApiErrorBody> {
+        let url = self.build_url(&["search", "issues"])?;
+        let request = with_pagination(
+            self.base_request(url, None).query(&[("q", query)]),
+            None,
+            Some(1),
+        );
+
+        let response = self.send_search(request).await?;
+        let status = response.status();
+        let body: GithubSearchCount = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        Ok(body.total_count)
+    }
+
+    /// Follows search pagination until every match, the API's 1000-result cap, or the page
+    /// cap is reached; pages are paced like every other search request.
+    pub async fn search_code_all(
+        &self,
+        query: &str,
+        extra_params: &BTreeMap<String, String>,
+        per_page: Option<usize>,
+    ) -> Result<SearchPage, ApiErrorBody> {
+        let url = self.build_url(&["search", "code"])?;
+        let per_page = per_page
+            .unwrap_or(SEARCH_MAX_PER_PAGE)
+            .clamp(1, SEARCH_MAX_PER_PAGE);
+
+        // GitHub's `next` links carry the whole query string, extra parameters included.
+        let mut request = with_pagination(
+            self.base_request(url, None)
+                .query(&[("q", query)])
+                .query(extra_params),
+            None,
+            Some(per_page),
+        );
+        let mut collected = SearchPage {
+            results: Vec::new(),
+            total_count: 0,
+            incomplete: false,
+        };
+
+        for _ in 0..SEARCH_MAX_PAGES {
+            let (body, next) = self.fetch_search_page(request).await?;
+            collected.total_count = body.total_count;
+            collected.incomplete |= body.incomplete;
+            collected.results.extend(body.results);
+
+            let reachable = usize::try_from(body.total_count)
+                .unwrap_or(usize::MAX)
+                .min(SEARCH_RESULT_CAP);
+            let Some(next) = next.filter(|_| collected.results.len() < reachable) else {
+                break;
+            };
+            request = self.base_request(next, None);
+        }
+
+        collected.results.truncate(SEARCH_RESULT_CAP);
+        Ok(collected)
+    }
+
+    pub async fn tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        depth: usize,
+        r#ref: Option<&str>,
+    ) -> Result<Vec<TreeEntry>, ApiErrorBody> {
+        let (entries, _) = self
+            .tree_within(owner, repo, path, depth, r#ref, CrawlLimits::default())
+            .await?;
+        Ok(entries)
+    }
+
+    /// [`Self::tree`] that stops listing once `limits` are reached, returning the entries
+    /// found so far and whether it stopped early; directories it did not open have no
+    /// children.
+    pub async fn tree_within(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        depth: usize,
+        r#ref: Option<&str>,
+        limits: CrawlLimits,
+    ) -> Result<(Vec<TreeEntry>, bool), ApiErrorBody> {
+        let listing = self.fetch_contents(owner, repo, path, r#ref);
+        let Some(contents) = before(limits.deadline, listing).await else {
+            return Ok((Vec::new(), true));
+        };
+        let contents = contents?;
+
+        let root_parent = match &contents {
+            GithubContents::Directory(_) => normalize_root_path(path),
+            GithubContents::File(file) => parent_path(&file.path),
+        };
+
+        self.expand_tree(owner, repo, contents, depth, r#ref, &root_parent, limits)
+            .await
+    }
+
+    /// Counts the files and directories directly under `path` with a single request.
+    pub async fn count_level(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+    ) -> Result<LevelCounts, ApiErrorBody> {
+        let mut counts = LevelCounts {
+            files: 0,
+            directories: 0,
+        };
+
+        match self.fetch_contents(owner, repo, path, r#ref).await? {
+            GithubContents::File(_) => counts.files = 1,
+            GithubContents::Directory(entries) => {
+                for entry in entries {
+                    match entry.r#type {
+                        GithubContentType::Dir => counts.directories += 1,
+                        GithubContentType::File | GithubContentType::Symlink => counts.files += 1,
+                        // Submodules are never expanded, so they cost no calls.
+                        GithubContentType::Submodule | GithubContentType::Unknown => {}
+                    }
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Sums file sizes under `path`, descending `depth` levels like [`Self::tree`] but
+    /// keeping only running totals. Paths for which `skip` returns true are left out along
+    /// with everything under them.
+    pub async fn dir_size(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        depth: usize,
+        r#ref: Option<&str>,
+        skip: impl Fn(&str) -> bool,
+    ) -> Result<DirSize, ApiErrorBody> {
+        let mut totals = DirSize::default();
+        let mut queue: VecDeque<(GithubContents, usize)> = VecDeque::new();
+
+        queue.push_back((self.fetch_contents(owner, repo, path, r#ref).await?, depth));
+
+        while let Some((node, remaining_depth)) = queue.pop_front() {
+            match node {
+                GithubContents::File(file) => {
+                    totals.files += 1;
+                    totals.bytes += file.size.unwrap_or(0);
+                }
+                GithubContents::Directory(entries) => {
+                    for entry in entries {
+                        if skip(&entry.path) {
+                            continue;
+                        }
+                        match entry.r#type {
+                            GithubContentType::Dir => {
+                                totals.directories += 1;
+                                if remaining_depth > 1 {
+                                    let nested = self
+                                        .fetch_contents(owner, repo, &entry.path, r#ref)
+                                        .await?;
+                                    queue.push_back((nested, remaining_depth - 1));
+                                }
+                            }
+                            GithubContentType::File | GithubContentType::Symlink => {
+                                totals.files += 1;
+                                totals.bytes += entry.size.unwrap_or(0);
+                            }
+                            // Submodule contents live in another repository.
+                            GithubContentType::Submodule | GithubContentType::Unknown => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(totals)
+    }
+
+    pub async fn get_stats(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+    ) -> Result<Stats, ApiErrorBody> {
+        let normalized_path = normalize_root_path(path);
+        let contents = self.fetch_contents(owner, repo, path, r#ref).await?;
+
+        match contents {
+            GithubContents::File(file) => Ok(file.into_stats()),
+            GithubContents::Directory(_) => Ok(directory_stats(&normalized_path)),
+        }
+    }
+
+    pub async fn get_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+    ) -> Result<String, ApiErrorBody> {
+        self.get_file_bytes(owner, repo, path, r#ref)
+            .await
+            .and_then(utf8_text)
+    }
+
+    /// [`Self::get_file`] without requiring the contents to be UTF-8 text.
+    pub async fn get_file_bytes(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+    ) -> Result<Vec<u8>, ApiErrorBody> {
+        self.read_path(owner, repo, path, r#ref).await?.into_file()
+    }
+
+    /// Asks GitHub whether `path` changed after `since`, an HTTP date; GitHub answers
+    /// `304 Not Modified` when it did not, which does not count against the rate limit.
+    pub async fn modified_since(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+        since: &str,
+    ) -> Result<Freshness, ApiErrorBody> {
+        let url = self.contents_url(owner, repo, path)?;
+        let request = self
+            .base_request(url, r#ref)
+            .header(IF_MODIFIED_SINCE, since);
+        let response = self.send(request).await?;
+        freshness(path, response).await
+    }
+
+    /// Asks GitHub whether `path` still carries `etag`, sent as `If-None-Match`; GitHub
+    /// answers `304 Not Modified` when it does. An empty `etag` just fetches the current one.
+    pub async fn etag_freshness(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+        etag: &str,
+    ) -> Result<Freshness, ApiErrorBody> {
+        let url = self.contents_url(owner, repo, path)?;
+        let mut request = self.base_request(url, r#ref);
+        if !etag.is_empty() {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = self.send(request).await?;
+        freshness(path, response).await
+    }
+
+    /// Reads a git blob's bytes by sha. Blobs GitHub will not inline (encoding `none`) are
+    /// fetched again through the raw media type, as for large files.
+    pub async fn get_blob(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Vec<u8>, ApiErrorBody> {
+        let url = self.build_url(&["repos", owner, repo, "git", "blobs", sha])?;
+        let response = self.send(self.base_request(url.clone(), None)).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+
+        let blob: GithubBlob = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        match blob.encoding.as_deref() {
+            Some("none") => self.fetch_raw_url(url, None).await,
+            Some("utf-8") => Ok(blob.content.unwrap_or_default().into_bytes()),
+            _ => decode_base64(blob.encoding, blob.content),
+        }
+    }
+
+    pub async fn read_path(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+    ) -> Result<PathContent, ApiErrorBody> {
+        let contents = self.fetch_contents(owner, repo, path, r#ref).await?;
+
+        match contents {
+            GithubContents::File(mut file) => match file.take_content() {
+                Some(content) => content,
+                // Files over 1 MB come back without content; the raw endpoint still serves them.
+                None => self
+                    .fetch_raw(owner, repo, path, r#ref)
+                    .await
+                    .map(PathContent::File),
+            },
+            GithubContents::Directory(_) => Err(ApiErrorBody::new(
+                "Requested path is a directory, not a file.",
+                "400",
+            )),
+        }
+    }
+
+    fn build_url(&self, segments: &[&str]) -> Result<Url, ApiErrorBody> {
+        let mut url = self.base_url.clone();
+        {
+            let mut parts = url
+                .path_segments_mut()
+                .map_err(|_| ApiErrorBody::new("API base URL is not valid for paths", "0"))?;
+            parts.pop_if_empty();
+            for segment in segments {
+                parts.push(segment);
+            }
+        }
+        Ok(url)
+    }
+
+    fn base_request(&self, url: Url, r#ref: Option<&str>) -> reqwest::RequestBuilder {
+        self.request_with_accept(url, r#ref, "application/vnd.github+json")
+    }
+
+    fn request_with_accept(
+        &self,
+        url: Url,
+        r#ref: Option<&str>,
+        accept: &str,
+    ) -> reqwest::RequestBuilder {
+        let mut builder = self
+            .http
+            .get(url)
+            .header("Accept", accept)
+            .header("X-GitHub-Api-Version", "2022-11-28");
+
+        if let Some(token) = &self.token {
+            builder = builder.bearer_auth(token);
+        }
+
+        for (name, value) in &self.extra_headers {
+            if !is_managed_header(name) {
+                builder = builder.header(name, value);
+            }
+        }
+
+        if let Some(r#ref) = r#ref {
+            builder = builder.query(&[("ref", normalize_ref(r#ref))]);
+        }
+
+        builder
+    }
+
+    /// Sends a request, retrying rate limits and upstream failures within the call's budget.
+    async fn send(
+        &self,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ApiErrorBody> {
+        self.send_with(&self.http, builder).await
+    }
+
+    /// [`Self::send`] through a specific HTTP client, e.g. one that does not follow redirects.
+    async fn send_with(
+        &self,
+        http: &Client,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ApiErrorBody> {
+        let mut request = builder.build().map_err(ApiErrorBody::from_reqwest)?;
+        assert_read_only(request.method(), request.url())?;
+        if let Some(app_auth) = &self.app_auth {
+            let token = app_auth.token(&self.http).await?;
+            let value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|err| ApiErrorBody::new(err.to_string(), "0"))?;
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+
+        // A caller's own conditional request must reach GitHub as is: with an ETag added,
+        // GitHub would ignore `If-Modified-Since`, and the caller's own ETag would be replaced.
+        let Some(cache) = self.etags.as_deref().filter(|_| {
+            request.method() == reqwest::Method::GET
+                && !request.headers().contains_key(IF_MODIFIED_SINCE)
+                && !request.headers().contains_key(IF_NONE_MATCH)
+        }) else {
+            return self.execute(http, request).await;
+        };
+
+        let key = EtagCache::key(&request);
+        let sent_etag = cache.etag(&key);
+        let mut conditional = request
+            .try_clone()
+            .ok_or_else(|| ApiErrorBody::new("Request body cannot be retried", "0"))?;
+        if let Some(etag) = sent_etag
+            .as_deref()
+            .and_then(|etag| HeaderValue::from_str(etag).ok())
+        {
+            conditional.headers_mut().insert(IF_NONE_MATCH, etag);
+        }
+
+        let response = self.execute(http, conditional).await?;
+        if response.status() != StatusCode::NOT_MODIFIED {
+            return self.remember(cache, key, response).await;
+        }
+
+        if let Some(cached) = sent_etag.and_then(|etag| cache.body(&key, &etag)) {
+            return Ok(cached.into_response());
+        }
+        // The body was evicted after its ETag was sent; a 304 alone has nothing to serve.
+        let response = self.execute(http, request).await?;
+        self.remember(cache, key, response).await
+    }
+
+    /// Caches a successful response that carries an ETag, handing back an equivalent one.
+    async fn remember(
+        &self,
+        cache: &EtagCache,
+        key: String,
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response, ApiErrorBody> {
+        let status = response.status();
+        let Some(etag) = response
+            .headers()
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .filter(|_| status == StatusCode::OK)
+        else {
+            return Ok(response);
+        };
+
+        let headers = response.headers().clone();
+        let url = response.url().clone();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        let cached = CachedBody {
+            etag,
+            url,
+            status,
+            headers,
+            body: body.to_vec(),
+        };
+        cache.insert(key, cached.clone());
+        Ok(cached.into_response())
+    }
+
+    /// Executes a built request, retrying rate limits and upstream failures within the
+    /// call's budget.
+    async fn execute(
+        &self,
+        http: &Client,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, ApiErrorBody> {
+        let budget = RetryBudget::current_or_new(&self.retry);
+        let category = self.resource_category(request.url());
+        let mut attempt = 0;
+
+        loop {
+            self.breaker.try_acquire().map_err(circuit_open_error)?;
+            match self.rate_limits.reserve(category) {
+                Ok(delay) if !delay.is_zero() => tokio::time::sleep(delay).await,
+                Ok(_) => {}
+                Err(wait) => return Err(quota_exhausted_error(category, wait)),
+            }
+
+            let pending = request
+                .try_clone()
+                .ok_or_else(|| ApiErrorBody::new("Request body cannot be retried", "0"))?;
+            // The permit covers only the exchange itself, body included, never a backoff or
+            // another request, so fan-outs waiting on each other cannot starve it.
+            let result = match &self.in_flight {
+                Some(in_flight) => {
+                    let _permit = in_flight
+                        .acquire()
+                        .await
+                        .map_err(|err| ApiErrorBody::new(err.to_string(), "0"))?;
+                    match http.execute(pending).await {
+                        Ok(response) => buffered(response).await,
+                        Err(err) => Err(err),
+                    }
+                }
+                None => http.execute(pending).await,
+            };
+            if let Ok(response) = &result {
+                self.rate_limits.observe(category, response.headers());
+            }
+
+            let upstream_failed = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(err) => err.is_connect() || err.is_timeout(),
+            };
+            if upstream_failed {
+                self.breaker.record_failure();
+            } else {
+                self.breaker.record_success();
+            }
+            let result = match result {
+                Ok(response) if response.status() == StatusCode::UNAUTHORIZED => {
+                    return Err(self.unauthorized_error(response).await);
+                }
+                other => other,
+            };
+
+            let delay = match &result {
+                Ok(response) if retry::is_retryable_status(response.status()) => {
+                    retry::retry_delay(Some(response.headers()), attempt, &self.retry)
+                }
+                Err(err) if err.is_connect() || err.is_timeout() => {
+                    retry::retry_delay(None, attempt, &self.retry)
+                }
+                _ => return result.map_err(ApiErrorBody::from_reqwest),
+            };
+
+            if !budget.try_spend(delay) {
+                return result.map_err(ApiErrorBody::from_reqwest);
+            }
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Explains a 401 in terms of how this client authenticates, since each mode is
+    /// fixed differently.
+    async fn unauthorized_error(&self, response: reqwest::Response) -> ApiErrorBody {
+        let mut err = ApiErrorBody::from_response(StatusCode::UNAUTHORIZED, response).await;
+        let hint = if self.app_auth.is_some() {
+            "GitHub rejected the GitHub App installation token; check that the App is still installed and its private key is current."
+        } else if self.token.is_some() {
+            "The provided GitHub token is invalid or expired; replace it and restart the server."
+        } else {
+            "GitHub requires authentication for this request; restart the server with a token or GitHub App credentials."
+        };
+        err.message = format!("{hint} ({})", err.message);
+        err
+    }
+
+    fn resource_category(&self, url: &Url) -> ResourceCategory {
+        let base_path = self.base_url.path().trim_end_matches('/');
+        let path = url.path();
+        ResourceCategory::for_path(path.strip_prefix(base_path).unwrap_or(path))
+    }
+
+    /// Sends a search request, turning GitHub's query validation failures into
+    /// `invalid_query` errors.
+    async fn send_search(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ApiErrorBody> {
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if status == StatusCode::UNPROCESSABLE_ENTITY {
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            return Err(error::invalid_search_query(&body)
+                .unwrap_or_else(|| ApiErrorBody::from_parts(status, &headers, &body)));
+        }
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+        Ok(response)
+    }
+
+    /// Fetches one page of code search results and the `next` link, if any.
+    async fn fetch_search_page(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<(SearchPage, Option<Url>), ApiErrorBody> {
+        let response = self.send_search(request).await?;
+        let status = response.status();
+
+        let next = next_link(response.headers());
+        let body: GithubSearchResponse = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        Ok((
+            SearchPage {
+                results: body.items.into_iter().map(Into::into).collect(),
+                total_count: body.total_count,
+                incomplete: body.incomplete_results,
+            },
+            next,
+        ))
+    }
+
+    async fn get_collection<T>(
+        &self,
+        url: Url,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Listing<String>, ApiErrorBody>
+    where
+        T: NamedItem + DeserializeOwned,
+    {
+        let listing = self.get_listing::<T>(url, page, per_page).await?;
+        Ok(listing.map(|item| item.name()))
+    }
+
+    /// Reads just the requested page when `page` or `per_page` is given, otherwise every page
+    /// up to the `max_list_pages` cap.
+    async fn get_listing<T: DeserializeOwned>(
+        &self,
+        url: Url,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Listing<T>, ApiErrorBody> {
+        if page.is_none() && per_page.is_none() {
+            return self.get_all_pages(url).await;
+        }
+
+        let request = with_pagination(self.base_request(url, None), page, per_page);
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+
+        let links = PageLinks {
+            next: link_page(response.headers(), "next"),
+            last: link_page(response.headers(), "last"),
+        };
+        let items = response
+            .json()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+        Ok(Listing {
+            items,
+            truncated: false,
+            links,
+        })
+    }
+
+    /// Follows `rel="next"` links from `url` until the last page or `max_list_pages`.
+    async fn get_all_pages<T: DeserializeOwned>(
+        &self,
+        url: Url,
+    ) -> Result<Listing<T>, ApiErrorBody> {
+        let mut request = with_pagination(self.base_request(url, None), None, Some(LIST_PER_PAGE));
+        let mut items = Vec::new();
+
+        for _ in 0..self.max_list_pages {
+            let response = self.send(request).await?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(ApiErrorBody::from_response(status, response).await);
+            }
+
+            let next = next_link(response.headers());
+            let page: Vec<T> = response
+                .json()
+                .await
+                .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+            items.extend(page);
+
+            let Some(next) = next else {
+                return Ok(Listing {
+                    items,
+                    truncated: false,
+                    links: PageLinks::default(),
+                });
+            };
+            request = self.base_request(next, None);
+        }
+
+        Ok(Listing {
+            items,
+            truncated: true,
+            links: PageLinks::default(),
+        })
+    }
+
+    fn contents_url(&self, owner: &str, repo: &str, path: &str) -> Result<Url, ApiErrorBody> {
+        let mut segments = vec!["repos", owner, repo, "contents"];
+        segments.extend(path.split('/').filter(|s| !s.is_empty()));
+        self.build_url(&segments)
+    }
+
+    /// Reads a file through the raw media type, which has no 1 MB limit unlike the JSON form.
+    async fn fetch_raw(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+    ) -> Result<Vec<u8>, ApiErrorBody> {
+        let url = self.contents_url(owner, repo, path)?;
+        self.fetch_raw_url(url, r#ref).await
+    }
+
+    /// Reads any contents or blob URL through the raw media type.
+    async fn fetch_raw_url(&self, url: Url, r#ref: Option<&str>) -> Result<Vec<u8>, ApiErrorBody> {
+        let request = self.request_with_accept(url, r#ref, "application/vnd.github.raw");
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        Ok(bytes.to_vec())
+    }
+
+    async fn fetch_contents(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+    ) -> Result<GithubContents, ApiErrorBody> {
+        let url = self.contents_url(owner, repo, path)?;
+
+        let response = self.send(self.base_request(url, r#ref)).await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(ApiErrorBody::from_response(status, response).await);
+        }
+        contents_body(response).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn expand_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        contents: GithubContents,
+        depth: usize,
+        r#ref: Option<&str>,
+        root_parent: &str,
+        limits: CrawlLimits,
+    ) -> Result<(Vec<TreeEntry>, bool), ApiErrorBody> {
+        let mut queue: VecDeque<(GithubContents, usize)> = VecDeque::new();
+        let mut children_by_parent: HashMap<String, Vec<TreeEntry>> = HashMap::new();
+        let mut listed = contents.len();
+        let mut stopped = false;
+
+        queue.push_back((contents, depth));
+
+        while let Some((node, remaining_depth)) = queue.pop_front() {
+            match node {
+                GithubContents::File(file) => {
+                    let parent = parent_path(&file.path);
+                    children_by_parent
+                        .entry(parent)
+                        .or_default()
+                        .push(file.into_tree_entry(Vec::new()));
+                }
+                GithubContents::Directory(entries) => {
+                    // Record the whole listing before opening subdirectories, so running
+                    // out of time never drops entries GitHub already returned.
+                    let mut subdirectories = Vec::new();
+                    for entry in entries {
+                        let parent = parent_path(&entry.path);
+                        if matches!(entry.r#type, GithubContentType::Dir) && remaining_depth > 1 {
+                            subdirectories.push(entry.path.clone());
+                        }
+
+                        children_by_parent
+                            .entry(parent)
+                            .or_default()
+                            .push(entry.into_tree_entry(Vec::new()));
+                    }
+
+                    for path in subdirectories {
+                        if limits.max_entries.is_some_and(|max| listed >= max) {
+                            stopped = true;
+                        }
+                        if stopped {
+                            break;
+                        }
+                        let nested = self.fetch_contents(owner, repo, &path, r#ref);
+                        let Some(nested_contents) = before(limits.deadline, nested).await else {
+                            stopped = true;
+                            break;
+                        };
+                        let nested_contents = nested_contents?;
+                        listed += nested_contents.len();
+                        queue.push_back((nested_contents, remaining_depth - 1));
+                    }
+                }
+            }
+        }
+
+        let entries = assemble_tree(&mut children_by_parent, root_parent, self.max_tree_nesting);
+        Ok((entries, stopped))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubRepo {
+    full_name: String,
+    description: Option<String>,
+    default_branch: String,
+    stargazers_count: u64,
+    forks_count: u64,
+    license: Option<GithubLicense>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubLicense {
+    key: Option<String>,
+    name: Option<String>,
+    spdx_id: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubLicenseContent {
+    path: String,
+    content: Option<String>,
+    encoding: Option<String>,
+    license: Option<GithubLicense>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubTag {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubBranch {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubBranchDetail {
+    name: String,
+    commit: GithubCommitRef,
+    protected: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubTagDetail {
+    name: String,
+    commit: GithubCommitRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCommitRef {
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubSearchResponse {
+    total_count: u64,
+    #[serde(default)]
+    incomplete_results: bool,
+    items: Vec<GithubSearchItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCompare {
+    merge_base_commit: GithubCommitRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubSearchCount {
+    total_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubSearchItem {
+    name: String,
+    path: String,
+    repository: GithubSearchRepo,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubSearchRepo {
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubIssue {
+    number: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+    comments: u64,
+    html_url: String,
+    created_at: String,
+    /// Present only when the issue is actually a pull request.
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubPull {
+    number: u64,
+    title: String,
+    state: String,
+    head: GithubPullBranch,
+    base: GithubPullBranch,
+    user: Option<GithubUser>,
+    #[serde(default)]
+    draft: bool,
+    html_url: String,
+    created_at: String,
+    merged_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubGist {
+    description: Option<String>,
+    files: BTreeMap<String, GithubGistFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubGistFile {
+    filename: String,
+    language: Option<String>,
+    /// Gists inline file text as is, not base64.
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubDeployment {
+    id: u64,
+    environment: String,
+    #[serde(rename = "ref")]
+    r#ref: String,
+    sha: String,
+    created_at: String,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubWeeklyCommits {
+    /// Unix timestamp of the Sunday starting the week.
+    week: i64,
+    total: u64,
+    days: Vec<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCollaborator {
+    login: String,
+    #[serde(default)]
+    permissions: CollaboratorPermissions,
+    role_name: Option<String>,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubBlob {
+    content: Option<String>,
+    encoding: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubMilestone {
+    number: u64,
+    title: String,
+    state: String,
+    open_issues: u64,
+    closed_issues: u64,
+    due_on: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubPullBranch {
+    #[serde(rename = "ref")]
+    r#ref: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubPullFile {
+    filename: String,
+    status: String,
+    additions: u64,
+    deletions: u64,
+    /// Omitted by GitHub for binary files and diffs too large to show.
+    patch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCommit {
+    sha: String,
+    commit: GithubCommitDetail,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCommitDetail {
+    message: String,
+    author: Option<GithubCommitAuthor>,
+    #[serde(default)]
+    committer: Option<GithubCommitAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCommitAuthor {
+    name: String,
+    date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubUser {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubLabel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubRepoSummary {
+    name: String,
+    full_name: String,
+    private: bool,
+    html_url: String,
+    description: Option<String>,
+    #[serde(default)]
+    fork: bool,
+    #[serde(default)]
+    archived: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubFile {
+    path: String,
+    #[serde(rename = "type")]
+    r#type: GithubContentType,
+    size: Option<u64>,
+    content: Option<String>,
+    encoding: Option<String>,
+    target: Option<String>,
+    submodule_git_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubDirectoryEntry {
+    #[serde(rename = "name")]
+    _name: String,
+    path: String,
+    #[serde(rename = "type")]
+    r#type: GithubContentType,
+    size: Option<u64>,
+    target: Option<String>,
+    submodule_git_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+enum GithubContentType {
+    File,
+    Dir,
+    Symlink,
+    Submodule,
+    /// A type GitHub added after this was written.
+    #[serde(other)]
+    Unknown,
+}
+
+impl GithubContentType {
+    fn to_entry_type(&self) -> EntryType {
+        match self {
+            GithubContentType::Dir => EntryType::Dir,
+            GithubContentType::File => EntryType::File,
+            GithubContentType::Symlink => EntryType::Symlink,
+            GithubContentType::Submodule => EntryType::Submodule,
+            GithubContentType::Unknown => EntryType::Unknown,
+        }
+    }
+}
+
+/// Whether `name` is set by the client itself and so cannot be supplied via custom headers.
+pub fn is_managed_header(name: &HeaderName) -> bool {
+    name == ACCEPT
+        || name == AUTHORIZATION
+        || name == USER_AGENT
+        || name.as_str() == "x-github-api-version"
+}
+
+/// Reads the root certificates in a PEM file, failing when it holds none.
+fn load_ca_certs(path: &Path) -> anyhow::Result<Vec<Certificate>> {
+    let pem = fs::read(path)
+        .with_context(|| format!("Failed to read CA certificate {}", path.display()))?;
+    let certs = Certificate::from_pem_bundle(&pem)
+        .with_context(|| format!("CA certificate {} is not valid PEM", path.display()))?;
+    if certs.is_empty() {
+        anyhow::bail!(
+            "CA certificate {} contains no PEM certificates",
+            path.display()
+        );
+    }
+    Ok(certs)
+}
+
+/// Loads the roots named by `SSL_CERT_FILE`, which rustls does not read on its own; an unset
+/// or empty variable adds nothing.
+fn env_ca_certs(value: Option<OsString>) -> anyhow::Result<Vec<Certificate>> {
+    let Some(path) = value.filter(|value| !value.is_empty()).map(PathBuf::from) else {
+        return Ok(Vec::new());
+    };
+    load_ca_certs(&path).with_context(|| {
+        format!(
+            "SSL_CERT_FILE points at {}, which is not a usable certificate file; fix or unset it",
+            path.display()
+        )
+    })
+}
+
+/// Decodes the base64 payload GitHub uses for file and license contents as UTF-8 text.
+fn decode_content(
+    encoding: Option<String>,
+    content: Option<String>,
+) -> Result<String, ApiErrorBody> {
+    decode_base64(encoding, content).and_then(utf8_text)
+}
+
+/// Reads file bytes as UTF-8, failing with [`ErrorKind::Binary`] when they are not.
+pub fn utf8_text(bytes: Vec<u8>) -> Result<String, ApiErrorBody> {
+    String::from_utf8(bytes)
+        .map_err(|err| ApiErrorBody::new(err.to_string(), "0").with_kind(ErrorKind::Binary))
+}
+
+fn decode_base64(
+    encoding: Option<String>,
+    content: Option<String>,
+) -> Result<Vec<u8>, ApiErrorBody> {
+    let encoding = encoding.unwrap_or_else(|| "base64".to_string());
+    if encoding != "base64" {
+        return Err(ApiErrorBody::new(
+            format!("Unsupported encoding: {}", encoding),
+            "0",
+        ));
+    }
+
+    let payload = content.ok_or_else(|| ApiErrorBody::new("File content missing", "0"))?;
+
+    STANDARD
+        .decode(payload.replace('\n', ""))
+        .map_err(|err| ApiErrorBody::new(err.to_string(), "0"))
+}
+
+/// Endpoints that may list an owner's repositories, in the order they are tried.
+fn owner_bases(owner_type: Option<OwnerType>) -> &'static [&'static str] {
+    match owner_type {
+        Some(OwnerType::User) => &["users"],
+        Some(OwnerType::Org) => &["orgs"],
+        None => &["users", "orgs"],
+    }
+}
+
+fn with_pagination(
+    request: reqwest::RequestBuilder,
+    page: Option<usize>,
+    per_page: Option<usize>,
+) -> reqwest::RequestBuilder {
+    let mut request = request;
+
+    if let Some(page) = page {
+        request = request.query(&[("page", &page.to_string())]);
+    }
+
+    if let Some(per_page) = per_page {
+        request = request.query(&[("per_page", &per_page.to_string())]);
+    }
+
+    request
+}
+
+/// Keeps the client from ever changing anything on GitHub: every API request goes through
+/// here, and only reads get through. Installation tokens are minted outside this path.
+fn assert_read_only(method: &reqwest::Method, url: &Url) -> Result<(), ApiErrorBody> {
+    let allowed = match *method {
+        reqwest::Method::GET | reqwest::Method::HEAD => true,
+        // Search endpoints only ever read.
+        reqwest::Method::POST => {
+            let path = url.path();
+            path.contains("/search/")
+                || READ_ONLY_POST_ENDPOINTS
+                    .iter()
+                    .any(|endpoint| path.ends_with(endpoint))
+        }
+        _ => false,
+    };
+    if allowed {
+        return Ok(());
+    }
+    Err(ApiErrorBody::new(
+        format!(
+            "Refusing {} {}: this server only reads from GitHub.",
+            method,
+            url.path()
+        ),
+        "405",
+    ))
+}
+
+/// Runs `future` to completion, or until `deadline` passes, when it yields `None`.
+async fn before<F: Future>(deadline: Option<Instant>, future: F) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+/// Reads the answer to a conditional contents request for `path`.
+async fn freshness(path: &str, response: reqwest::Response) -> Result<Freshness, ApiErrorBody> {
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(Freshness::Unchanged);
+    }
+    if !status.is_success() {
+        return Err(ApiErrorBody::from_response(status, response).await);
+    }
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let (stats, content) = match contents_body(response).await? {
+        GithubContents::File(mut file) => {
+            let content = file.take_content().transpose()?;
+            (file.into_stats(), content)
+        }
+        GithubContents::Directory(_) => (directory_stats(&normalize_root_path(path)), None),
+    };
+    Ok(Freshness::Changed {
+        etag,
+        stats: Some(stats),
+        content,
+    })
+}
+
+/// Parses a successful contents response, which lists a directory or describes one entry.
+async fn contents_body(response: reqwest::Response) -> Result<GithubContents, ApiErrorBody> {
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+    let value: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+
+    if value.is_array() {
+        let entries: Vec<GithubDirectoryEntry> = serde_json::from_value(value)
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        Ok(GithubContents::Directory(entries))
+    } else {
+        let file: GithubFile = serde_json::from_value(value)
+            .map_err(|err| ApiErrorBody::new(err.to_string(), status.as_u16()))?;
+        Ok(GithubContents::File(file))
+    }
+}
+
+/// Reads the whole body of `response` and hands back an equivalent response holding it,
+/// so the download counts against whatever limit was held while it ran.
+async fn buffered(response: reqwest::Response) -> reqwest::Result<reqwest::Response> {
+    let mut builder = http::Response::builder()
+        .status(response.status())
+        .version(response.version())
+        .url(response.url().clone());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    let body = response.bytes().await?;
+    Ok(builder
+        .body(body)
+        .expect("parts copied from a valid response")
+        .into())
+}
+
+fn circuit_open_error(wait: Duration) -> ApiErrorBody {
+    let secs = wait.as_secs().max(1);
+    let mut err = ApiErrorBody::new(
+        format!(
+            "GitHub appears to be unavailable, backing off for {}s.",
+            secs
+        ),
+        "503",
+    )
+    .with_kind(ErrorKind::Unavailable);
+    err.retry_after_secs = Some(secs);
+    err
+}
+
+fn quota_exhausted_error(category: ResourceCategory, wait: Duration) -> ApiErrorBody {
+    let secs = wait.as_secs().max(1);
+    let mut err = ApiErrorBody::new(
+        format!(
+            "GitHub {} rate limit is exhausted; it resets in {}s.",
+            category.as_str(),
+            secs
+        ),
+        "429",
+    )
+    .with_kind(ErrorKind::RateLimited);
+    err.retry_after_secs = Some(secs);
+    err
+}
+
+/// Extracts the `rel="next"` target from a `Link` header.
+fn next_link(headers: &HeaderMap) -> Option<Url> {
+    link_target(headers, "next")
+}
+
+/// The `page` query parameter of the `Link` target for `rel`.
+fn link_page(headers: &HeaderMap, rel: &str) -> Option<usize> {
+    link_target(headers, rel)?
+        .query_pairs()
+        .find(|(name, _)| name == "page")?
+        .1
+        .parse()
+        .ok()
+}
+
+fn link_target(headers: &HeaderMap, rel: &str) -> Option<Url> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+    let wanted = format!("rel=\"{}\"", rel);
+
+    link.split(',').find_map(|part| {
+        let (target, params) = part.split_once(';')?;
+        if !params.split(';').any(|param| param.trim() == wanted) {
+            return None;
+        }
+
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        Url::parse(target).ok()
+    })
+}
+
+fn entry_name(path: &str) -> String {
+    path.rsplit('/').next().unwrap_or(path).to_string()
+}
+
+fn directory_stats(path: &str) -> Stats {
+    let normalized = path.to_string();
+    Stats {
+        r#type: EntryType::Dir,
+        name: entry_name(&normalized),
+        path: normalized,
+        size: None,
+        target: None,
+        submodule_git_url: None,
+        target_type: None,
+    }
+}
+
+fn parent_path(path: &str) -> String {
+    path.rsplit_once('/')
+        .map(|(parent, _)| parent.to_string())
+        .unwrap_or_default()
+}
+
+fn normalize_root_path(path: &str) -> String {
+    path.trim_matches('/').to_string()
+}
+
+/// Strips a fully-qualified `refs/heads/` or `refs/tags/` prefix, which the contents API does
+/// not always accept; anything else, shas included, passes through unchanged.
+pub fn normalize_ref(r#ref: &str) -> &str {
+    r#ref
+        .strip_prefix("refs/heads/")
+        .or_else(|| r#ref.strip_prefix("refs/tags/"))
+        .unwrap_or(r#ref)
+}
+
+impl GithubFile {
+    fn into_tree_entry(self, children: Vec<TreeEntry>) -> TreeEntry {
+        let r#type = self.r#type.to_entry_type();
+
+        TreeEntry {
+            r#type,
+            name: entry_name(&self.path),
+            path: self.path,
+            rel_path: None,
+            size: match r#type {
+                EntryType::Dir | EntryType::Submodule => None,
+                _ => self.size,
+            },
+            target: self.target,
+            submodule_git_url: self.submodule_git_url,
+            children,
+        }
+    }
+
+    /// What the file holds, or `None` when GitHub left it out for being over 1 MB. Takes
+    /// the encoded content out, leaving the rest for [`Self::into_stats`].
+    fn take_content(&mut self) -> Option<Result<PathContent, ApiErrorBody>> {
+        if matches!(self.r#type, GithubContentType::Symlink) {
+            return Some(Ok(PathContent::Symlink {
+                target: self.target.clone().unwrap_or_default(),
+            }));
+        }
+        if self.encoding.as_deref() == Some("none") {
+            return None;
+        }
+        Some(decode_base64(self.encoding.take(), self.content.take()).map(PathContent::File))
+    }
+
+    fn into_stats(self) -> Stats {
+        let r#type = self.r#type.to_entry_type();
+
+        Stats {
+            r#type,
+            name: entry_name(&self.path),
+            path: self.path,
+            size: match r#type {
+                EntryType::Dir | EntryType::Submodule => None,
+                _ => self.size,
+            },
+            target: self.target,
+            submodule_git_url: self.submodule_git_url,
+            target_type: None,
+        }
+    }
+}
+
+impl GithubDirectoryEntry {
+    fn into_tree_entry(self, children: Vec<TreeEntry>) -> TreeEntry {
+        let r#type = self.r#type.to_entry_type();
+
+        TreeEntry {
+            r#type,
+            name: entry_name(&self.path),
+            path: self.path,
+            rel_path: None,
+            size: match r#type {
+                EntryType::Dir | EntryType::Submodule => None,
+                _ => self.size,
+            },
+            target: self.target,
+            submodule_git_url: self.submodule_git_url,
+            children,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum GithubContents {
+    File(GithubFile),
+    Directory(Vec<GithubDirectoryEntry>),
+}
+
+impl GithubContents {
+    /// Entries this response adds to a tree: the file itself, or each listed entry.
+    fn len(&self) -> usize {
+        match self {
+            GithubContents::File(_) => 1,
+            GithubContents::Directory(entries) => entries.len(),
+        }
+    }
+}
+
+trait NamedItem {
+    fn name(self) -> String;
+}
+
+impl NamedItem for GithubTag {
+    fn name(self) -> String {
+        self.name
+    }
+}
+
+impl NamedItem for GithubBranch {
+    fn name(self) -> String {
+        self.name
+    }
+}
+
+impl From<GithubBranchDetail> for BranchDetail {
+    fn from(branch: GithubBranchDetail) -> Self {
+        BranchDetail {
+            name: branch.name,
+            commit_sha: branch.commit.sha,
+            protected: branch.protected,
+        }
+    }
+}
+
+impl From<GithubTagDetail> for TagDetail {
+    fn from(tag: GithubTagDetail) -> Self {
+        TagDetail {
+            name: tag.name,
+            commit_sha: tag.commit.sha,
+            date: None,
+        }
+    }
+}
+
+impl From<GithubRepo> for RepoInfo {
+    fn from(repo: GithubRepo) -> Self {
+        RepoInfo {
+            description: repo.description,
+            stars: repo.stargazers_count,
+            forks: repo.forks_count,
+            license: repo.license.map(Into::into),
+        }
+    }
+}
+
+impl From<GithubLicense> for LicenseInfo {
+    fn from(license: GithubLicense) -> Self {
+        LicenseInfo {
+            key: license.key,
+            name: license.name,
+            spdx_id: license.spdx_id,
+            url: license.url,
+        }
+    }
+}
+
+impl From<GithubSearchItem> for SearchResult {
+    fn from(item: GithubSearchItem) -> Self {
+        SearchResult {
+            name: item.name,
+            path: item.path,
+            repository: item.repository.full_name,
+        }
+    }
+}
+
+impl From<GithubIssue> for IssueSummary {
+    fn from(issue: GithubIssue) -> Self {
+        IssueSummary {
+            number: issue.number,
+            title: issue.title,
+            state: issue.state,
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            comments: issue.comments,
+            html_url: issue.html_url,
+            created_at: issue.created_at,
+            is_pull_request: issue.pull_request.is_some(),
+        }
+    }
+}
+
+impl From<GithubPullFile> for ChangedFile {
+    fn from(file: GithubPullFile) -> Self {
+        ChangedFile {
+            filename: file.filename,
+            status: file.status,
+            additions: file.additions,
+            deletions: file.deletions,
+            patch: file.patch,
+        }
+    }
+}
+
+impl From<GithubCommit> for CommitSummary {
+    fn from(commit: GithubCommit) -> Self {
+        let (author, date) = commit
+            .commit
+            .author
+            .map(|author| (author.name, author.date))
+            .unwrap_or_default();
+
+        CommitSummary {
+            sha: commit.sha,
+            summary: commit
+                .commit
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            author,
+            date,
+            html_url: commit.html_url,
+        }
+    }
+}
+
+impl From<GithubGist> for Gist {
+    fn from(gist: GithubGist) -> Self {
+        Gist {
+            description: gist.description.filter(|text| !text.is_empty()),
+            files: gist
+                .files
+                .into_values()
+                .map(|file| GistFile {
+                    filename: file.filename,
+                    language: file.language,
+                    content: file.content,
+                    truncated: file.truncated,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<GithubDeployment> for Deployment {
+    fn from(deployment: GithubDeployment) -> Self {
+        Deployment {
+            id: deployment.id,
+            environment: deployment.environment,
+            r#ref: deployment.r#ref,
+            sha: deployment.sha,
+            created_at: deployment.created_at,
+            description: deployment.description.filter(|text| !text.is_empty()),
+        }
+    }
+}
+
+impl From<GithubWeeklyCommits> for WeeklyCommits {
+    fn from(week: GithubWeeklyCommits) -> Self {
+        WeeklyCommits {
+            week: DateTime::from_timestamp(week.week, 0)
+                .map(|start| start.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            total: week.total,
+            days: week.days,
+        }
+    }
+}
+
+impl From<GithubCollaborator> for Collaborator {
+    fn from(collaborator: GithubCollaborator) -> Self {
+        Collaborator {
+            login: collaborator.login,
+            permissions: collaborator.permissions,
+            role_name: collaborator.role_name,
+            html_url: collaborator.html_url,
+        }
+    }
+}
+
+impl From<GithubMilestone> for Milestone {
+    fn from(milestone: GithubMilestone) -> Self {
+        Milestone {
+            number: milestone.number,
+            title: milestone.title,
+            state: milestone.state,
+            open_issues: milestone.open_issues,
+            closed_issues: milestone.closed_issues,
+            due_on: milestone.due_on,
+            description: milestone.description.filter(|text| !text.is_empty()),
+        }
+    }
+}
+
+impl From<GithubPull> for PullSummary {
+    fn from(pull: GithubPull) -> Self {
+        // GitHub reports merged pull requests as `closed`; `merged_at` tells them apart.
+        let state = if pull.merged_at.is_some() {
+            "merged".to_string()
+        } else {
+            pull.state
+        };
+
+        PullSummary {
+            number: pull.number,
+            title: pull.title,
+            state,
+            head_ref: pull.head.r#ref,
+            base_ref: pull.base.r#ref,
+            // Deleted accounts come back as `null`; GitHub shows them as "ghost".
+            user: pull
+                .user
+                .map_or_else(|| "ghost".to_string(), |user| user.login),
+            draft: pull.draft,
+            html_url: pull.html_url,
+            created_at: pull.created_at,
+        }
+    }
+}
+
+impl From<GithubRepoSummary> for RepoSummary {
+    fn from(repo: GithubRepoSummary) -> Self {
+        RepoSummary {
+            name: repo.name,
+            full_name: repo.full_name,
+            private: repo.private,
+            description: repo.description,
+            html_url: repo.html_url,
+            fork: repo.fork,
+            archived: repo.archived,
+        }
+    }
+}
+
+/// Sort rank of an entry type within a directory listing: folders first, then files.
+fn type_rank(entry_type: EntryType) -> u8 {
+    match entry_type {
+        EntryType::Dir => 0,
+        EntryType::File => 1,
+        EntryType::Symlink => 2,
+        EntryType::Submodule => 3,
+        EntryType::Unknown => 4,
+    }
+}
+
+/// Removes and returns the children listed under `parent`, ordered by `(type_rank, name)`
+/// so the output never depends on the order in which listings were fetched.
+fn take_sorted(
+    children_by_parent: &mut HashMap<String, Vec<TreeEntry>>,
+    parent: &str,
+) -> Vec<TreeEntry> {
+    let mut entries = children_by_parent.remove(parent).unwrap_or_default();
+    entries.sort_by(|a, b| (type_rank(a.r#type), &a.name).cmp(&(type_rank(b.r#type), &b.name)));
+    entries
+}
+
+/// Builds the nested tree under `parent` without recursion, so pathological depths cannot
+/// overflow the stack. Directories at nesting level `max_nesting` (top-level entries are
+/// level 1) get their whole subtree as a flat, path-named list instead of nested children.
+fn assemble_tree(
+    children_by_parent: &mut HashMap<String, Vec<TreeEntry>>,
+    parent: &str,
+    max_nesting: usize,
+) -> Vec<TreeEntry> {
+    // Listings in pre-order, so every directory's listing comes after its parent's.
+    let mut levels: Vec<(String, Vec<TreeEntry>)> = Vec::new();
+    let mut pending = vec![(parent.to_string(), 1)];
+
+    while let Some((parent, nesting)) = pending.pop() {
+        let mut entries = take_sorted(children_by_parent, &parent);
+        for entry in entries.iter_mut() {
+            if !matches!(entry.r#type, EntryType::Dir) {
+                continue;
+            }
+            if nesting >= max_nesting {
+                entry.children = flatten_subtree(children_by_parent, &entry.path);
+            } else {
+                pending.push((entry.path.clone(), nesting + 1));
+            }
+        }
+        levels.push((parent, entries));
+    }
+
+    // Attach listings bottom-up; children are always complete before their parent.
+    let mut assembled: HashMap<String, Vec<TreeEntry>> = HashMap::new();
+    while let Some((parent, mut entries)) = levels.pop() {
+        for entry in entries.iter_mut() {
+            if let Some(children) = assembled.remove(&entry.path) {
+                entry.children = children;
+            }
+        }
+        assembled.insert(parent, entries);
+    }
+
+    assembled.remove(parent).unwrap_or_default()
+}
+
+/// Every entry below `dir` in tree order, each named by its path relative to `dir`.
+fn flatten_subtree(
+    children_by_parent: &mut HashMap<String, Vec<TreeEntry>>,
+    dir: &str,
+) -> Vec<TreeEntry> {
+    let mut flat = Vec::new();
+    let mut stack: Vec<TreeEntry> = take_sorted(children_by_parent, dir)
+        .into_iter()
+        .rev()
+        .collect();
+
+    while let Some(mut entry) = stack.pop() {
+        if matches!(entry.r#type, EntryType::Dir) {
+            stack.extend(
+                take_sorted(children_by_parent, &entry.path)
+                    .into_iter()
+                    .rev(),
+            );
+        }
+        if let Some(relative) = entry
+            .path
+            .strip_prefix(dir)
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            entry.name = relative.to_string();
+        }
+        flat.push(entry);
+    }
+
+    flat
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path, query_param, query_param_is_missing},
+    };
+
+    use super::*;
+
+    fn mock_client(server: &MockServer, options: ClientOptions) -> GithubClient {
+        GithubClient::with_options(server.uri(), None, options).unwrap()
+    }
+
+    #[test]
+    fn overrides_the_api_base_with_http_urls_only() {
+        let client = GithubClient::new("https://api.github.com".to_string(), None).unwrap();
+
+        let enterprise = client
+            .with_api_base("https://ghe.example.com/api/v3/")
+            .unwrap();
+        assert_eq!(enterprise.api_base(), "https://ghe.example.com/api/v3");
+        assert_eq!(client.api_base(), "https://api.github.com/");
+
+        for base in ["ftp://ghe.example.com", "file:///etc", "not a url"] {
+            let Err(err) = client.with_api_base(base) else {
+                panic!("{} should be rejected", base);
+            };
+            assert_eq!(err.code, "400");
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_requests_that_could_write() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/octo/repo/issues"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let client = mock_client(&server, ClientOptions::default());
+        let url = |path: &str| Url::parse(&format!("{}{}", server.uri(), path)).unwrap();
+
+        let err = client
+            .send(client.http.delete(url("/repos/octo/repo")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, "405");
+        assert!(err.message.contains("DELETE /repos/octo/repo"));
+
+        let err = client
+            .send(client.http.post(url("/repos/octo/repo/issues")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, "405");
+    }
+
+    #[test]
+    fn allows_reads_and_read_only_posts() {
+        let url =
+            |path: &str| Url::parse(&format!("https://ghe.example.com/api/v3{}", path)).unwrap();
+
+        assert!(assert_read_only(&reqwest::Method::GET, &url("/repos/octo/repo")).is_ok());
+        assert!(assert_read_only(&reqwest::Method::POST, &url("/markdown")).is_ok());
+        assert!(assert_read_only(&reqwest::Method::POST, &url("/search/code")).is_ok());
+        assert!(assert_read_only(&reqwest::Method::POST, &url("/repos/octo/repo/forks")).is_err());
+        assert!(assert_read_only(&reqwest::Method::PATCH, &url("/repos/octo/repo")).is_err());
+        assert!(assert_read_only(&reqwest::Method::PUT, &url("/user/starred/octo/repo")).is_err());
+    }
+
+    #[test]
+    fn normalize_ref_strips_branch_and_tag_prefixes() {
+        assert_eq!(normalize_ref("refs/heads/main"), "main");
+        assert_eq!(normalize_ref("refs/heads/feature/login"), "feature/login");
+        assert_eq!(normalize_ref("refs/tags/v1.2.0"), "v1.2.0");
+        assert_eq!(normalize_ref("refs/pull/7/head"), "refs/pull/7/head");
+        assert_eq!(
+            normalize_ref("3f786850e387550fdab836ed7e6dc881de23001b"),
+            "3f786850e387550fdab836ed7e6dc881de23001b"
+        );
+    }
+
+    fn fast_retry(max_retries: u32, max_wait_ms: u64) -> ClientOptions {
+        ClientOptions {
+            retry: RetryPolicy {
+                max_retries,
+                max_wait: Duration::from_millis(max_wait_ms),
+                base_delay: Duration::from_millis(20),
+            },
+            // Retry tests replay long runs of 503s that would otherwise trip the breaker.
+            breaker: BreakerPolicy {
+                failure_threshold: u32::MAX,
+                ..BreakerPolicy::default()
+            },
+            ..ClientOptions::default()
+        }
+    }
+
+    fn search_body(total_count: u64, paths: &[&str]) -> serde_json::Value {
+        let items: Vec<_> = paths
+            .iter()
+            .map(|path| {
+                serde_json::json!({
+                    "name": entry_name(path),
+                    "path": path,
+                    "repository": { "full_name": "octo/repo" },
+                })
+            })
+            .collect();
+
+        serde_json::json!({ "total_count": total_count, "items": items })
+    }
+
+    fn repo_body(full_name: &str) -> serde_json::Value {
+        serde_json::json!({
+            "full_name": full_name,
+            "description": null,
+            "default_branch": "main",
+            "stargazers_count": 3,
+            "forks_count": 1,
+            "license": null,
+        })
+    }
+
+    #[tokio::test]
+    async fn gists_are_fetched_under_an_enterprise_api_base() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/gists/aa5a315d"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "description": "",
+                "files": {}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = GithubClient::with_options(
+            format!("{}/api/v3/", server.uri()),
+            None,
+            ClientOptions::default(),
+        )
+        .unwrap();
+        let gist = client.get_gist("aa5a315d").await.unwrap();
+
+        assert_eq!(gist.description, None);
+        assert!(gist.files.is_empty());
+    }
+
+    #[test]
+    fn gists_list_every_file_with_inline_content() {
+        let gist: GithubGist = serde_json::from_value(serde_json::json!({
+            "id": "aa5a315d61ae9438b18d",
+            "description": "Hello world examples",
+            "public": true,
+            "files": {
+                "hello_world.rb": {
+                    "filename": "hello_world.rb",
+                    "type": "application/x-ruby",
+                    "language": "Ruby",
+                    "raw_url": "https://gist.githubusercontent.com/octocat/aa5a315d61ae9438b18d/raw/hello_world.rb",
+                    "size": 29,
+                    "truncated": false,
+                    "content": "puts \"Hello world\"\n"
+                },
+                "README": {
+                    "filename": "README",
+                    "type": "text/plain",
+                    "language": null,
+                    "size": 8,
+                    "content": "Run it.\n"
+                }
+            }
+        }))
+        .unwrap();
+
+        let gist = Gist::from(gist);
+        assert_eq!(gist.description.as_deref(), Some("Hello world examples"));
+        let files: Vec<(&str, Option<&str>, &str)> = gist
+            .files
+            .iter()
+            .map(|file| {
+                (
+                    file.filename.as_str(),
+                    file.language.as_deref(),
+                    file.content.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("README", None, "Run it.\n"),
+                ("hello_world.rb", Some("Ruby"), "puts \"Hello world\"\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn deployments_keep_ref_sha_and_description() {
+        let deployments: Vec<GithubDeployment> = serde_json::from_value(serde_json::json!([
+            {
+                "id": 1299,
+                "node_id": "MDEwOkRlcGxveW1lbnQx",
+                "sha": "a84d88e7554fc1fa21bcbc4efae3c782a70d2b9d",
+                "ref": "topic-branch",
+                "task": "deploy",
+                "payload": {},
+                "environment": "production",
+                "description": "Deploy request from hubot",
+                "creator": { "login": "octocat" },
+                "created_at": "2012-07-20T01:19:13Z",
+                "updated_at": "2012-07-20T01:19:13Z"
+            },
+            {
+                "id": 1300,
+                "sha": "b84d88e7554fc1fa21bcbc4efae3c782a70d2b9d",
+                "ref": "main",
+                "environment": "staging",
+                "description": "",
+                "created_at": "2012-07-21T08:00:00Z"
+            }
+        ]))
+        .unwrap();
+
+        let deployments: Vec<Deployment> = deployments.into_iter().map(Into::into).collect();
+        assert_eq!(deployments[0].id, 1299);
+        assert_eq!(deployments[0].environment, "production");
+        assert_eq!(deployments[0].r#ref, "topic-branch");
+        assert_eq!(
+            deployments[0].sha,
+            "a84d88e7554fc1fa21bcbc4efae3c782a70d2b9d"
+        );
+        assert_eq!(
+            deployments[0].description.as_deref(),
+            Some("Deploy request from hubot")
+        );
+        assert_eq!(deployments[1].description, None);
+    }
+
+    #[test]
+    fn collaborators_keep_permissions_and_role() {
+        let collaborators: Vec<GithubCollaborator> = serde_json::from_value(serde_json::json!([
+            {
+                "login": "octocat",
+                "id": 1,
+                "html_url": "https://github.com/octocat",
+                "type": "User",
+                "site_admin": false,
+                "permissions": {
+                    "pull": true,
+                    "triage": true,
+                    "push": true,
+                    "maintain": false,
+                    "admin": false
+                },
+                "role_name": "write"
+            },
+            {
+                "login": "hubot",
+                "html_url": "https://github.com/hubot",
+                "permissions": { "pull": true }
+            }
+        ]))
+        .unwrap();
+
+        let collaborators: Vec<Collaborator> = collaborators.into_iter().map(Into::into).collect();
+        assert_eq!(collaborators[0].login, "octocat");
+        assert!(collaborators[0].permissions.push);
+        assert!(collaborators[0].permissions.triage);
+        assert!(!collaborators[0].permissions.admin);
+        assert_eq!(collaborators[0].role_name.as_deref(), Some("write"));
+        assert!(collaborators[1].permissions.pull);
+        assert!(!collaborators[1].permissions.push);
+        assert_eq!(collaborators[1].role_name, None);
+    }
+
+    #[tokio::test]
+    async fn list_collaborators_explains_missing_access() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/collaborators"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+                "message": "Must have push access to view repository collaborators."
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let err = client
+            .list_collaborators("octo", "repo", None, None, None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, "403");
+        assert!(
+            err.message
+                .starts_with("Listing collaborators of octo/repo needs a token")
+        );
+        assert!(err.message.contains("Must have push access"));
+    }
+
+    #[test]
+    fn milestones_keep_counts_and_due_dates() {
+        let milestones: Vec<GithubMilestone> = serde_json::from_value(serde_json::json!([
+            {
+                "url": "https://api.github.com/repos/octocat/Hello-World/milestones/1",
+                "id": 1002604,
+                "number": 1,
+                "state": "open",
+                "title": "v1.0",
+                "description": "Tracking milestone for version 1.0",
+                "creator": { "login": "octocat" },
+                "open_issues": 4,
+                "closed_issues": 8,
+                "created_at": "2011-04-10T20:09:31Z",
+                "due_on": "2012-10-09T23:39:01Z",
+                "closed_at": null
+            },
+            {
+                "number": 2,
+                "state": "open",
+                "title": "Someday",
+                "description": null,
+                "open_issues": 0,
+                "closed_issues": 0,
+                "due_on": null
+            }
+        ]))
+        .unwrap();
+
+        let milestones: Vec<Milestone> = milestones.into_iter().map(Into::into).collect();
+        assert_eq!(milestones[0].number, 1);
+        assert_eq!(milestones[0].title, "v1.0");
+        assert_eq!(milestones[0].open_issues, 4);
+        assert_eq!(milestones[0].closed_issues, 8);
+        assert_eq!(
+            milestones[0].due_on.as_deref(),
+            Some("2012-10-09T23:39:01Z")
+        );
+        assert_eq!(milestones[1].due_on, None);
+        assert_eq!(milestones[1].description, None);
+    }
+
+    #[tokio::test]
+    async fn list_milestones_reports_disabled_issues_as_none() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/milestones"))
+            .respond_with(ResponseTemplate::new(410).set_body_json(
+                serde_json::json!({ "message": "Issues are disabled for this repo" }),
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let milestones = client
+            .list_milestones("octo", "repo", None, None, None)
+            .await
+            .unwrap();
+
+        assert!(milestones.is_none());
+    }
+
+    #[test]
+    fn branch_details_carry_head_sha_and_protection() {
+        let branch: GithubBranchDetail = serde_json::from_value(serde_json::json!({
+            "name": "main",
+            "commit": {
+                "sha": "c5b97d5ae6c19d5c5df71a34c7fbeeda2479ccbc",
+                "url": "https://api.github.com/repos/octo/repo/commits/c5b97d5"
+            },
+            "protected": true
+        }))
+        .unwrap();
+
+        let detail = BranchDetail::from(branch);
+        assert_eq!(detail.name, "main");
+        assert_eq!(
+            detail.commit_sha,
+            "c5b97d5ae6c19d5c5df71a34c7fbeeda2479ccbc"
+        );
+        assert!(detail.protected);
+    }
+
+    #[tokio::test]
+    async fn commit_diff_requests_raw_diff_media_type() {
+        let server = MockServer::start().await;
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n";
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits/feature/x"))
+            .and(wiremock::matchers::header(
+                "accept",
+                "application/vnd.github.diff",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(diff))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let text = client
+            .commit_diff("octo", "repo", "feature/x", DiffFormat::Diff)
+            .await
+            .unwrap();
+
+        assert_eq!(text, diff);
+    }
+
+    #[tokio::test]
+    async fn search_requests_track_the_search_quota_not_core() {
+        let server = MockServer::start().await;
+        let reset = (std::time::SystemTime::now() + Duration::from_secs(600))
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string();
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-ratelimit-remaining", "0")
+                    .insert_header("x-ratelimit-reset", reset.as_str())
+                    .set_body_json(repo_body("octo/repo")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search/code"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-ratelimit-remaining", "0")
+                    .insert_header("x-ratelimit-reset", reset.as_str())
+                    .set_body_json(search_body(1, &["src/a.rs"])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(
+            &server,
+            ClientOptions {
+                search_interval: Duration::ZERO,
+                ..ClientOptions::default()
+            },
+        );
+
+        // An exhausted core quota does not hold back search, which has its own.
+        client.get_repo("octo", "repo").await.unwrap();
+        client
+            .search_code("fn", &BTreeMap::new(), None, None)
+            .await
+            .unwrap();
+
+        for err in [
+            client
+                .search_code("fn", &BTreeMap::new(), None, None)
+                .await
+                .unwrap_err(),
+            client.get_repo("octo", "repo").await.unwrap_err(),
+        ] {
+            assert_eq!(err.kind, Some(ErrorKind::RateLimited));
+            assert!(err.retry_after_secs.unwrap() > 590);
+        }
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_on_repeated_outages_and_recovers_after_cooldown() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(repo_body("octo/repo")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(
+            &server,
+            ClientOptions {
+                breaker: BreakerPolicy {
+                    failure_threshold: 2,
+                    window: Duration::from_secs(60),
+                    cooldown: Duration::from_millis(200),
+                },
+                ..fast_retry(0, 0)
+            },
+        );
+
+        for _ in 0..2 {
+            let err = client.get_repo("octo", "repo").await.unwrap_err();
+            assert_eq!(err.code, "503");
+            assert_eq!(err.kind, None);
+        }
+
+        let err = client.get_repo("octo", "repo").await.unwrap_err();
+        assert_eq!(err.kind, Some(ErrorKind::Unavailable));
+        assert!(err.message.contains("backing off"), "{}", err.message);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(client.get_repo("octo", "repo").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn app_auth_sends_installation_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/app/installations/7/access_tokens"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "token": "ghs_installation",
+                "expires_at": "2999-01-01T00:00:00Z",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .and(wiremock::matchers::header(
+                "authorization",
+                "Bearer ghs_installation",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(repo_body("octo/repo")))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = GithubClient::with_options(
+            server.uri(),
+            Some("ghp_ignored".to_string()),
+            ClientOptions {
+                app: Some(AppCredentials {
+                    app_id: "1".to_string(),
+                    installation_id: 7,
+                    private_key: include_bytes!("testdata/app-key.pem").to_vec(),
+                }),
+                ..ClientOptions::default()
+            },
+        )
+        .unwrap();
+
+        client.get_repo("octo", "repo").await.unwrap();
+        client.get_repo("octo", "repo").await.unwrap();
+    }
+
+    #[test]
+    fn ssl_cert_file_env_is_loaded_or_explained() {
+        assert!(env_ca_certs(None).unwrap().is_empty());
+        assert!(env_ca_certs(Some(OsString::new())).unwrap().is_empty());
+
+        let Err(err) = env_ca_certs(Some(OsString::from("/nonexistent/bundle.pem"))) else {
+            panic!("missing SSL_CERT_FILE target was accepted");
+        };
+        let message = format!("{err:#}");
+        assert!(message.starts_with("SSL_CERT_FILE points at /nonexistent/bundle.pem"));
+        assert!(message.contains("Failed to read CA certificate /nonexistent/bundle.pem"));
+    }
+
+    #[test]
+    fn unreadable_ca_cert_fails_client_construction() {
+        let options = |path: &str| ClientOptions {
+            ca_cert: Some(PathBuf::from(path)),
+            ..ClientOptions::default()
+        };
+
+        let Err(err) = GithubClient::with_options(
+            "https://api.github.com".to_string(),
+            None,
+            options("/nonexistent/proxy-ca.pem"),
+        ) else {
+            panic!("client built without its CA certificate");
+        };
+        let message = format!("{err:#}");
+        assert!(message.contains("Failed to read CA certificate /nonexistent/proxy-ca.pem"));
+
+        // A PEM file holding only a private key has no certificate to trust.
+        let key = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/client/testdata/app-key.pem"
+        );
+        let Err(err) =
+            GithubClient::with_options("https://api.github.com".to_string(), None, options(key))
+        else {
+            panic!("client built from a PEM file without certificates");
+        };
+        assert!(err.to_string().contains("contains no PEM certificates"));
+    }
+
+    #[tokio::test]
+    async fn rejected_token_reports_an_unauthorized_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .set_body_json(serde_json::json!({ "message": "Bad credentials" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = GithubClient::with_options(
+            server.uri(),
+            Some("ghp_revoked".to_string()),
+            ClientOptions::default(),
+        )
+        .unwrap();
+        let err = client.get_repo("octo", "repo").await.unwrap_err();
+
+        assert_eq!(err.kind, Some(ErrorKind::Unauthorized));
+        assert_eq!(err.code, "401");
+        assert!(
+            err.message
+                .starts_with("The provided GitHub token is invalid or expired")
+        );
+    }
+
+    #[tokio::test]
+    async fn unauthorized_without_a_token_asks_for_credentials() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .set_body_json(serde_json::json!({ "message": "Requires authentication" })),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let err = client.get_repo("octo", "repo").await.unwrap_err();
+
+        assert_eq!(err.kind, Some(ErrorKind::Unauthorized));
+        assert!(
+            err.message
+                .starts_with("GitHub requires authentication for this request"),
+            "{}",
+            err.message
+        );
+        assert!(err.message.contains("Requires authentication"));
+    }
+
+    #[tokio::test]
+    async fn get_file_falls_back_to_raw_for_large_files() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/data/big.csv"))
+            .and(wiremock::matchers::header(
+                "accept",
+                "application/vnd.github.raw",
+            ))
+            .and(query_param("ref", "main"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("id,value\n1,2\n"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/data/big.csv"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "path": "data/big.csv",
+                "type": "file",
+                "size": 2_000_000,
+                "content": "",
+                "encoding": "none",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let content = client
+            .get_file("octo", "repo", "data/big.csv", Some("main"))
+            .await
+            .unwrap();
+
+        assert_eq!(content, "id,value\n1,2\n");
+    }
+
+    #[tokio::test]
+    async fn search_reports_invalid_query_details() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search/code"))
+            .respond_with(ResponseTemplate::new(422).set_body_json(serde_json::json!({
+                "message": "Validation Failed",
+                "errors": [
+                    {
+                        "resource": "Search",
+                        "field": "q",
+                        "code": "invalid",
+                        "message": "The search contains unbalanced quotes."
+                    },
+                    {
+                        "resource": "Search",
+                        "field": "q",
+                        "code": "invalid",
+                        "message": "The listed users cannot be searched."
+                    }
+                ],
+                "documentation_url": "https://docs.github.com/rest/search/search#search-code"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let err = client
+            .search_code("\"unbalanced", &BTreeMap::new(), None, None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, "422");
+        assert_eq!(err.kind, Some(ErrorKind::InvalidQuery));
+        assert_eq!(
+            err.message,
+            "Invalid search query: The search contains unbalanced quotes.; The listed users cannot be searched."
+        );
+        assert_eq!(
+            err.doc_url.as_deref(),
+            Some("https://docs.github.com/rest/search/search#search-code")
+        );
+    }
+
+    #[tokio::test]
+    async fn search_code_sends_extra_params() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search/code"))
+            .and(query_param("q", "fn parse"))
+            .and(query_param("sort", "indexed"))
+            .and(query_param("order", "asc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(search_body(1, &["src/a.rs"])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let extra = BTreeMap::from([
+            ("sort".to_string(), "indexed".to_string()),
+            ("order".to_string(), "asc".to_string()),
+        ]);
+        let client = mock_client(&server, ClientOptions::default());
+        let page = client
+            .search_code("fn parse", &extra, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(page.total_count, 1);
+    }
+
+    #[tokio::test]
+    async fn search_code_all_follows_next_links() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search/code"))
+            .and(query_param_is_missing("page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(
+                        "Link",
+                        format!(
+                            "<{0}/search/code?q=fn&per_page=2&page=2>; rel=\"next\", \
+                             <{0}/search/code?q=fn&per_page=2&page=2>; rel=\"last\"",
+                            server.uri()
+                        ),
+                    )
+                    .set_body_json(search_body(3, &["src/a.rs", "src/b.rs"])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search/code"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(search_body(3, &["src/c.rs"])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(
+            &server,
+            ClientOptions {
+                search_interval: Duration::ZERO,
+                ..ClientOptions::default()
+            },
+        );
+        let page = client
+            .search_code_all("fn", &BTreeMap::new(), Some(2))
+            .await
+            .unwrap();
+
+        assert_eq!(page.total_count, 3);
+        let paths: Vec<_> = page
+            .results
+            .iter()
+            .map(|result| result.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["src/a.rs", "src/b.rs", "src/c.rs"]);
+    }
+
+    #[tokio::test]
+    async fn list_pagination_stops_at_the_page_cap() {
+        let server = MockServer::start().await;
+        let next = |page: u32| {
+            format!(
+                "<{}/repos/octo/repo/tags?per_page=100&page={}>; rel=\"next\"",
+                server.uri(),
+                page
+            )
+        };
+        for (page, name, calls) in [("1", "v3", 1), ("2", "v2", 1), ("3", "v1", 0)] {
+            let template = ResponseTemplate::new(200)
+                .insert_header("Link", next(page.parse::<u32>().unwrap() + 1))
+                .set_body_json(serde_json::json!([{ "name": name }]));
+            let mock = Mock::given(method("GET")).and(path("/repos/octo/repo/tags"));
+            let mock = if page == "1" {
+                mock.and(query_param_is_missing("page"))
+            } else {
+                mock.and(query_param("page", page))
+            };
+            mock.respond_with(template)
+                .expect(calls)
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(
+            &server,
+            ClientOptions {
+                max_list_pages: 2,
+                ..ClientOptions::default()
+            },
+        );
+        let tags = client.list_tags("octo", "repo", None, None).await.unwrap();
+
+        assert_eq!(tags.items, vec!["v3", "v2"]);
+        assert!(tags.truncated);
+    }
+
+    #[tokio::test]
+    async fn list_pulls_reports_drafts_and_merged_state() {
+        let server = MockServer::start().await;
+        let pull = |number: u64, state: &str, draft: bool, merged_at: Option<&str>| {
+            serde_json::json!({
+                "number": number,
+                "title": format!("PR {}", number),
+                "state": state,
+                "draft": draft,
+                "head": { "ref": format!("feature-{}", number), "sha": "abc" },
+                "base": { "ref": "main", "sha": "def" },
+                "user": { "login": "octocat" },
+                "html_url": format!("https://github.com/octo/repo/pull/{}", number),
+                "created_at": "2024-01-01T00:00:00Z",
+                "merged_at": merged_at,
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/pulls"))
+            .and(query_param("state", "all"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                pull(3, "open", true, None),
+                pull(2, "closed", false, Some("2024-01-02T00:00:00Z")),
+                pull(1, "closed", false, None),
+            ])))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let pulls = client
+            .list_pulls("octo", "repo", Some(IssueState::All), None, None)
+            .await
+            .unwrap();
+
+        assert!(pulls[0].draft);
+        assert_eq!(pulls[0].state, "open");
+        assert_eq!(pulls[0].head_ref, "feature-3");
+        assert_eq!(pulls[0].base_ref, "main");
+        assert_eq!(pulls[0].user, "octocat");
+        assert!(!pulls[1].draft);
+        assert_eq!(pulls[1].state, "merged");
+        assert_eq!(pulls[2].state, "closed");
+    }
+
+    #[tokio::test]
+    async fn list_tags_requests_a_single_page_and_reports_links() {
+        let server = MockServer::start().await;
+        let links = format!(
+            "<{0}/repos/octo/repo/tags?page=3&per_page=2>; rel=\"next\", <{0}/repos/octo/repo/tags?page=9&per_page=2>; rel=\"last\"",
+            server.uri()
+        );
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/tags"))
+            .and(query_param("page", "2"))
+            .and(query_param("per_page", "2"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Link", links.as_str())
+                    .set_body_json(serde_json::json!([{ "name": "v3" }, { "name": "v2" }])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let tags = client
+            .list_tags("octo", "repo", Some(2), Some(2))
+            .await
+            .unwrap();
+
+        assert_eq!(tags.items, vec!["v3", "v2"]);
+        assert!(!tags.truncated);
+        assert_eq!(
+            tags.links,
+            PageLinks {
+                next: Some(3),
+                last: Some(9),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn list_branches_forwards_per_page_alone() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/branches"))
+            .and(query_param("per_page", "5"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!([{ "name": "main" }])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let branches = client
+            .list_branches("octo", "repo", None, Some(5))
+            .await
+            .unwrap();
+
+        assert_eq!(branches.items, vec!["main"]);
+        assert_eq!(branches.links, PageLinks::default());
+    }
+
+    #[tokio::test]
+    async fn list_pull_files_follows_pages_and_keeps_missing_patches_empty() {
+        let server = MockServer::start().await;
+        let file = |name: &str, status: &str, patch: Option<&str>| {
+            serde_json::json!({
+                "sha": "abc",
+                "filename": name,
+                "status": status,
+                "additions": 3,
+                "deletions": 1,
+                "changes": 4,
+                "blob_url": "https://github.com/octo/repo/blob/abc/x",
+                "patch": patch,
+            })
+        };
+        let next = format!("<{}/pulls-files-page-2>; rel=\"next\"", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/pulls/7/files"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Link", next.as_str())
+                    .set_body_json(serde_json::json!([file(
+                        "src/lib.rs",
+                        "modified",
+                        Some("@@ -1 +1 @@\n-a\n+b")
+                    ),])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/pulls-files-page-2"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!([file("logo.png", "added", None),])),
+            )
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let files = client
+            .list_pull_files("octo", "repo", 7, None, None)
+            .await
+            .unwrap();
+
+        assert!(!files.truncated);
+        assert_eq!(files.items.len(), 2);
+        assert_eq!(files.items[0].filename, "src/lib.rs");
+        assert_eq!(files.items[0].status, "modified");
+        assert_eq!(files.items[0].additions, 3);
+        assert_eq!(files.items[0].deletions, 1);
+        assert_eq!(files.items[0].patch.as_deref(), Some("@@ -1 +1 @@\n-a\n+b"));
+        assert_eq!(files.items[1].status, "added");
+        assert!(files.items[1].patch.is_none());
+    }
+
+    #[tokio::test]
+    async fn commit_activity_waits_while_github_computes_it() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/stats/commit_activity"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({})))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/stats/commit_activity"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                    "days": [0, 3, 26, 20, 39, 1, 0],
+                    "total": 89,
+                    "week": 1336280400,
+                }])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(
+            &server,
+            ClientOptions {
+                stats_poll_interval: Duration::ZERO,
+                ..ClientOptions::default()
+            },
+        );
+        let weeks = client.commit_activity("octo", "repo").await.unwrap();
+
+        assert_eq!(weeks.len(), 1);
+        assert_eq!(weeks[0].week, "2012-05-06");
+        assert_eq!(weeks[0].total, 89);
+        assert_eq!(weeks[0].days, vec![0, 3, 26, 20, 39, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn commit_activity_gives_up_while_still_computing() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/stats/commit_activity"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({})))
+            .expect(STATS_POLL_ATTEMPTS as u64)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(
+            &server,
+            ClientOptions {
+                stats_poll_interval: Duration::ZERO,
+                ..ClientOptions::default()
+            },
+        );
+        let err = client.commit_activity("octo", "repo").await.unwrap_err();
+
+        assert_eq!(err.code, "202");
+        assert!(err.message.contains("try again shortly"), "{}", err.message);
+    }
+
+    /// Answers slowly and records when each request arrived.
+    struct SlowResponder(Arc<std::sync::Mutex<Vec<Instant>>>);
+
+    const SLOW_RESPONSE: Duration = Duration::from_millis(100);
+
+    impl wiremock::Respond for SlowResponder {
+        fn respond(&self, _: &wiremock::Request) -> ResponseTemplate {
+            self.0.lock().unwrap().push(Instant::now());
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!([]))
+                .set_delay(SLOW_RESPONSE)
+        }
+    }
+
+    /// Most requests a [`SlowResponder`] was serving at the same moment.
+    async fn max_simultaneous(max_concurrency: Option<usize>) -> usize {
+        let server = MockServer::start().await;
+        let arrivals = Arc::new(std::sync::Mutex::new(Vec::new()));
+        Mock::given(method("GET"))
+            .respond_with(SlowResponder(arrivals.clone()))
+            .mount(&server)
+            .await;
+        let client = mock_client(
+            &server,
+            ClientOptions {
+                max_concurrency,
+                ..ClientOptions::default()
+            },
+        );
+
+        let calls =
+            ["a", "b", "c", "d"].map(|repo| client.list_pulls("octo", repo, None, None, None));
+        for result in futures::future::join_all(calls).await {
+            result.unwrap();
+        }
+
+        let arrivals = arrivals.lock().unwrap();
+        arrivals
+            .iter()
+            .map(|start| {
+                arrivals
+                    .iter()
+                    .filter(|other| **other >= *start && **other < *start + SLOW_RESPONSE)
+                    .count()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[tokio::test]
+    async fn max_concurrency_serializes_requests_across_calls() {
+        assert_eq!(max_simultaneous(Some(1)).await, 1);
+        assert!(max_simultaneous(None).await > 1);
+    }
+
+    #[tokio::test]
+    async fn max_concurrency_holds_the_slot_while_the_body_downloads() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Headers go out at once and the body only after `SLOW_RESPONSE`, which wiremock
+        // cannot do, so this serves raw HTTP.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0; 1024];
+                    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                        let read = socket.read(&mut buf).await.unwrap();
+                        request.extend_from_slice(&buf[..read]);
+                    }
+                    let head = "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 2\r\nconnection: close\r\n\r\n";
+                    socket.write_all(head.as_bytes()).await.unwrap();
+                    socket.flush().await.unwrap();
+                    tokio::time::sleep(SLOW_RESPONSE).await;
+                    socket.write_all(b"[]").await.unwrap();
+                });
+            }
+        });
+        let client = GithubClient::with_options(
+            base,
+            None,
+            ClientOptions {
+                max_concurrency: Some(1),
+                ..ClientOptions::default()
+            },
+        )
+        .unwrap();
+
+        let started = Instant::now();
+        let calls = ["a", "b"].map(|repo| client.list_pulls("octo", repo, None, None, None));
+        for result in futures::future::join_all(calls).await {
+            result.unwrap();
+        }
+
+        assert!(started.elapsed() >= SLOW_RESPONSE * 2);
+    }
+
+    #[test]
+    fn web_urls_use_the_web_host_of_the_api() {
+        let github = GithubClient::new("https://api.github.com".to_string(), None).unwrap();
+        assert_eq!(
+            github.web_url_for("octo", "repo", EntryType::File, None, "src/lib.rs"),
+            "https://github.com/octo/repo/blob/HEAD/src/lib.rs"
+        );
+        assert_eq!(
+            github.web_url_for("octo", "repo", EntryType::Dir, Some("feature/x"), "docs/"),
+            "https://github.com/octo/repo/tree/feature/x/docs"
+        );
+        assert_eq!(
+            github.web_url_for("octo", "repo", EntryType::Dir, None, ""),
+            "https://github.com/octo/repo"
+        );
+
+        let enterprise =
+            GithubClient::new("https://ghe.example.com/api/v3/".to_string(), None).unwrap();
+        assert_eq!(
+            enterprise.web_url_for("octo", "repo", EntryType::File, Some("v1.0"), "a b.md"),
+            "https://ghe.example.com/octo/repo/blob/v1.0/a%20b.md"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_commits_forwards_the_time_window() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits"))
+            .and(query_param("since", "2024-05-01"))
+            .and(query_param("until", "2024-05-08T00:00:00Z"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                    "sha": "abc123",
+                    "commit": {
+                        "message": "Fix parser\n\nLonger explanation.",
+                        "author": { "name": "Octo Cat", "date": "2024-05-03T10:00:00Z" },
+                    },
+                    "html_url": "https://github.com/octo/repo/commit/abc123",
+                }])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let commits = client
+            .list_commits(
+                "octo",
+                "repo",
+                None,
+                Some("2024-05-01"),
+                Some("2024-05-08T00:00:00Z"),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].sha, "abc123");
+        assert_eq!(commits[0].summary, "Fix parser");
+        assert_eq!(commits[0].author, "Octo Cat");
+        assert_eq!(commits[0].date, "2024-05-03T10:00:00Z");
+    }
+
+    #[tokio::test]
+    async fn archive_url_returns_the_redirect_location_without_following_it() {
+        let server = MockServer::start().await;
+        let location = format!(
+            "{}/codeload/octo/repo/legacy.zip/refs/heads/release/1.x",
+            server.uri()
+        );
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/zipball/release/1.x"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", location.as_str()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/codeload/octo/repo/legacy.zip/refs/heads/release/1.x",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 16]))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let url = client
+            .archive_url("octo", "repo", Some("release/1.x"))
+            .await
+            .unwrap();
+
+        assert_eq!(url, location);
+    }
+
+    async fn mount_etag_tags(server: &MockServer, unconditional_calls: u64) {
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/tags"))
+            .and(wiremock::matchers::header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("ETag", "\"v1\""))
+            .with_priority(1)
+            .expect(1)
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/tags"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(serde_json::json!([{ "name": "v1.0.0" }])),
+            )
+            .expect(unconditional_calls)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn not_modified_is_served_from_the_etag_cache() {
+        let server = MockServer::start().await;
+        mount_etag_tags(&server, 1).await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let first = client.list_tags("octo", "repo", None, None).await.unwrap();
+        let second = client.list_tags("octo", "repo", None, None).await.unwrap();
+
+        assert_eq!(first.items, vec!["v1.0.0"]);
+        assert_eq!(second.items, vec!["v1.0.0"]);
+    }
+
+    #[tokio::test]
+    async fn not_modified_without_a_cached_body_fetches_fresh_content() {
+        let server = MockServer::start().await;
+        mount_etag_tags(&server, 2).await;
+
+        // Too small for any body, so only the ETag survives the first response.
+        let client = mock_client(
+            &server,
+            ClientOptions {
+                etag_cache_bytes: 1,
+                ..ClientOptions::default()
+            },
+        );
+        client.list_tags("octo", "repo", None, None).await.unwrap();
+        let tags = client.list_tags("octo", "repo", None, None).await.unwrap();
 
-#[derive(Debug, Deserialize)]
-struct GithubFile {
-    path: String,
-    #[serde(rename = "type")]
-    r#type: GithubContentType,
-    size: Option<u64>,
-    content: Option<String>,
-    encoding: Option<String>,
-    target: Option<String>,
-    submodule_git_url: Option<String>,
-}
+        assert_eq!(tags.items, vec!["v1.0.0"]);
+    }
 
-#[derive(Debug, Deserialize)]
-struct GithubDirectoryEntry {
-    #[serde(rename = "name")]
-    _name: String,
-    path: String,
-    #[serde(rename = "type")]
-    r#type: GithubContentType,
-    size: Option<u64>,
-    target: Option<String>,
-    submodule_git_url: Option<String>,
-}
+    #[test]
+    fn next_link_ignores_other_relations() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            "<https://api.github.com/x?page=1>; rel=\"prev\", <https://api.github.com/x?page=3>; rel=\"next\""
+                .parse()
+                .unwrap(),
+        );
 
-#[derive(Debug, Deserialize, Clone)]
-#[serde(rename_all = "lowercase")]
-enum GithubContentType {
-    File,
-    Dir,
-    Symlink,
-    Submodule,
-}
+        assert_eq!(
+            next_link(&headers).map(String::from),
+            Some("https://api.github.com/x?page=3".to_string())
+        );
+        assert_eq!(next_link(&HeaderMap::new()), None);
+    }
 
-impl GithubContentType {
-    fn to_entry_type(&self) -> EntryType {
-        match self {
-            GithubContentType::Dir => EntryType::Dir,
-            GithubContentType::File => EntryType::File,
-            GithubContentType::Symlink => EntryType::Symlink,
-            GithubContentType::Submodule => EntryType::Submodule,
-        }
+    #[tokio::test]
+    async fn get_repo_reports_redirect_to_renamed_repo() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/old-owner/old-name"))
+            .respond_with(
+                ResponseTemplate::new(301)
+                    .insert_header("Location", format!("{}/repositories/42", server.uri())),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(repo_body("new-owner/new-name")))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let repo = client
+            .get_repo("old-owner", "old-name")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(repo.redirected_to.as_deref(), Some("new-owner/new-name"));
+        assert_eq!(repo.info.stars, 3);
     }
-}
 
-fn entry_name(path: &str) -> String {
-    path.rsplit('/').next().unwrap_or(path).to_string()
-}
+    #[tokio::test]
+    async fn cached_repo_keeps_the_url_it_was_fetched_from() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .and(wiremock::matchers::header("if-none-match", "\"r1\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("ETag", "\"r1\""))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"r1\"")
+                    .set_body_json(repo_body("octo/renamed")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
 
-fn directory_stats(path: &str) -> Stats {
-    let normalized = path.to_string();
-    Stats {
-        r#type: EntryType::Dir,
-        name: entry_name(&normalized),
-        path: normalized,
-        size: None,
-        target: None,
-        submodule_git_url: None,
+        let client = mock_client(&server, ClientOptions::default());
+        client.get_repo("octo", "repo").await.unwrap().unwrap();
+        let cached = client.get_repo("octo", "repo").await.unwrap().unwrap();
+
+        // Only a changed URL counts as a redirect, so a cache hit must not report one.
+        assert!(cached.redirected_to.is_none());
     }
-}
 
-fn parent_path(path: &str) -> String {
-    path.rsplit_once('/')
-        .map(|(parent, _)| parent.to_string())
-        .unwrap_or_else(String::new)
-}
+    #[tokio::test]
+    async fn get_repo_without_redirect_reports_none() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/Octo/Repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(repo_body("octo/repo")))
+            .mount(&server)
+            .await;
 
-fn normalize_root_path(path: &str) -> String {
-    path.trim_matches('/').to_string()
-}
+        let client = mock_client(&server, ClientOptions::default());
+        let repo = client.get_repo("Octo", "Repo").await.unwrap().unwrap();
 
-impl GithubFile {
-    fn into_tree_entry(self, children: Vec<TreeEntry>) -> TreeEntry {
-        let r#type = self.r#type.to_entry_type();
+        assert!(repo.redirected_to.is_none());
+    }
 
-        TreeEntry {
-            r#type,
-            name: entry_name(&self.path),
-            path: self.path,
-            size: match r#type {
-                EntryType::Dir | EntryType::Submodule => None,
-                _ => self.size,
+    #[test]
+    fn custom_headers_are_sent_but_cannot_clobber_managed_ones() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-routing-key", "tenant-a".parse().unwrap());
+        headers.insert(AUTHORIZATION, "Bearer hijacked".parse().unwrap());
+        headers.insert(ACCEPT, "text/plain".parse().unwrap());
+
+        let client = GithubClient::with_options(
+            "https://example.com".to_string(),
+            Some("secret".to_string()),
+            ClientOptions {
+                headers,
+                ..ClientOptions::default()
             },
-            target: self.target,
-            submodule_git_url: self.submodule_git_url,
-            children,
-        }
+        )
+        .unwrap();
+
+        let url = client.build_url(&["repos", "octo", "repo"]).unwrap();
+        let request = client.base_request(url, None).build().unwrap();
+        let sent = request.headers();
+
+        assert_eq!(sent["x-routing-key"], "tenant-a");
+        assert_eq!(sent.get_all(AUTHORIZATION).iter().count(), 1);
+        assert_eq!(sent[AUTHORIZATION], "Bearer secret");
+        assert_eq!(sent.get_all(ACCEPT).iter().count(), 1);
+        assert_eq!(sent[ACCEPT], "application/vnd.github+json");
     }
 
-    fn into_stats(self) -> Stats {
-        let r#type = self.r#type.to_entry_type();
+    #[tokio::test]
+    async fn resolve_ref_reads_sha_media_type() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits/feature/x"))
+            .and(wiremock::matchers::header(
+                "accept",
+                "application/vnd.github.sha",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string("abc123\n"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits/nope"))
+            .respond_with(ResponseTemplate::new(422))
+            .mount(&server)
+            .await;
 
-        Stats {
-            r#type,
-            name: entry_name(&self.path),
-            path: self.path,
-            size: match r#type {
-                EntryType::Dir | EntryType::Submodule => None,
-                _ => self.size,
-            },
-            target: self.target,
-            submodule_git_url: self.submodule_git_url,
-        }
+        let client = mock_client(&server, ClientOptions::default());
+
+        let sha = client
+            .resolve_ref("octo", "repo", "feature/x")
+            .await
+            .unwrap();
+        assert_eq!(sha.as_deref(), Some("abc123"));
+        assert!(
+            client
+                .resolve_ref("octo", "repo", "nope")
+                .await
+                .unwrap()
+                .is_none()
+        );
     }
-}
 
-impl GithubDirectoryEntry {
-    fn into_tree_entry(self, children: Vec<TreeEntry>) -> TreeEntry {
-        let r#type = self.r#type.to_entry_type();
+    #[test]
+    fn decodes_wrapped_base64_content() {
+        let content = Some("TUlUIExp\nY2Vuc2UK\n".to_string());
 
-        TreeEntry {
-            r#type,
-            name: entry_name(&self.path),
-            path: self.path,
-            size: match r#type {
-                EntryType::Dir | EntryType::Submodule => None,
-                _ => self.size,
-            },
-            target: self.target,
-            submodule_git_url: self.submodule_git_url,
-            children,
-        }
+        let decoded = decode_content(Some("base64".to_string()), content).unwrap();
+
+        assert_eq!(decoded, "MIT License\n");
     }
-}
 
-#[derive(Debug)]
-enum GithubContents {
-    File(GithubFile),
-    Directory(Vec<GithubDirectoryEntry>),
-}
+    #[tokio::test]
+    async fn get_blob_decodes_wrapped_base64() {
+        let server = MockServer::start().await;
+        let sha = "3a0f86fb8db8eea7ccbb9a95f325ddbedfb25e15";
+        let encoded = STANDARD.encode("fn main() {\n    println!(\"hi\");\n}\n");
+        let (head, tail) = encoded.split_at(20);
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/octo/repo/git/blobs/{}", sha)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sha": sha,
+                "size": 32,
+                "content": format!("{}\n{}\n", head, tail),
+                "encoding": "base64",
+            })))
+            .mount(&server)
+            .await;
 
-trait NamedItem {
-    fn name(self) -> String;
-}
+        let client = mock_client(&server, ClientOptions::default());
+        let blob = client.get_blob("octo", "repo", sha).await.unwrap();
 
-impl NamedItem for GithubTag {
-    fn name(self) -> String {
-        self.name
+        assert_eq!(blob, b"fn main() {\n    println!(\"hi\");\n}\n");
     }
-}
 
-impl NamedItem for GithubBranch {
-    fn name(self) -> String {
-        self.name
+    #[tokio::test]
+    async fn get_blob_falls_back_to_raw_for_large_blobs() {
+        let server = MockServer::start().await;
+        let sha = "3a0f86fb8db8eea7ccbb9a95f325ddbedfb25e15";
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/octo/repo/git/blobs/{}", sha)))
+            .and(|req: &wiremock::Request| {
+                req.headers
+                    .get("accept")
+                    .is_some_and(|accept| accept == "application/vnd.github.raw")
+            })
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"raw bytes".to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/octo/repo/git/blobs/{}", sha)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sha": sha,
+                "size": 104857600,
+                "content": "",
+                "encoding": "none",
+            })))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+        let blob = client.get_blob("octo", "repo", sha).await.unwrap();
+
+        assert_eq!(blob, b"raw bytes");
     }
-}
 
-impl From<GithubRepo> for RepoInfo {
-    fn from(repo: GithubRepo) -> Self {
-        RepoInfo {
-            description: repo.description,
-            stars: repo.stargazers_count,
-            forks: repo.forks_count,
-            license: repo.license.map(Into::into),
-        }
+    #[test]
+    fn rejects_unknown_content_encoding() {
+        let err = decode_content(Some("utf-16".to_string()), Some(String::new())).unwrap_err();
+
+        assert!(err.message.contains("utf-16"));
     }
-}
 
-impl From<GithubLicense> for LicenseInfo {
-    fn from(license: GithubLicense) -> Self {
-        LicenseInfo {
-            key: license.key,
-            name: license.name,
-            spdx_id: license.spdx_id,
-            url: license.url,
-        }
+    #[tokio::test]
+    async fn get_license_decodes_license_text() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/license"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": "LICENSE",
+                "path": "LICENSE",
+                "content": STANDARD.encode("MIT License\n\nCopyright (c) Octo\n"),
+                "encoding": "base64",
+                "license": { "key": "mit", "name": "MIT License", "spdx_id": "MIT", "url": null }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/unlicensed/license"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, ClientOptions::default());
+
+        let license = client.get_license("octo", "repo").await.unwrap().unwrap();
+        assert_eq!(license.spdx_id.as_deref(), Some("MIT"));
+        assert_eq!(license.name.as_deref(), Some("MIT License"));
+        assert_eq!(license.path, "LICENSE");
+        assert!(license.content.starts_with("MIT License\n"));
+
+        assert!(
+            client
+                .get_license("octo", "unlicensed")
+                .await
+                .unwrap()
+                .is_none()
+        );
     }
-}
 
-impl From<GithubSearchItem> for SearchResult {
-    fn from(item: GithubSearchItem) -> Self {
-        SearchResult {
-            name: item.name,
-            path: item.path,
-            repository: item.repository.full_name,
-        }
+    #[tokio::test]
+    async fn retry_budget_is_shared_across_requests_of_one_call() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server, fast_retry(2, 10_000));
+        let started = Instant::now();
+
+        client
+            .scoped(async {
+                for _ in 0..4 {
+                    let err = client.get_repo("octo", "repo").await.unwrap_err();
+                    assert_eq!(err.code, "503");
+                }
+            })
+            .await;
+
+        // Four requests plus the two retries the shared budget allows.
+        assert_eq!(server.received_requests().await.unwrap().len(), 6);
+        assert!(started.elapsed() < Duration::from_secs(2));
     }
-}
 
-impl From<GithubRepoSummary> for RepoSummary {
-    fn from(repo: GithubRepoSummary) -> Self {
-        RepoSummary {
-            name: repo.name,
-            full_name: repo.full_name,
-            private: repo.private,
-            description: repo.description,
-            html_url: repo.html_url,
-        }
+    #[tokio::test]
+    async fn retry_wait_is_bounded_by_the_time_budget() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        // Backoff runs 20ms, 40ms, 80ms, ...; only the first two fit in 70ms.
+        let client = mock_client(&server, fast_retry(100, 70));
+        let started = Instant::now();
+
+        let err = client
+            .scoped(client.list_tags("octo", "repo", None, None))
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, "503");
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+        assert!(started.elapsed() < Duration::from_secs(1));
     }
-}
 
-fn assemble_tree(
-    children_by_parent: &mut HashMap<String, Vec<TreeEntry>>,
-    parent: &str,
-) -> Vec<TreeEntry> {
-    let mut entries = children_by_parent.remove(parent).unwrap_or_default();
+    #[tokio::test]
+    async fn retries_recover_from_transient_failures() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "name": "v1.0.0" }
+            ])))
+            .mount(&server)
+            .await;
 
-    for entry in entries.iter_mut() {
-        if matches!(entry.r#type, EntryType::Dir) {
-            entry.children = assemble_tree(children_by_parent, &entry.path);
-        }
+        let client = mock_client(&server, fast_retry(3, 1_000));
+        let tags = client.list_tags("octo", "repo", None, None).await.unwrap();
+
+        assert_eq!(tags.items, vec!["v1.0.0".to_string()]);
     }
 
-    entries
-}
+    #[test]
+    fn unrecognized_content_types_become_unknown_entries() {
+        let entries: Vec<GithubDirectoryEntry> = serde_json::from_value(serde_json::json!([
+            { "name": "vendor", "path": "vendor", "type": "gitlink", "size": 0 },
+            { "name": "lib.rs", "path": "lib.rs", "type": "file", "size": 3 },
+        ]))
+        .unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        assert!(matches!(entries[0].r#type, GithubContentType::Unknown));
+        let entry = entries
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_tree_entry(Vec::new());
+        assert!(matches!(entry.r#type, EntryType::Unknown));
+        assert_eq!(entry.path, "vendor");
+    }
 
     #[tokio::test]
     async fn expands_symlink_and_submodule_entries() {
@@ -669,8 +4613,16 @@ mod tests {
             },
         ]);
 
-        let entries = client
-            .expand_tree("owner", "repo", contents, 1, None, "")
+        let (entries, _) = client
+            .expand_tree(
+                "owner",
+                "repo",
+                contents,
+                1,
+                None,
+                "",
+                CrawlLimits::default(),
+            )
             .await
             .unwrap();
 
@@ -706,8 +4658,16 @@ mod tests {
             submodule_git_url: None,
         });
 
-        let entries = client
-            .expand_tree("owner", "repo", contents, 1, None, "")
+        let (entries, _) = client
+            .expand_tree(
+                "owner",
+                "repo",
+                contents,
+                1,
+                None,
+                "",
+                CrawlLimits::default(),
+            )
             .await
             .unwrap();
 
@@ -718,6 +4678,124 @@ mod tests {
         assert!(entries[0].children.is_empty());
     }
 
+    fn assemble_in_order(order: &[(EntryType, &str)]) -> serde_json::Value {
+        let mut children_by_parent: HashMap<String, Vec<TreeEntry>> = HashMap::new();
+        for (r#type, path) in order {
+            children_by_parent
+                .entry(parent_path(path))
+                .or_default()
+                .push(TreeEntry::stub(*r#type, path));
+        }
+
+        serde_json::to_value(assemble_tree(&mut children_by_parent, "", usize::MAX)).unwrap()
+    }
+
+    fn nesting_depth(entries: &[TreeEntry]) -> usize {
+        let mut deepest = 0;
+        let mut stack: Vec<(&TreeEntry, usize)> = entries.iter().map(|entry| (entry, 1)).collect();
+        while let Some((entry, depth)) = stack.pop() {
+            deepest = deepest.max(depth);
+            stack.extend(entry.children.iter().map(|child| (child, depth + 1)));
+        }
+        deepest
+    }
+
+    #[test]
+    fn assembles_pathologically_deep_trees_without_recursing() {
+        const DEPTH: usize = 200_000;
+        // Opaque keys keep the synthetic chain cheap; assembly only relates parents by path.
+        let mut children_by_parent: HashMap<String, Vec<TreeEntry>> = HashMap::new();
+        for level in 0..DEPTH {
+            let parent = if level == 0 {
+                String::new()
+            } else {
+                format!("d{}", level - 1)
+            };
+            children_by_parent
+                .entry(parent)
+                .or_default()
+                .push(TreeEntry::stub(EntryType::Dir, &format!("d{}", level)));
+        }
+
+        let tree = assemble_tree(&mut children_by_parent, "", 16);
+
+        assert_eq!(nesting_depth(&tree), 17);
+        let mut node = &tree[0];
+        for _ in 1..16 {
+            node = &node.children[0];
+        }
+        assert_eq!(node.path, "d15");
+        assert_eq!(node.children.len(), DEPTH - 16);
+        assert!(node.children.iter().all(|entry| entry.children.is_empty()));
+    }
+
+    #[test]
+    fn flattens_subtrees_below_max_nesting_by_relative_path() {
+        let mut children_by_parent: HashMap<String, Vec<TreeEntry>> = HashMap::new();
+        for (r#type, path) in [
+            (EntryType::Dir, "a"),
+            (EntryType::Dir, "a/b"),
+            (EntryType::File, "a/z.txt"),
+            (EntryType::File, "a/b/c.rs"),
+        ] {
+            children_by_parent
+                .entry(parent_path(path))
+                .or_default()
+                .push(TreeEntry::stub(r#type, path));
+        }
+
+        let tree = assemble_tree(&mut children_by_parent, "", 1);
+
+        let names: Vec<&str> = tree[0]
+            .children
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["b", "b/c.rs", "z.txt"]);
+    }
+
+    #[test]
+    fn assembled_tree_is_independent_of_listing_order() {
+        let entries = [
+            (EntryType::File, "README.md"),
+            (EntryType::Dir, "src"),
+            (EntryType::File, "src/main.rs"),
+            (EntryType::Dir, "src/bin"),
+            (EntryType::File, "src/bin/tool.rs"),
+            (EntryType::File, "src/lib.rs"),
+            (EntryType::Symlink, "latest"),
+            (EntryType::Dir, "docs"),
+            (EntryType::Submodule, "vendor"),
+            (EntryType::File, "Cargo.toml"),
+        ];
+
+        let expected = assemble_in_order(&entries);
+        let names: Vec<_> = expected
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["docs", "src", "Cargo.toml", "README.md", "latest", "vendor"]
+        );
+
+        // Every rotation of the listing, forwards and backwards.
+        for shift in 0..entries.len() {
+            let mut order = entries.to_vec();
+            order.rotate_left(shift);
+            assert_eq!(assemble_in_order(&order), expected, "rotation {shift}");
+
+            order.reverse();
+            assert_eq!(
+                assemble_in_order(&order),
+                expected,
+                "reversed rotation {shift}"
+            );
+        }
+    }
+
     #[test]
     fn converts_search_item() {
         let item = GithubSearchItem {
@@ -743,6 +4821,8 @@ mod tests {
             private: false,
             html_url: "https://github.com/octo/repo".to_string(),
             description: Some("cool repo".to_string()),
+            fork: true,
+            archived: false,
         };
 
         let summary: RepoSummary = repo.into();
@@ -752,6 +4832,8 @@ mod tests {
         assert!(!summary.private);
         assert_eq!(summary.html_url, "https://github.com/octo/repo");
         assert_eq!(summary.description.as_deref(), Some("cool repo"));
+        assert!(summary.fork);
+        assert!(!summary.archived);
     }
 
     #[test]
@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::header::HeaderMap;
+
+/// GitHub rate-limit bucket a request draws from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceCategory {
+    /// REST endpoints sharing the hourly core limit.
+    Core,
+    /// `/search/*` endpoints, limited per minute and far more tightly.
+    Search,
+}
+
+impl ResourceCategory {
+    /// Classifies an API path relative to the configured API base.
+    pub fn for_path(relative_path: &str) -> Self {
+        if relative_path.trim_start_matches('/').starts_with("search/") {
+            Self::Search
+        } else {
+            Self::Core
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Core => "core",
+            Self::Search => "search",
+        }
+    }
+}
+
+/// Remaining quota per category, learned from `X-RateLimit-*` headers, plus search pacing.
+#[derive(Debug)]
+pub struct RateLimiter {
+    search_interval: Duration,
+    state: Mutex<HashMap<ResourceCategory, CategoryState>>,
+}
+
+#[derive(Debug, Default)]
+struct CategoryState {
+    remaining: Option<u64>,
+    reset_at: Option<SystemTime>,
+    /// Earliest time the next paced request may start.
+    next_slot: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(search_interval: Duration) -> Self {
+        Self {
+            search_interval,
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A limiter with the same pacing and no quota learned yet, for another host.
+    pub fn fresh(&self) -> Self {
+        Self::new(self.search_interval)
+    }
+
+    /// Reserves a slot for one request, returning how long to wait before sending it,
+    /// or `Err` with the time until reset when the category's quota is exhausted.
+    pub fn reserve(&self, category: ResourceCategory) -> Result<Duration, Duration> {
+        self.reserve_at(category, Instant::now(), SystemTime::now())
+    }
+
+    /// Records the quota GitHub reported in a response for `category`.
+    pub fn observe(&self, category: ResourceCategory, headers: &HeaderMap) {
+        let remaining = header_u64(headers, "x-ratelimit-remaining");
+        let reset_at = header_u64(headers, "x-ratelimit-reset")
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+        if remaining.is_none() && reset_at.is_none() {
+            return;
+        }
+
+        let mut state = self.lock();
+        let entry = state.entry(category).or_default();
+        entry.remaining = remaining;
+        entry.reset_at = reset_at;
+    }
+
+    fn reserve_at(
+        &self,
+        category: ResourceCategory,
+        now: Instant,
+        wall: SystemTime,
+    ) -> Result<Duration, Duration> {
+        let mut state = self.lock();
+        let entry = state.entry(category).or_default();
+
+        if entry.remaining == Some(0)
+            && let Some(wait) = entry
+                .reset_at
+                .and_then(|reset_at| reset_at.duration_since(wall).ok())
+                .filter(|wait| !wait.is_zero())
+        {
+            return Err(wait);
+        }
+
+        if category != ResourceCategory::Search {
+            return Ok(Duration::ZERO);
+        }
+
+        let slot = entry.next_slot.map_or(now, |next| next.max(now));
+        entry.next_slot = Some(slot + self.search_interval);
+        Ok(slot - now)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<ResourceCategory, CategoryState>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exhausted_until(reset_at: SystemTime) -> HeaderMap {
+        let reset = reset_at.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        headers.insert("x-ratelimit-reset", reset.to_string().parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn classifies_search_paths() {
+        assert_eq!(
+            ResourceCategory::for_path("/search/code"),
+            ResourceCategory::Search
+        );
+        assert_eq!(
+            ResourceCategory::for_path("/repos/octo/search/contents"),
+            ResourceCategory::Core
+        );
+    }
+
+    #[test]
+    fn paces_search_but_not_core() {
+        let limiter = RateLimiter::new(Duration::from_secs(2));
+        let (now, wall) = (Instant::now(), SystemTime::now());
+
+        assert_eq!(
+            limiter.reserve_at(ResourceCategory::Search, now, wall),
+            Ok(Duration::ZERO)
+        );
+        assert_eq!(
+            limiter.reserve_at(ResourceCategory::Search, now, wall),
+            Ok(Duration::from_secs(2))
+        );
+        assert_eq!(
+            limiter.reserve_at(ResourceCategory::Core, now, wall),
+            Ok(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn exhausted_category_blocks_until_reset_only_for_itself() {
+        let limiter = RateLimiter::new(Duration::ZERO);
+        let wall = SystemTime::now();
+        limiter.observe(
+            ResourceCategory::Search,
+            &exhausted_until(wall + Duration::from_secs(30)),
+        );
+
+        let wait = limiter
+            .reserve_at(ResourceCategory::Search, Instant::now(), wall)
+            .unwrap_err();
+        assert!(wait <= Duration::from_secs(30) && wait > Duration::from_secs(28));
+        assert!(
+            limiter
+                .reserve_at(ResourceCategory::Core, Instant::now(), wall)
+                .is_ok()
+        );
+
+        let after_reset = wall + Duration::from_secs(31);
+        assert!(
+            limiter
+                .reserve_at(ResourceCategory::Search, Instant::now(), after_reset)
+                .is_ok()
+        );
+    }
+}
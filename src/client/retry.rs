@@ -0,0 +1,185 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::{StatusCode, header::HeaderMap};
+
+/// Retry settings applied to every tool call.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries allowed for a single tool call, shared across all of its requests.
+    pub max_retries: u32,
+    /// Total time a single tool call may spend backing off between retries.
+    pub max_wait: Duration,
+    /// Delay before the first retry; doubled for every further attempt of a request.
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            max_wait: Duration::from_secs(30),
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+tokio::task_local! {
+    static BUDGET: RetryBudget;
+}
+
+/// Retry allowance shared by every request spawned from one tool call.
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    state: Arc<Mutex<BudgetState>>,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    retries_left: u32,
+    wait_left: Duration,
+    spent: RetryStats,
+}
+
+/// Retries a tool call actually made, and how long it backed off in total.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryStats {
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl RetryBudget {
+    pub fn new(policy: &RetryPolicy) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BudgetState {
+                retries_left: policy.max_retries,
+                wait_left: policy.max_wait,
+                spent: RetryStats::default(),
+            })),
+        }
+    }
+
+    /// Budget of the tool call currently running, or a fresh one for standalone requests.
+    pub fn current_or_new(policy: &RetryPolicy) -> Self {
+        BUDGET
+            .try_with(Clone::clone)
+            .unwrap_or_else(|_| Self::new(policy))
+    }
+
+    /// Runs `fut` with this budget installed as the shared budget of its requests.
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        BUDGET.scope(self, fut).await
+    }
+
+    /// Reserves one retry waiting `delay`; returns false once the budget is exhausted.
+    pub fn try_spend(&self, delay: Duration) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if state.retries_left == 0 || state.wait_left < delay {
+            state.retries_left = 0;
+            return false;
+        }
+
+        state.retries_left -= 1;
+        state.wait_left -= delay;
+        state.spent.retries += 1;
+        state.spent.backoff += delay;
+        true
+    }
+
+    /// Retries spent so far, by every request sharing this budget.
+    pub fn spent(&self) -> RetryStats {
+        self.state
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .spent
+    }
+}
+
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Delay before retrying, preferring GitHub's `Retry-After` over exponential backoff.
+pub fn retry_delay(headers: Option<&HeaderMap>, attempt: u32, policy: &RetryPolicy) -> Duration {
+    headers
+        .and_then(retry_after)
+        .unwrap_or_else(|| policy.base_delay.saturating_mul(1 << attempt.min(16)))
+}
+
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_retries: u32, max_wait_ms: u64) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            max_wait: Duration::from_millis(max_wait_ms),
+            base_delay: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn budget_fails_fast_once_retries_are_spent() {
+        let budget = RetryBudget::new(&policy(2, 1_000));
+
+        assert!(budget.try_spend(Duration::from_millis(10)));
+        assert!(budget.try_spend(Duration::from_millis(10)));
+        assert!(!budget.try_spend(Duration::from_millis(10)));
+        assert_eq!(
+            budget.spent(),
+            RetryStats {
+                retries: 2,
+                backoff: Duration::from_millis(20),
+            }
+        );
+    }
+
+    #[test]
+    fn budget_rejects_waits_longer_than_what_is_left() {
+        let budget = RetryBudget::new(&policy(10, 100));
+
+        assert!(budget.try_spend(Duration::from_millis(80)));
+        assert!(!budget.try_spend(Duration::from_millis(40)));
+        assert!(!budget.try_spend(Duration::from_millis(1)));
+    }
+
+    #[tokio::test]
+    async fn scoped_requests_share_one_budget() {
+        let policy = policy(1, 1_000);
+
+        RetryBudget::new(&policy)
+            .scope(async {
+                assert!(RetryBudget::current_or_new(&policy).try_spend(Duration::ZERO));
+                assert!(!RetryBudget::current_or_new(&policy).try_spend(Duration::ZERO));
+            })
+            .await;
+
+        assert!(RetryBudget::current_or_new(&policy).try_spend(Duration::ZERO));
+    }
+
+    #[test]
+    fn prefers_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "7".parse().unwrap());
+
+        assert_eq!(
+            retry_delay(Some(&headers), 0, &policy(1, 1)),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            retry_delay(None, 2, &policy(1, 1)),
+            Duration::from_millis(40)
+        );
+    }
+}
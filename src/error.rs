@@ -1,13 +1,44 @@
 use std::collections::HashMap;
 
+use reqwest::header::HeaderMap;
 use rmcp::model::Content;
 use schemars::JsonSchema;
 use serde::Serialize;
 
+/// Seconds GitHub asks clients to wait after a secondary rate limit when no `Retry-After` is sent.
+const SECONDARY_RATE_LIMIT_WAIT_SECS: u64 = 60;
+
 #[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct ApiErrorBody {
     pub message: String,
     pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ErrorKind>,
+    /// Suggested wait before retrying, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+    /// GitHub documentation for the failed request, from the error body's `documentation_url`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_url: Option<String>,
+}
+
+/// Coarse classification of failures that callers can act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// GitHub throttled the request (primary or secondary/abuse rate limit).
+    RateLimited,
+    /// The repository exists but has no commits yet.
+    EmptyRepository,
+    /// GitHub kept failing, so requests are paused until it recovers.
+    Unavailable,
+    /// The file is binary rather than UTF-8 text.
+    Binary,
+    /// GitHub rejected a search query as malformed; the message says what to fix.
+    InvalidQuery,
+    /// GitHub rejected the credentials (401); the token needs replacing. Unlike a 403, no
+    /// request will succeed until it is.
+    Unauthorized,
 }
 
 impl ApiErrorBody {
@@ -15,9 +46,21 @@ impl ApiErrorBody {
         Self {
             message: message.into(),
             code: code.to_string(),
+            kind: None,
+            retry_after_secs: None,
+            doc_url: None,
         }
     }
 
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.code == "404"
+    }
+
     pub fn from_reqwest(err: reqwest::Error) -> Self {
         let code = err
             .status()
@@ -27,34 +70,214 @@ impl ApiErrorBody {
     }
 
     pub async fn from_response(status: reqwest::StatusCode, response: reqwest::Response) -> Self {
+        let headers = response.headers().clone();
         let body = response.text().await.unwrap_or_default();
+        Self::from_parts(status, &headers, &body)
+    }
+
+    pub fn from_parts(status: reqwest::StatusCode, headers: &HeaderMap, body: &str) -> Self {
         let fallback = status
             .canonical_reason()
             .unwrap_or("GitHub API error")
             .to_string();
 
-        let message = serde_json::from_str::<HashMap<String, serde_json::Value>>(&body)
-            .ok()
-            .and_then(|map| {
-                map.get("message")
-                    .and_then(|m| m.as_str().map(str::to_string))
-            })
-            .unwrap_or_else(|| {
-                if body.is_empty() {
-                    fallback
-                } else {
-                    body.clone()
-                }
-            });
+        let fields = serde_json::from_str::<HashMap<String, serde_json::Value>>(body).ok();
+        let field = |name: &str| fields.as_ref()?.get(name)?.as_str().map(str::to_string);
+        let message = field("message").unwrap_or_else(|| {
+            if body.is_empty() {
+                fallback
+            } else {
+                body.to_string()
+            }
+        });
 
-        Self::new(message, status.as_u16().to_string())
+        let mut err = Self::classify(status, headers, message);
+        err.doc_url = field("documentation_url");
+        err
     }
+
+    /// Builds the error for a GitHub failure whose message has already been extracted.
+    fn classify(status: reqwest::StatusCode, headers: &HeaderMap, message: String) -> Self {
+        let retry_after = headers
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+
+        if is_secondary_rate_limit(status, &message) {
+            let wait = retry_after.unwrap_or(SECONDARY_RATE_LIMIT_WAIT_SECS);
+            let mut err = Self::new(
+                format!(
+                    "GitHub secondary rate limit exceeded; wait {wait}s before retrying and reduce request concurrency. ({message})"
+                ),
+                status.as_u16(),
+            )
+            .with_kind(ErrorKind::RateLimited);
+            err.retry_after_secs = Some(wait);
+            return err;
+        }
+
+        // `GithubClient` words the fix for how it authenticates, which is not known here.
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Self::new(message, status.as_u16()).with_kind(ErrorKind::Unauthorized);
+        }
+
+        let mut err = Self::new(message, status.as_u16().to_string());
+        if is_empty_repository(status, &err.message) {
+            err.kind = Some(ErrorKind::EmptyRepository);
+        }
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            err.kind = Some(ErrorKind::RateLimited);
+            err.retry_after_secs = retry_after;
+        }
+        err
+    }
+}
+
+/// Condenses a search 422 body into the validation messages GitHub listed under `errors`.
+pub fn invalid_search_query(body: &str) -> Option<ApiErrorBody> {
+    #[derive(serde::Deserialize)]
+    struct ValidationBody {
+        #[serde(default)]
+        errors: Vec<ValidationError>,
+        documentation_url: Option<String>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct ValidationError {
+        message: Option<String>,
+    }
+
+    let parsed: ValidationBody = serde_json::from_str(body).ok()?;
+    let messages: Vec<String> = parsed
+        .errors
+        .into_iter()
+        .filter_map(|error| error.message)
+        .filter(|message| !message.trim().is_empty())
+        .collect();
+    if messages.is_empty() {
+        return None;
+    }
+
+    let mut err = ApiErrorBody::new(
+        format!("Invalid search query: {}", messages.join("; ")),
+        "422",
+    )
+    .with_kind(ErrorKind::InvalidQuery);
+    err.doc_url = parsed.documentation_url;
+    Some(err)
+}
+
+/// The contents API answers 404 (or 409 for git data) with this message on repos without commits.
+fn is_empty_repository(status: reqwest::StatusCode, message: &str) -> bool {
+    matches!(status.as_u16(), 404 | 409)
+        && message.to_ascii_lowercase().contains("repository is empty")
+}
+
+fn is_secondary_rate_limit(status: reqwest::StatusCode, message: &str) -> bool {
+    matches!(status.as_u16(), 403 | 429)
+        && message
+            .to_ascii_lowercase()
+            .contains("secondary rate limit")
 }
 
 impl rmcp::model::IntoContents for ApiErrorBody {
     fn into_contents(self) -> Vec<Content> {
         Content::json(&self)
             .map(|content| vec![content])
-            .unwrap_or_else(|_| vec![Content::text(format!("{} ({})", self.message, self.code))])
+            .unwrap_or_else(|_| {
+                let text = match &self.doc_url {
+                    Some(url) => format!("{} ({}); see {}", self.message, self.code, url),
+                    None => format!("{} ({})", self.message, self.code),
+                };
+                vec![Content::text(text)]
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{StatusCode, header::HeaderMap};
+
+    use super::{ApiErrorBody, ErrorKind, invalid_search_query};
+
+    #[test]
+    fn ignores_validation_bodies_without_messages() {
+        assert!(invalid_search_query(r#"{"message":"Validation Failed"}"#).is_none());
+        assert!(invalid_search_query("not json").is_none());
+    }
+
+    #[test]
+    fn classifies_secondary_rate_limit_as_rate_limited() {
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "42".parse().unwrap());
+        let body = r#"{"message":"You have exceeded a secondary rate limit. Please wait a few minutes before you try again.","documentation_url":"https://docs.github.com/rest"}"#;
+
+        let err = ApiErrorBody::from_parts(StatusCode::FORBIDDEN, &headers, body);
+
+        assert_eq!(err.kind, Some(ErrorKind::RateLimited));
+        assert_eq!(err.retry_after_secs, Some(42));
+        assert_eq!(err.code, "403");
+        assert!(err.message.contains("secondary rate limit"));
+    }
+
+    #[test]
+    fn secondary_rate_limit_defaults_to_a_minute_wait() {
+        let body = r#"{"message":"You have exceeded a secondary rate limit."}"#;
+
+        let err = ApiErrorBody::from_parts(StatusCode::FORBIDDEN, &HeaderMap::new(), body);
+
+        assert_eq!(err.retry_after_secs, Some(60));
+    }
+
+    #[test]
+    fn classifies_empty_repository_responses() {
+        let body = r#"{"message":"This repository is empty.","status":"404"}"#;
+
+        let err = ApiErrorBody::from_parts(StatusCode::NOT_FOUND, &HeaderMap::new(), body);
+        assert_eq!(err.kind, Some(ErrorKind::EmptyRepository));
+
+        let err = ApiErrorBody::from_parts(StatusCode::CONFLICT, &HeaderMap::new(), body);
+        assert_eq!(err.kind, Some(ErrorKind::EmptyRepository));
+
+        let missing = r#"{"message":"Not Found"}"#;
+        let err = ApiErrorBody::from_parts(StatusCode::NOT_FOUND, &HeaderMap::new(), missing);
+        assert_eq!(err.kind, None);
+    }
+
+    #[test]
+    fn unauthorized_responses_are_tagged() {
+        let body =
+            r#"{"message":"Bad credentials","documentation_url":"https://docs.github.com/rest"}"#;
+
+        let err = ApiErrorBody::from_parts(StatusCode::UNAUTHORIZED, &HeaderMap::new(), body);
+
+        assert_eq!(err.kind, Some(ErrorKind::Unauthorized));
+        assert_eq!(err.code, "401");
+        assert_eq!(err.message, "Bad credentials");
+    }
+
+    #[test]
+    fn keeps_the_documentation_url() {
+        let body = r#"{"message":"Not Found","documentation_url":"https://docs.github.com/rest/repos/repos#get-a-repository","status":"404"}"#;
+
+        let err = ApiErrorBody::from_parts(StatusCode::NOT_FOUND, &HeaderMap::new(), body);
+        assert_eq!(
+            err.doc_url.as_deref(),
+            Some("https://docs.github.com/rest/repos/repos#get-a-repository")
+        );
+        assert_eq!(err.message, "Not Found");
+
+        let err = ApiErrorBody::from_parts(StatusCode::NOT_FOUND, &HeaderMap::new(), "");
+        assert_eq!(err.doc_url, None);
+    }
+
+    #[test]
+    fn permission_errors_are_not_rate_limits() {
+        let body = r#"{"message":"Resource not accessible by integration"}"#;
+
+        let err = ApiErrorBody::from_parts(StatusCode::FORBIDDEN, &HeaderMap::new(), body);
+
+        assert_eq!(err.kind, None);
+        assert_eq!(err.message, "Resource not accessible by integration");
     }
 }
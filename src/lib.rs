@@ -2,24 +2,87 @@ pub mod cli;
 pub mod client;
 pub mod error;
 pub mod models;
+pub mod oneshot;
 pub mod server;
 
-use std::collections::HashSet;
+use std::{collections::HashSet, future::Future, time::Duration};
 
 use cli::Args;
 use client::GithubClient;
-use rmcp::ServiceExt;
+use rmcp::{RoleServer, ServiceExt, transport::IntoTransport};
 use server::GithubServer;
 
+/// Time given to the MCP session to write the responses of drained tool calls, which
+/// it sends on its own tasks, before the session is closed.
+const SHUTDOWN_FLUSH_GRACE: Duration = Duration::from_millis(250);
+
 pub async fn run(args: Args) -> anyhow::Result<()> {
     let token = args.resolve_token();
     let allowed_tools: HashSet<_> = args.allowed_tools();
 
-    let client = GithubClient::new(args.api_base, token)?;
-    let server = GithubServer::new(client, allowed_tools);
+    let options = args.client_options()?;
+    let server_options = args.server_options();
+
+    let client = GithubClient::with_options(args.api_base, token, options)?;
+    let server = GithubServer::with_options(client, allowed_tools, server_options);
+
+    if let Some(tool) = args.oneshot {
+        let output = oneshot::call_tool(server, tool.as_str(), &args.oneshot_args).await?;
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    serve_until(server, rmcp::transport::stdio(), shutdown_signal()).await
+}
+
+/// Serves MCP over `transport` until the client disconnects or `shutdown` resolves.
+///
+/// On shutdown, new tool calls are refused while in-flight ones run to completion and
+/// send their results before the session closes.
+pub async fn serve_until<T, E, A>(
+    server: GithubServer,
+    transport: T,
+    shutdown: impl Future<Output = anyhow::Result<()>>,
+) -> anyhow::Result<()>
+where
+    T: IntoTransport<RoleServer, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let service = server.clone().serve(transport).await?;
+    let cancel = service.cancellation_token();
+    let waiting = service.waiting();
+    tokio::pin!(waiting);
+
+    tokio::select! {
+        quit = &mut waiting => {
+            quit?;
+            return Ok(());
+        }
+        signal = shutdown => signal?,
+    }
+
+    eprintln!("Shutdown requested; finishing in-flight tool calls before exiting.");
+    server.drain().await;
+    tokio::time::sleep(SHUTDOWN_FLUSH_GRACE).await;
+    cancel.cancel();
+    waiting.await?;
+    eprintln!("Shutdown complete.");
+    Ok(())
+}
 
-    let service = server.serve(rmcp::transport::stdio()).await?;
+/// Resolves on the first SIGINT (Ctrl-C) or, on Unix, SIGTERM.
+async fn shutdown_signal() -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
 
-    service.waiting().await?;
     Ok(())
 }
@@ -1,291 +1,1832 @@
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, collections::BTreeMap, fmt};
 
 use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::de::{self, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::error::ApiErrorBody;
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct RepoArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetRepoArgs {
+    #[serde(default)]
     pub owner: String,
     pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Only return the repository if its ETag is no longer this one, from an earlier
+    /// response's `etag`; otherwise `not_modified` is set instead. Pass `""` to get a first `etag`.
+    #[serde(default)]
+    pub etag: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct StatsArgs {
+    #[serde(default)]
     pub owner: String,
     pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
     pub path: String,
     pub r#ref: Option<String>,
+    /// Only report the path if it changed after this time (RFC 3339, a date, or an HTTP date
+    /// like a `Last-Modified` value); otherwise `not_modified` is set instead.
+    #[serde(default)]
+    pub if_modified_since: Option<String>,
+    /// Only report the path if its ETag is no longer this one, from an earlier response's
+    /// `etag`; otherwise `not_modified` is set instead. Pass `""` to get a first `etag`.
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// For a symlink, follow it and report what it points at as `target_type`.
+    #[serde(default)]
+    pub resolve_target: bool,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct TreeArgs {
+    #[serde(default)]
     pub owner: String,
     pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
     #[serde(default)]
     pub path: Option<String>,
     #[serde(default = "default_depth")]
     pub depth: usize,
     pub r#ref: Option<String>,
+    /// Merge chains of directories that each hold only one subdirectory into a single
+    /// `a/b/c` node, as in Java-style `src/main/java/com/acme` layouts.
+    #[serde(default)]
+    pub collapse_chains: bool,
+    /// Return only files or only directories, as a flat list named by path below `path`.
+    #[serde(default)]
+    pub only: Option<EntryKind>,
+    /// Leave out entries matched by the repository's root `.gitignore` (nested ones are
+    /// not read).
+    #[serde(default)]
+    pub respect_gitignore: bool,
+    /// Add each entry's `rel_path`, its path below `path`, for reconstructing locations
+    /// after flattening the tree.
+    #[serde(default)]
+    pub include_paths: bool,
+    /// Stop crawling after this many seconds and return what was listed so far.
+    #[serde(default)]
+    pub deadline_secs: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TreeEstimateArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    pub r#ref: Option<String>,
+    /// Deepest crawl to estimate; defaults to the server's `--max-tree-depth`.
+    #[serde(default)]
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DirSizeArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    pub r#ref: Option<String>,
+    /// Levels to descend; defaults to, and is clamped by, the server's `--max-tree-depth`.
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DirProfileArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    pub r#ref: Option<String>,
+    /// Levels to descend; defaults to, and is clamped by, the server's `--max-tree-depth`.
+    #[serde(default)]
+    pub depth: Option<usize>,
+}
+
+/// Diagram language `tree_graph` renders.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// A Mermaid `graph TD` flowchart.
+    Mermaid,
+    /// A Graphviz `digraph`.
+    Dot,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TreeGraphArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    pub r#ref: Option<String>,
+    /// Levels to draw; defaults to 2 and is clamped by the server's `--max-tree-depth`.
+    #[serde(default)]
+    pub depth: Option<usize>,
+    pub format: GraphFormat,
+}
+
+/// Kind of tree entry kept by the `tree` tool's `only` filter.
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Files,
+    Dirs,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetFileArgs {
+    #[serde(default)]
     pub owner: String,
     pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
     pub path: String,
     pub r#ref: Option<String>,
     #[serde(default)]
     pub line_range: Option<LineRange>,
+    /// Several line ranges to read at once, instead of `line_range`; overlapping and
+    /// adjacent ranges are merged and the pieces returned in file order.
+    #[serde(default)]
+    pub line_ranges: Option<Vec<LineRange>>,
     #[serde(default)]
     pub max_chars: Option<usize>,
+    /// When `ref` is not found, retry once against the repository's default branch.
+    #[serde(default)]
+    pub ref_fallback: bool,
+    /// When `path` is a symlink, read the file it points to instead of failing.
+    #[serde(default)]
+    pub resolve_symlink: bool,
+    /// Convert `\r\n` and lone `\r` line endings to `\n` before applying limits.
+    #[serde(default)]
+    pub normalize_eol: bool,
+    /// Strip trailing whitespace from every line before applying limits.
+    #[serde(default)]
+    pub trim_trailing_ws: bool,
+    /// Drop the newline ending the last line taken by `line_range` or `line_ranges`.
+    #[serde(default)]
+    pub trim_final_newline: bool,
+    /// Characters per chunk; enables chunk mode, applied after `line_range` and `max_chars`.
+    #[serde(default)]
+    pub chunk_size: Option<usize>,
+    /// Zero-based chunk to return in chunk mode (defaults to the first).
+    #[serde(default)]
+    pub chunk_index: Option<usize>,
+    /// Add `content_sha256`, the digest of the whole file's raw bytes.
+    #[serde(default)]
+    pub with_hash: bool,
+    /// Replace bytes that are not valid UTF-8 with U+FFFD instead of failing as binary.
+    #[serde(default)]
+    pub lossy: bool,
+    /// Only return the file if it changed after this time (RFC 3339, a date, or an HTTP date
+    /// like a `Last-Modified` value); otherwise `not_modified` is set and `content` is empty.
+    #[serde(default)]
+    pub if_modified_since: Option<String>,
+    /// Only return the file if its ETag is no longer this one, from an earlier response's
+    /// `etag`; otherwise `not_modified` is set. Pass `""` to get a first `etag`.
+    #[serde(default)]
+    pub etag: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
-pub struct RepoInfo {
-    pub description: Option<String>,
-    pub stars: u64,
-    pub forks: u64,
-    pub license: Option<LicenseInfo>,
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct FileLocator {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    pub path: String,
+    pub r#ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DiffFilesArgs {
+    /// The "before" side, e.g. the upstream repository.
+    pub left: FileLocator,
+    /// The "after" side, e.g. a fork.
+    pub right: FileLocator,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListBranchesArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Also return each branch's head commit sha and protection status.
+    #[serde(default)]
+    pub detailed: bool,
+    /// Fetch only this page; without `page`/`per_page` every page is fetched.
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListTagsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Also return each tag's commit sha and date, newest first. Costs one request per
+    /// tag, so only the first 100 tags listed are dated.
+    #[serde(default)]
+    pub detailed: bool,
+    /// Fetch only this page; without `page`/`per_page` every page is fetched.
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SemverTagsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Version requirement with comma-separated comparators, e.g. `>=1.2.0, <2.0.0` or `^1.4`.
+    pub range: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BundleArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Directory to bundle; defaults to the repository root.
+    #[serde(default)]
+    pub path: Option<String>,
+    pub r#ref: Option<String>,
+    /// Globs a file path must match to be bundled; empty means every file.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs excluding matching file paths.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Upper bound on the bundle size, headers included.
+    #[serde(default = "default_bundle_bytes")]
+    pub max_total_bytes: usize,
+    /// Files fetched at once; defaults to, and is capped by, the server's `--batch-concurrency`.
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GrepRepoArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Directory to search; defaults to the repository root.
+    #[serde(default)]
+    pub path: Option<String>,
+    pub r#ref: Option<String>,
+    /// Regular expression matched against each line, e.g. `(?i)todo` for a case-insensitive search.
+    pub pattern: String,
+    /// Globs a file path must match to be searched; empty means every file.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Most files fetched and searched; capped by the server.
+    #[serde(default = "default_grep_max_files")]
+    pub max_files: usize,
+    /// Files fetched at once; defaults to, and is capped by, the server's `--batch-concurrency`.
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CommitDiffArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Commit sha, branch, or tag whose head commit is diffed against its parent.
+    pub r#ref: String,
+    #[serde(default)]
+    pub format: DiffFormat,
+    /// Truncate the diff to this many characters.
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetBlobArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Full blob sha, as listed by a git tree or commit.
+    pub sha: String,
+    /// Truncate the content to this many characters.
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+    /// Replace bytes that are not valid UTF-8 with U+FFFD instead of failing as binary.
+    #[serde(default)]
+    pub lossy: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MainFileArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    pub r#ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FileWithImportsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// A Rust, Python, or JavaScript/TypeScript file.
+    pub path: String,
+    pub r#ref: Option<String>,
+    /// Most imported files to fetch; capped by the server.
+    #[serde(default = "default_max_imports")]
+    pub max_imports: usize,
+    /// Files fetched at once; defaults to, and is capped by, the server's `--batch-concurrency`.
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CommunityFileArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    pub kind: CommunityFileKind,
+    pub r#ref: Option<String>,
+}
+
+/// Community health file GitHub recognizes in a repository.
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CommunityFileKind {
+    /// How to report vulnerabilities.
+    Security,
+    /// How to contribute.
+    Contributing,
+    CodeOfConduct,
+}
+
+impl CommunityFileKind {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            CommunityFileKind::Security => "SECURITY.md",
+            CommunityFileKind::Contributing => "CONTRIBUTING.md",
+            CommunityFileKind::CodeOfConduct => "CODE_OF_CONDUCT.md",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ArchiveUrlArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Branch, tag, or commit to archive; defaults to the default branch.
+    pub r#ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WebUrlArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// File or directory to link; defaults to the repository root.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Branch, tag, or commit to link at; defaults to the default branch.
+    pub r#ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PathExistsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    pub path: String,
+    pub r#ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GistArgs {
+    /// Gist id, the hex string at the end of its URL.
+    pub id: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LicenseCheckArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// SPDX id of the license of the project that would include this repository's code,
+    /// e.g. `Apache-2.0`.
+    #[serde(default)]
+    pub against: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ProjectTypeArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    pub r#ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DependenciesArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    pub r#ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetReposArgs {
+    /// Repositories as `owner/repo` (at most 50).
+    pub repos: Vec<String>,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResolveRefsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Branches, tags, or shas to resolve (at most 50).
+    pub refs: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RepoInfo {
+    pub description: Option<String>,
+    pub stars: u64,
+    pub forks: u64,
+    pub license: Option<LicenseInfo>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RepoResponse {
+    pub repo: Option<RepoInfo>,
+    /// Canonical `owner/repo` when the requested repository was renamed or transferred.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirected_to: Option<String>,
+    /// True when the repository still has the `etag` passed; `repo` is then absent.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub not_modified: bool,
+    /// GitHub's current ETag for the repository, set when `etag` was passed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+}
+
+/// Provenance attached to every tool result when the server runs with `--include-meta`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Meta {
+    /// Version of this server.
+    pub version: String,
+    pub api_base: String,
+    /// Ref the result was read from: the fallback ref actually used, else the requested one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#ref: Option<String>,
+    /// When the result was produced, as an RFC 3339 UTC timestamp.
+    pub timestamp: String,
+    /// Requests retried while producing the result, after rate limits or upstream failures.
+    pub retries: u32,
+    /// Total time spent backing off before those retries, in milliseconds.
+    pub backoff_ms: u64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RepoResult {
+    /// The `owner/repo` string as requested.
+    pub repo: String,
+    /// Absent when the lookup failed; `error` then says why.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<RepoInfo>,
+    /// Canonical `owner/repo` when the requested repository was renamed or transferred.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirected_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiErrorBody>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GetReposResponse {
+    /// One entry per requested repository, in request order.
+    pub repos: Vec<RepoResult>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RefreshRepoCacheResponse {
+    /// Metadata fetched fresh from GitHub, or `null` when the repository was not found.
+    pub repo: Option<RepoInfo>,
+    /// Canonical `owner/repo` when the requested repository was renamed or transferred.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirected_to: Option<String>,
+    /// The default branch as GitHub reports it now.
+    pub default_branch: Option<String>,
+    /// Cached responses for the repository that were dropped.
+    pub cleared: usize,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Gist {
+    pub description: Option<String>,
+    /// Files in name order.
+    pub files: Vec<GistFile>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GistFile {
+    pub filename: String,
+    pub language: Option<String>,
+    pub content: String,
+    /// True when GitHub cut `content` short because the file is over 1 MB.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GistResponse {
+    pub gist: Gist,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LicenseCheckResponse {
+    /// SPDX id GitHub detected for the repository; `null` when it found no license.
+    pub spdx_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub against: Option<String>,
+    /// Whether the repository's code can be included in an `against`-licensed project;
+    /// present when `against` was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Compatibility>,
+    /// Reminder that `verdict` is advisory, present alongside it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Advisory license compatibility from a small built-in matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Compatibility {
+    Compatible,
+    Incompatible,
+    /// The matrix does not cover the pair, or the answer depends on details it ignores.
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LicenseInfo {
+    pub key: Option<String>,
+    pub name: Option<String>,
+    pub spdx_id: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LicenseText {
+    pub spdx_id: Option<String>,
+    pub name: Option<String>,
+    /// Path of the license file in the repository.
+    pub path: String,
+    /// Decoded license text.
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LicenseResponse {
+    /// `null` when GitHub detected no license file.
+    pub license: Option<LicenseText>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TreeEntry {
+    pub r#type: EntryType,
+    /// Base name of the entry (no parent path), or `a/b/c` for a collapsed directory chain.
+    pub name: String,
+    /// Full path relative to the repository root, kept internal for tree assembly.
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub path: String,
+    /// Path relative to the requested root, set when `include_paths` is requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rel_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submodule_git_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeEntry>,
+}
+
+#[cfg(test)]
+impl TreeEntry {
+    /// A bare entry at `path` for tests, named after its last segment.
+    pub fn stub(r#type: EntryType, path: &str) -> Self {
+        TreeEntry {
+            r#type,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            rel_path: None,
+            size: None,
+            target: None,
+            submodule_git_url: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn stub_file(path: &str) -> Self {
+        Self::stub(EntryType::File, path)
+    }
+
+    pub fn stub_dir(path: &str, children: Vec<TreeEntry>) -> Self {
+        TreeEntry {
+            children,
+            ..Self::stub(EntryType::Dir, path)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TreeResponse {
+    pub entries: Vec<TreeEntry>,
+    /// Depth actually expanded, after clamping to the server's `--max-tree-depth`.
+    pub depth: usize,
+    /// True when the repository has no commits yet, so there is nothing to list.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub empty: bool,
+    /// True when `entries` is partial: directories left unopened have no `children`, or
+    /// nothing is listed because `.gitignore` could not be applied in time.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// True when `deadline_secs` ran out before the crawl finished, or before `.gitignore`
+    /// was read for `respect_gitignore`, in which case no entries are returned.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DirSizeResponse {
+    /// Sum of the sizes of every file counted.
+    pub total_bytes: u64,
+    pub file_count: usize,
+    pub dir_count: usize,
+    /// Depth actually walked; directories at this depth are counted but not entered.
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ExtensionCount {
+    /// Lowercased extension without the dot; absent for files that have none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext: Option<String>,
+    pub count: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DirProfileResponse {
+    /// Files grouped by extension, most common first.
+    pub by_extension: Vec<ExtensionCount>,
+    pub total_files: usize,
+    pub total_dirs: usize,
+    /// Depth actually walked; directories at this depth are counted but not entered.
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TreeGraphResponse {
+    /// The diagram source, one node per entry labelled with its base name.
+    pub graph: String,
+    pub format: GraphFormat,
+    /// Nodes drawn, the root included.
+    pub nodes: usize,
+    /// Set when the node cap left the deepest entries out.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TreeEstimateResponse {
+    /// Files directly under `path`.
+    pub files: usize,
+    /// Directories directly under `path`.
+    pub directories: usize,
+    /// Estimated API calls for a `tree` of each depth from 1 up to the requested depth,
+    /// assuming every directory holds as many subdirectories as `path` does.
+    pub estimates: Vec<DepthEstimate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct DepthEstimate {
+    pub depth: usize,
+    pub calls: u64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct StatsResponse {
+    /// Absent when `not_modified` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item: Option<Stats>,
+    /// True when GitHub reported no change since `if_modified_since` or to `etag`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub not_modified: bool,
+    /// GitHub's current ETag for the path, set when `etag` was passed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    File,
+    Dir,
+    Symlink,
+    Submodule,
+    /// A content type GitHub reported that this server does not recognize.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineRange {
+    /// A single number N or a prefix like `..N` means keep lines 1..=N.
+    End(usize),
+    /// A range like `start..end` or `start...end` keeps lines start..=end.
+    Range { start: usize, end: usize },
+    /// A suffix like `start..` keeps lines start..=EOF.
+    Start(usize),
+}
+
+impl LineRange {
+    pub fn bounds(self) -> (usize, Option<usize>) {
+        match self {
+            LineRange::End(end) => (1, Some(end)),
+            LineRange::Range { start, end } => (start, Some(end)),
+            LineRange::Start(start) => (start, None),
+        }
+    }
+}
+
+impl Serialize for LineRange {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for LineRange {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LineRangeVisitor;
+
+        impl<'de> Visitor<'de> for LineRangeVisitor {
+            type Value = LineRange;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a line range like \"1..200\", \"1...200\", \"..200\", \"1..\", \"1:200\", \"1:\", \":200\", or a positive integer")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(LineRange::End(value as usize))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if value < 0 {
+                    return Err(E::invalid_value(Unexpected::Signed(value), &self));
+                }
+
+                Ok(LineRange::End(value as usize))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                parse_line_range(value)
+                    .ok_or_else(|| E::invalid_value(Unexpected::Str(value), &self))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visit_str(&value)
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let _ = seq;
+                Err(de::Error::invalid_type(Unexpected::Seq, &self))
+            }
+        }
+
+        deserializer.deserialize_any(LineRangeVisitor)
+    }
+}
+
+impl fmt::Display for LineRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineRange::End(end) => write!(f, "..{}", end),
+            LineRange::Range { start, end } => write!(f, "{}..{}", start, end),
+            LineRange::Start(start) => write!(f, "{}..", start),
+        }
+    }
+}
+
+impl JsonSchema for LineRange {
+    fn schema_name() -> Cow<'static, str> {
+        Cow::from("LineRange")
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": ["string", "integer"],
+            "description": "Line ranges like \"1..200\", \"1...200\", \"1..\", \"..200\", \"1:200\", \"1:\", or \":200\"; a bare number N keeps lines 1..=N.",
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct FileResponse {
+    pub content: String,
+    /// True when the requested `ref` was not found and the default branch was read instead.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub used_fallback: bool,
+    /// The ref the content was actually read from, set when a fallback happened.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_ref: Option<String>,
+    /// Path of the file actually read, set when `path` was a symlink that got resolved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_path: Option<String>,
+    /// Likely language, inferred locally from the file name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Likely MIME type, inferred locally from the file name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime: Option<String>,
+    /// With `line_ranges`, the merged ranges `content` holds, in order and clamped to the
+    /// file; each piece is whole lines, so it starts where the previous one ended.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_ranges: Option<Vec<LineRange>>,
+    /// Position of `content` within the file, set in chunk mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<ChunkInfo>,
+    /// True when `lossy` replaced invalid UTF-8 in the file with U+FFFD.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub had_invalid_utf8: bool,
+    /// Hex SHA-256 of the file's raw bytes, before `lossy` decoding, normalization, or any
+    /// line, char, or chunk limits; set when `with_hash` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_sha256: Option<String>,
+    /// Chars in the whole file (after normalization, before any line, char, or chunk limit).
+    pub original_length: usize,
+    /// Chars of the file in `content`, not counting any `--truncation-marker`.
+    pub returned_length: usize,
+    /// True when `content` is only part of the file because of `line_range`, `max_chars`,
+    /// or chunking.
+    pub truncated: bool,
+    /// True when GitHub reported no change since `if_modified_since` or to `etag`; nothing
+    /// else is set but `etag`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub not_modified: bool,
+    /// GitHub's current ETag for the file, set when `etag` was passed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// True when the file is a Git LFS pointer: `content` is the pointer text, and the real
+    /// file lives in LFS storage, which this server does not read.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_lfs: bool,
+    /// The LFS object the pointer stands in for, set with `is_lfs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lfs: Option<LfsPointer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct LfsPointer {
+    /// Object id, e.g. `sha256:4d7a...`.
+    pub oid: String,
+    /// Size of the real file in bytes.
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct ChunkInfo {
+    pub chunk_index: usize,
+    pub total_chunks: usize,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffStatus {
+    Added,
+    Removed,
+    Modified,
+    Unchanged,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DiffSide {
+    pub owner: String,
+    pub repo: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#ref: Option<String>,
+    /// False when the path does not exist on this side.
+    pub exists: bool,
+    pub lines: usize,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DiffFilesResponse {
+    pub status: DiffStatus,
+    /// Unified diff from `left` to `right`; empty when the files are identical.
+    pub diff: String,
+    pub left: DiffSide,
+    pub right: DiffSide,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MergeBaseFileArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Branch, tag, or sha on one side of the comparison.
+    pub base: String,
+    /// Branch, tag, or sha on the other side.
+    pub head: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MergeBaseFileResponse {
+    /// Sha of the common ancestor the file was read at.
+    pub merge_base: String,
+    pub path: String,
+    /// False when the path did not exist at the merge base.
+    pub exists: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ToolDescription {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON schema of the tool's arguments.
+    pub input_schema: serde_json::Map<String, serde_json::Value>,
+    /// JSON schema of the tool's structured result, when it declares one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DescribeToolsResponse {
+    /// Every tool this server exposes, sorted by name.
+    pub tools: Vec<ToolDescription>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Stats {
+    #[serde(rename = "type")]
+    pub r#type: EntryType,
+    /// Base name of the entry (no parent path), or `a/b/c` for a collapsed directory chain.
+    pub name: String,
+    /// Full path relative to the repository root.
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submodule_git_url: Option<String>,
+    /// What a symlink finally points at, with `resolve_target`; absent when the link dangles,
+    /// loops, or leaves the readable tree.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_type: Option<EntryType>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchArgs {
+    pub query: String,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+    /// Follow pagination (ignoring `page`) up to `total_count` or GitHub's 1000-result cap.
+    #[serde(default)]
+    pub all_pages: bool,
+    /// Extra query parameters passed to GitHub as is, for search options not modeled here;
+    /// `q`, `page`, and `per_page` are reserved.
+    #[serde(default)]
+    pub extra_params: Option<BTreeMap<String, String>>,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListReposArgs {
+    pub owner: String,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+    /// Drop forks from the returned page.
+    #[serde(default)]
+    pub exclude_forks: bool,
+    /// Drop archived repositories from the returned page.
+    #[serde(default)]
+    pub exclude_archived: bool,
+    /// Fetch every page (up to the server's `--max-list-pages`) instead of `page`.
+    #[serde(default)]
+    pub all_pages: bool,
+    /// Whether `owner` is a user or an organization; without it the user endpoint is tried
+    /// first and the organization one after a 404.
+    #[serde(default)]
+    pub owner_type: Option<OwnerType>,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OwnerType {
+    User,
+    Org,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListIssuesArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Issue state to list; GitHub defaults to `open`.
+    #[serde(default)]
+    pub state: Option<IssueState>,
+    /// Only issues carrying all of these labels.
+    #[serde(default)]
+    pub labels: Option<Vec<String>>,
+    /// Keep pull requests, which GitHub's issues endpoint also returns.
+    #[serde(default)]
+    pub include_prs: bool,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListPullsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Pull request state to list; GitHub defaults to `open`. `closed` includes merged ones.
+    #[serde(default)]
+    pub state: Option<IssueState>,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListDeploymentsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Only deployments to this environment, e.g. `production`.
+    #[serde(default)]
+    pub environment: Option<String>,
+    /// Only deployments of this branch, tag, or sha.
+    #[serde(default)]
+    pub r#ref: Option<String>,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListMilestonesArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Milestone state to list; GitHub defaults to `open`.
+    #[serde(default)]
+    pub state: Option<IssueState>,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListCollaboratorsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// GitHub defaults to `all`.
+    #[serde(default)]
+    pub affiliation: Option<Affiliation>,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListPullFilesArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Pull request number.
+    pub number: u64,
+    /// Include each file's unified diff hunk, which can be large.
+    #[serde(default)]
+    pub include_patch: bool,
+    /// Fetch only this page; without `page`/`per_page` every page is fetched.
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListCommitsArgs {
+    #[serde(default)]
+    pub owner: String,
+    pub repo: String,
+    /// Another GitHub API base to call instead, honored only with `--allow-base-override`.
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Branch, tag, or sha to list history from; defaults to the default branch.
+    #[serde(default)]
+    pub r#ref: Option<String>,
+    /// Only commits after this ISO-8601 date or timestamp, e.g. `2024-05-01` or `2024-05-01T12:00:00Z`.
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Only commits before this ISO-8601 date or timestamp.
+    #[serde(default)]
+    pub until: Option<String>,
+    /// `desc` (default) for newest first or `asc` for oldest first, applied within the fetched page.
+    #[serde(default)]
+    pub order: Option<Order>,
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueState {
+    Open,
+    Closed,
+    All,
+}
+
+impl IssueState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueState::Open => "open",
+            IssueState::Closed => "closed",
+            IssueState::All => "all",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    /// Newest first.
+    #[default]
+    Desc,
+    /// Oldest first.
+    Asc,
+}
+
+/// Which collaborators to list, as GitHub's `affiliation` filter.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Affiliation {
+    /// Outside collaborators of an organization-owned repository.
+    Outside,
+    /// Collaborators with permissions granted directly, regardless of organization membership.
+    Direct,
+    All,
+}
+
+impl Affiliation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Affiliation::Outside => "outside",
+            Affiliation::Direct => "direct",
+            Affiliation::All => "all",
+        }
+    }
+}
+
+/// Raw text representation of a commit served by the commits endpoint.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffFormat {
+    /// Plain unified diff.
+    #[default]
+    Diff,
+    /// `git format-patch` output, including the commit message and author.
+    Patch,
+}
+
+impl DiffFormat {
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            DiffFormat::Diff => "application/vnd.github.diff",
+            DiffFormat::Patch => "application/vnd.github.patch",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchResult {
+    pub name: String,
+    pub path: String,
+    pub repository: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Matches GitHub reported for the query, which may exceed what can be fetched.
+    pub total_count: u64,
+    /// Results returned in this response.
+    pub collected: usize,
+    /// GitHub's search timed out and returned only part of the matches.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub incomplete: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RepoSummary {
+    pub name: String,
+    pub full_name: String,
+    pub private: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub html_url: String,
+    pub fork: bool,
+    pub archived: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReposResponse {
+    pub repos: Vec<RepoSummary>,
+    /// More pages remained when the server's `--max-list-pages` cap was reached.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Items fetched before the cap, present only when `truncated`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct IssueSummary {
+    pub number: u64,
+    pub title: String,
+    pub state: String,
+    pub labels: Vec<String>,
+    pub comments: u64,
+    pub html_url: String,
+    pub created_at: String,
+    /// Set for pull requests, which only appear when `include_prs` is requested.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_pull_request: bool,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-pub struct RepoResponse {
-    pub repo: Option<RepoInfo>,
+pub struct IssuesResponse {
+    pub issues: Vec<IssueSummary>,
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
-pub struct LicenseInfo {
-    pub key: Option<String>,
-    pub name: Option<String>,
-    pub spdx_id: Option<String>,
-    pub url: Option<String>,
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct IssueCountsResponse {
+    /// Open issues, pull requests excluded.
+    pub open: u64,
+    /// Closed issues, pull requests excluded.
+    pub closed: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
-pub struct TreeEntry {
-    pub r#type: EntryType,
-    /// Base name of the entry (no parent path).
-    pub name: String,
-    /// Full path relative to the repository root, kept internal for tree assembly.
-    #[serde(skip)]
-    #[schemars(skip)]
-    pub path: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub size: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub target: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub submodule_git_url: Option<String>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub children: Vec<TreeEntry>,
+pub struct PullSummary {
+    pub number: u64,
+    pub title: String,
+    /// `open`, `closed`, or `merged` (a closed pull request whose changes were merged).
+    pub state: String,
+    /// Branch the changes come from.
+    pub head_ref: String,
+    /// Branch the changes would be merged into.
+    pub base_ref: String,
+    /// Login of the author.
+    pub user: String,
+    pub draft: bool,
+    pub html_url: String,
+    pub created_at: String,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-pub struct TreeResponse {
-    pub entries: Vec<TreeEntry>,
+pub struct PullsResponse {
+    pub pulls: Vec<PullSummary>,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-pub struct StatsResponse {
-    pub item: Stats,
+pub struct Deployment {
+    pub id: u64,
+    pub environment: String,
+    /// Branch, tag, or sha that was requested for deployment.
+    pub r#ref: String,
+    /// Commit that was deployed.
+    pub sha: String,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy)]
-#[serde(rename_all = "lowercase")]
-pub enum EntryType {
-    File,
-    Dir,
-    Symlink,
-    Submodule,
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DeploymentsResponse {
+    /// Newest first; empty when the repository has no deployments.
+    pub deployments: Vec<Deployment>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum LineRange {
-    /// A single number N or a prefix like `..N` means keep lines 1..=N.
-    End(usize),
-    /// A range like `start..end` or `start...end` keeps lines start..=end.
-    Range { start: usize, end: usize },
-    /// A suffix like `start..` keeps lines start..=EOF.
-    Start(usize),
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Milestone {
+    pub number: u64,
+    pub title: String,
+    /// `open` or `closed`.
+    pub state: String,
+    pub open_issues: u64,
+    pub closed_issues: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
-impl LineRange {
-    pub fn bounds(self) -> (usize, Option<usize>) {
-        match self {
-            LineRange::End(end) => (1, Some(end)),
-            LineRange::Range { start, end } => (start, Some(end)),
-            LineRange::Start(start) => (start, None),
-        }
-    }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MilestonesResponse {
+    pub milestones: Vec<Milestone>,
+    /// Set instead of an error when the repository has issues, and so milestones, disabled.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub issues_disabled: bool,
 }
 
-impl Serialize for LineRange {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_str(&self.to_string())
-    }
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CollaboratorPermissions {
+    pub admin: bool,
+    pub maintain: bool,
+    pub push: bool,
+    pub triage: bool,
+    pub pull: bool,
 }
 
-impl<'de> Deserialize<'de> for LineRange {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct LineRangeVisitor;
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Collaborator {
+    pub login: String,
+    pub permissions: CollaboratorPermissions,
+    /// Highest role on the repository, e.g. `admin`, `write`, `read`, or a custom role.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_name: Option<String>,
+    pub html_url: String,
+}
 
-        impl<'de> Visitor<'de> for LineRangeVisitor {
-            type Value = LineRange;
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CollaboratorsResponse {
+    pub collaborators: Vec<Collaborator>,
+}
 
-            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                f.write_str("a line range like \"1..200\", \"1...200\", \"..200\", \"1..\", \"1:200\", \"1:\", \":200\", or a positive integer")
-            }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ChangedFile {
+    pub filename: String,
+    /// `added`, `removed`, `modified`, `renamed`, `copied`, `changed`, or `unchanged`.
+    pub status: String,
+    pub additions: u64,
+    pub deletions: u64,
+    /// Unified diff hunk; absent unless requested, and for binary or very large files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<String>,
+}
 
-            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                Ok(LineRange::End(value as usize))
-            }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PullFilesResponse {
+    pub files: Vec<ChangedFile>,
+    /// More pages remained when the server's `--max-list-pages` cap was reached.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Next page number when a single page was requested and more follow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page: Option<usize>,
+    /// Number of the last page when a single page was requested and GitHub reported it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_page: Option<usize>,
+}
 
-            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                if value < 0 {
-                    return Err(E::invalid_value(Unexpected::Signed(value), &self));
-                }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommitSummary {
+    pub sha: String,
+    /// First line of the commit message.
+    pub summary: String,
+    /// Author name as recorded in the commit.
+    pub author: String,
+    /// Author date of the commit.
+    pub date: String,
+    pub html_url: String,
+}
 
-                Ok(LineRange::End(value as usize))
-            }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommitsResponse {
+    pub commits: Vec<CommitSummary>,
+}
 
-            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                parse_line_range(value)
-                    .ok_or_else(|| E::invalid_value(Unexpected::Str(value), &self))
-            }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WeeklyCommits {
+    /// Date (`YYYY-MM-DD`, UTC) of the Sunday starting the week.
+    pub week: String,
+    pub total: u64,
+    /// Commits per day, Sunday first.
+    pub days: Vec<u64>,
+}
 
-            fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                self.visit_str(&value)
-            }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommitActivityResponse {
+    /// The last 52 weeks, oldest first; empty when the repository has no commits.
+    pub weeks: Vec<WeeklyCommits>,
+}
 
-            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'de>,
-            {
-                let _ = seq;
-                Err(de::Error::invalid_type(Unexpected::Seq, &self))
-            }
-        }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ResolvedRef {
+    pub r#ref: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha: Option<String>,
+    pub exists: bool,
+}
 
-        deserializer.deserialize_any(LineRangeVisitor)
-    }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GrepMatch {
+    pub path: String,
+    /// 1-based line number.
+    pub line_no: usize,
+    pub line: String,
 }
 
-impl fmt::Display for LineRange {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            LineRange::End(end) => write!(f, "..{}", end),
-            LineRange::Range { start, end } => write!(f, "{}..{}", start, end),
-            LineRange::Start(start) => write!(f, "{}..", start),
-        }
-    }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GrepResponse {
+    pub matches: Vec<GrepMatch>,
+    /// Text files actually searched.
+    pub files_searched: usize,
+    /// Whether files or matches were left out to stay within the file, byte, or match limits.
+    pub truncated: bool,
 }
 
-impl JsonSchema for LineRange {
-    fn schema_name() -> Cow<'static, str> {
-        Cow::from("LineRange")
-    }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BundleResponse {
+    /// Concatenated files, each introduced by a `==== path ====` header line.
+    pub content: String,
+    /// Files included in `content`, in order.
+    pub files: Vec<String>,
+    /// Matching files left out because they are binary.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_binary: Vec<String>,
+    /// Whether matching files were left out to stay within `max_total_bytes`.
+    pub truncated: bool,
+}
 
-    fn json_schema(_: &mut SchemaGenerator) -> Schema {
-        json_schema!({
-            "type": ["string", "integer"],
-            "description": "Line ranges like \"1..200\", \"1...200\", \"1..\", \"..200\", \"1:200\", \"1:\", or \":200\"; a bare number N keeps lines 1..=N.",
-        })
-    }
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommitDiffResponse {
+    pub content: String,
+    /// Whether `content` was cut short by `max_chars`.
+    pub truncated: bool,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-pub struct FileResponse {
+pub struct BlobResponse {
+    pub sha: String,
+    /// Size of the blob in bytes.
+    pub size: u64,
     pub content: String,
+    /// Whether `content` was cut short by `max_chars`.
+    pub truncated: bool,
+    /// True when `lossy` replaced invalid UTF-8 in the blob with U+FFFD.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub had_invalid_utf8: bool,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-pub struct Stats {
-    #[serde(rename = "type")]
-    pub r#type: EntryType,
-    /// Base name of the entry (no parent path).
-    pub name: String,
-    /// Full path relative to the repository root.
+pub struct FileWithImportsResponse {
     pub path: String,
+    pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub size: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub target: Option<String>,
+    pub language: Option<String>,
+    /// Contents of the repository files the file imports, keyed by path.
+    pub imports: BTreeMap<String, String>,
+    /// Imports that look local but matched no file in the repository.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unresolved: Vec<String>,
+    /// Whether more imports resolved than `max_imports` allowed fetching.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MainFileResponse {
+    /// Entry file that was detected, relative to the repository root.
+    pub path: String,
+    pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub submodule_git_url: Option<String>,
+    pub language: Option<String>,
 }
 
-#[derive(Debug, Deserialize, JsonSchema)]
-pub struct SearchArgs {
-    pub query: String,
-    #[serde(default)]
-    pub page: Option<usize>,
-    #[serde(default)]
-    pub per_page: Option<usize>,
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommunityFileResponse {
+    /// Location the file was found at, relative to the repository root.
+    pub path: String,
+    pub content: String,
 }
 
-#[derive(Debug, Deserialize, JsonSchema)]
-pub struct ListReposArgs {
-    pub owner: String,
-    #[serde(default)]
-    pub page: Option<usize>,
-    #[serde(default)]
-    pub per_page: Option<usize>,
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WebUrlResponse {
+    /// Page for the path on GitHub's web interface.
+    pub url: String,
+    /// What `path` is, which decides between a `tree` and a `blob` page.
+    #[serde(rename = "type")]
+    pub r#type: EntryType,
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
-pub struct SearchResult {
-    pub name: String,
-    pub path: String,
-    pub repository: String,
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PathExistsResponse {
+    pub exists: bool,
+    /// What the path is; absent when it does not exist.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<EntryType>,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-pub struct SearchResponse {
-    pub results: Vec<SearchResult>,
+pub struct ArchiveUrlResponse {
+    /// Download location of the zip archive; for private repositories it embeds a token
+    /// that expires after a few minutes.
+    pub url: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
-pub struct RepoSummary {
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ProjectTypeResponse {
+    /// Project types inferred from root manifests, e.g. `Rust` or `Node`.
+    pub detected: Vec<String>,
+    /// Root files that were recognized as manifests.
+    pub manifests: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Dependency {
     pub name: String,
-    pub full_name: String,
-    pub private: bool,
+    /// Version requirement as written; absent for path, git, or workspace dependencies.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-    pub html_url: String,
+    pub version_req: Option<String>,
+    pub kind: DependencyKind,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-pub struct ReposResponse {
-    pub repos: Vec<RepoSummary>,
+pub struct DependenciesResponse {
+    /// Manifest the dependencies were read from, e.g. `Cargo.toml`.
+    pub manifest: String,
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ResolveRefsResponse {
+    /// One entry per requested ref, in request order.
+    pub refs: Vec<ResolvedRef>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SemverTagsResponse {
+    /// Matching tag names, lowest version first.
+    pub tags: Vec<String>,
+    /// More pages of tags remained when the server's `--max-list-pages` cap was reached, so
+    /// matches among them are missing.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct TagsResponse {
     pub tags: Vec<String>,
+    /// Commit and date per tag, newest first, present when `detailed` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<TagDetail>>,
+    /// True when more tags were listed than `detailed` dates, so `details` leaves some out.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub details_truncated: bool,
+    /// More pages remained when the server's `--max-list-pages` cap was reached.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Items fetched before the cap, present only when `truncated`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched: Option<usize>,
+    /// Next page number when a single page was requested and more follow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page: Option<usize>,
+    /// Number of the last page when a single page was requested and GitHub reported it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_page: Option<usize>,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct BranchesResponse {
     pub branches: Vec<String>,
+    /// Head commit and protection per branch, present when `detailed` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<BranchDetail>>,
+    /// More pages remained when the server's `--max-list-pages` cap was reached.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Items fetched before the cap, present only when `truncated`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched: Option<usize>,
+    /// Next page number when a single page was requested and more follow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page: Option<usize>,
+    /// Number of the last page when a single page was requested and GitHub reported it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_page: Option<usize>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TagDetail {
+    pub name: String,
+    pub commit_sha: String,
+    /// Date of the tagged commit (RFC 3339).
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BranchDetail {
+    pub name: String,
+    pub commit_sha: String,
+    pub protected: bool,
+}
+
+pub fn default_grep_max_files() -> usize {
+    100
+}
+
+pub fn default_max_imports() -> usize {
+    10
+}
+
+pub fn default_bundle_bytes() -> usize {
+    256 * 1024
 }
 
 pub fn default_depth() -> usize {
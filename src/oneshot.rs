@@ -0,0 +1,58 @@
+use anyhow::{Context, bail};
+use rmcp::{
+    ServiceExt,
+    model::{CallToolRequestParam, RawContent},
+};
+use serde_json::Value;
+
+use crate::server::GithubServer;
+
+/// Buffer size of the in-memory pipe between the one-shot client and the server.
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+/// Invokes a single tool through the server's router and returns its JSON result.
+///
+/// The call travels over an in-memory MCP session, so argument parsing, tool
+/// filtering, and per-call behavior are exactly those of the stdio server.
+pub async fn call_tool(server: GithubServer, tool: &str, arguments: &str) -> anyhow::Result<Value> {
+    let arguments = match serde_json::from_str(arguments).context("--args is not valid JSON")? {
+        Value::Object(map) => map,
+        _ => bail!("--args must be a JSON object"),
+    };
+
+    let (server_io, client_io) = tokio::io::duplex(PIPE_CAPACITY);
+    let server_task = tokio::spawn(async move {
+        let service = server.serve(server_io).await?;
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+
+    let client = ().serve(client_io).await?;
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: tool.to_string().into(),
+            arguments: Some(arguments),
+        })
+        .await;
+    client.cancel().await?;
+    server_task.await??;
+
+    let result = result?;
+    let output = result.structured_content.clone().unwrap_or_else(|| {
+        let text: String = result
+            .content
+            .iter()
+            .filter_map(|content| match &content.raw {
+                RawContent::Text(text) => Some(text.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        serde_json::from_str(&text).unwrap_or(Value::String(text))
+    });
+
+    if result.is_error.unwrap_or(false) {
+        bail!("{} failed: {}", tool, output);
+    }
+
+    Ok(output)
+}
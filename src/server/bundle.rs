@@ -0,0 +1,172 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::error::ApiErrorBody;
+
+/// Include/exclude glob filter over repository paths.
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl PathFilter {
+    /// An empty `include` list admits every path not excluded.
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, ApiErrorBody> {
+        Ok(Self {
+            include: if include.is_empty() {
+                None
+            } else {
+                Some(build_glob_set(include)?)
+            },
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        self.include.as_ref().is_none_or(|set| set.is_match(path)) && !self.exclude.is_match(path)
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet, ApiErrorBody> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|err| {
+            ApiErrorBody::new(format!("Invalid glob `{}`: {}", pattern, err), "400")
+        })?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|err| ApiErrorBody::new(err.to_string(), "400"))
+}
+
+pub fn file_header(path: &str) -> String {
+    format!("==== {} ====\n", path)
+}
+
+/// Bytes a file occupies in the bundle: its header, its content, and a closing newline.
+pub fn bundled_size(path: &str, content_bytes: u64) -> u64 {
+    file_header(path).len() as u64 + content_bytes + 1
+}
+
+/// Packs files into a byte budget in listing order. Only files actually included are
+/// charged, and one that does not fit is skipped so smaller ones after it still can be.
+pub struct Packer {
+    remaining: u64,
+    /// Smallest listed cost from each position on, to stop once nothing left could fit.
+    smallest_from: Vec<u64>,
+    next: usize,
+    /// Some file was left out for want of room.
+    pub truncated: bool,
+}
+
+impl Packer {
+    /// `costs` are the listed costs of the files that will be offered, in order.
+    pub fn new(costs: &[u64], max_total: u64) -> Self {
+        let mut smallest_from = vec![u64::MAX; costs.len() + 1];
+        for (i, cost) in costs.iter().enumerate().rev() {
+            smallest_from[i] = smallest_from[i + 1].min(*cost);
+        }
+        Self {
+            remaining: max_total,
+            smallest_from,
+            next: 0,
+            truncated: false,
+        }
+    }
+
+    /// Offers the next file at `cost`, or `None` for one skipped without being charged (e.g.
+    /// binary); returns whether it was included.
+    pub fn offer(&mut self, cost: Option<u64>) -> bool {
+        self.next += 1;
+        let Some(cost) = cost else {
+            return false;
+        };
+        if cost > self.remaining {
+            self.truncated = true;
+            return false;
+        }
+        self.remaining -= cost;
+        true
+    }
+
+    /// True once none of the files still to be offered could fit; they count as left out.
+    pub fn exhausted(&mut self) -> bool {
+        let smallest = self
+            .smallest_from
+            .get(self.next)
+            .copied()
+            .unwrap_or(u64::MAX);
+        let exhausted = smallest != u64::MAX && smallest > self.remaining;
+        self.truncated |= exhausted;
+        exhausted
+    }
+}
+
+/// Text that decoded as UTF-8 can still be binary; NUL bytes are the usual giveaway.
+pub fn looks_binary(content: &str) -> bool {
+    content.contains('\0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_files_that_do_not_fit_and_keeps_packing() {
+        let mut packer = Packer::new(&[10, 50, 5, 5], 20);
+
+        assert!(packer.offer(Some(10)));
+        assert!(!packer.offer(Some(50)));
+        assert!(packer.truncated);
+        assert!(packer.offer(Some(5)));
+        assert!(packer.offer(Some(5)));
+        assert!(!packer.exhausted());
+    }
+
+    #[test]
+    fn skipped_files_cost_nothing() {
+        let mut packer = Packer::new(&[15, 15], 20);
+
+        assert!(!packer.offer(None));
+        assert!(packer.offer(Some(15)));
+        assert!(!packer.truncated);
+    }
+
+    #[test]
+    fn is_exhausted_once_nothing_left_fits() {
+        let mut packer = Packer::new(&[15, 10, 8], 20);
+
+        assert!(packer.offer(Some(15)));
+        assert!(packer.exhausted());
+        assert!(packer.truncated);
+    }
+
+    #[test]
+    fn filters_by_include_and_exclude_globs() {
+        let filter = PathFilter::new(
+            &["src/**/*.rs".to_string()],
+            &["**/generated/**".to_string()],
+        )
+        .unwrap();
+
+        assert!(filter.matches("src/lib.rs"));
+        assert!(filter.matches("src/server/mod.rs"));
+        assert!(!filter.matches("src/generated/api.rs"));
+        assert!(!filter.matches("README.md"));
+
+        let everything = PathFilter::new(&[], &[]).unwrap();
+        assert!(everything.matches("README.md"));
+    }
+
+    #[test]
+    fn rejects_invalid_globs() {
+        assert!(PathFilter::new(&["src/[".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn flags_nul_bytes_as_binary() {
+        assert!(looks_binary("PNG\0\0\0"));
+        assert!(!looks_binary("fn main() {}\n"));
+    }
+}
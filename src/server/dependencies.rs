@@ -0,0 +1,193 @@
+use serde_json::Value;
+
+use crate::{
+    error::ApiErrorBody,
+    models::{Dependency, DependencyKind},
+};
+
+/// Root manifests `get_dependencies` can parse, in the order they are looked for.
+pub const MANIFESTS: &[&str] = &["Cargo.toml", "package.json"];
+
+/// Cargo tables and the kind of dependency each declares.
+const CARGO_TABLES: &[(&str, DependencyKind)] = &[
+    ("dependencies", DependencyKind::Normal),
+    ("dev-dependencies", DependencyKind::Dev),
+    ("build-dependencies", DependencyKind::Build),
+];
+
+/// npm fields and the kind of dependency each declares; optional dependencies are installed
+/// like regular ones when they can be.
+const NPM_FIELDS: &[(&str, DependencyKind)] = &[
+    ("dependencies", DependencyKind::Normal),
+    ("optionalDependencies", DependencyKind::Normal),
+    ("devDependencies", DependencyKind::Dev),
+];
+
+/// Parses the dependencies declared by `manifest`, one of [`MANIFESTS`].
+pub fn parse(manifest: &str, content: &str) -> Result<Vec<Dependency>, ApiErrorBody> {
+    let parsed = match manifest {
+        "Cargo.toml" => parse_cargo(content),
+        "package.json" => parse_npm(content),
+        _ => Err(format!("`{}` is not a supported manifest", manifest)),
+    };
+    parsed.map_err(|err| ApiErrorBody::new(format!("Could not parse {}: {}", manifest, err), "422"))
+}
+
+/// Reads `[dependencies]`, `[dev-dependencies]`, and `[build-dependencies]`, including their
+/// `[target.'cfg(...)'.*]` variants. Entries without a version (path, git, or workspace
+/// dependencies) have no `version_req`.
+fn parse_cargo(content: &str) -> Result<Vec<Dependency>, String> {
+    let manifest: toml::Table = content
+        .parse()
+        .map_err(|err: toml::de::Error| err.to_string())?;
+    let mut tables = vec![&manifest];
+    if let Some(toml::Value::Table(targets)) = manifest.get("target") {
+        tables.extend(targets.values().filter_map(toml::Value::as_table));
+    }
+
+    let mut dependencies = Vec::new();
+    for table in tables {
+        for (section, kind) in CARGO_TABLES {
+            let Some(toml::Value::Table(entries)) = table.get(*section) else {
+                continue;
+            };
+            for (name, spec) in entries {
+                let version_req = match spec {
+                    toml::Value::String(version) => Some(version.clone()),
+                    toml::Value::Table(spec) => spec
+                        .get("version")
+                        .and_then(toml::Value::as_str)
+                        .map(str::to_string),
+                    _ => None,
+                };
+                dependencies.push(Dependency {
+                    name: name.clone(),
+                    version_req,
+                    kind: *kind,
+                });
+            }
+        }
+    }
+    Ok(dependencies)
+}
+
+fn parse_npm(content: &str) -> Result<Vec<Dependency>, String> {
+    let manifest: Value = serde_json::from_str(content).map_err(|err| err.to_string())?;
+
+    let mut dependencies = Vec::new();
+    for (field, kind) in NPM_FIELDS {
+        let Some(Value::Object(entries)) = manifest.get(*field) else {
+            continue;
+        };
+        for (name, version) in entries {
+            dependencies.push(Dependency {
+                name: name.clone(),
+                version_req: version.as_str().map(str::to_string),
+                kind: *kind,
+            });
+        }
+    }
+    Ok(dependencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::models::DependencyKind;
+
+    fn summary(manifest: &str, content: &str) -> Vec<(String, Option<String>, DependencyKind)> {
+        parse(manifest, content)
+            .unwrap()
+            .into_iter()
+            .map(|dep| (dep.name, dep.version_req, dep.kind))
+            .collect()
+    }
+
+    #[test]
+    fn parses_cargo_sections_and_target_tables() {
+        let manifest = r#"
+            [package]
+            name = "demo"
+
+            [dependencies]
+            serde = { version = "1", features = ["derive"] }
+            local = { path = "../local" }
+
+            [dev-dependencies]
+            wiremock = "0.6"
+
+            [build-dependencies]
+            cc = "1.0"
+
+            [target.'cfg(unix)'.dependencies]
+            libc = "0.2"
+        "#;
+
+        assert_eq!(
+            summary("Cargo.toml", manifest),
+            vec![
+                ("local".to_string(), None, DependencyKind::Normal),
+                (
+                    "serde".to_string(),
+                    Some("1".to_string()),
+                    DependencyKind::Normal
+                ),
+                (
+                    "wiremock".to_string(),
+                    Some("0.6".to_string()),
+                    DependencyKind::Dev
+                ),
+                (
+                    "cc".to_string(),
+                    Some("1.0".to_string()),
+                    DependencyKind::Build
+                ),
+                (
+                    "libc".to_string(),
+                    Some("0.2".to_string()),
+                    DependencyKind::Normal
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_npm_dependency_fields() {
+        let manifest = r#"{
+            "name": "demo",
+            "dependencies": { "react": "^18.2.0" },
+            "optionalDependencies": { "fsevents": "~2.3.2" },
+            "devDependencies": { "typescript": "5.4.5" },
+            "peerDependencies": { "react-dom": "*" }
+        }"#;
+
+        assert_eq!(
+            summary("package.json", manifest),
+            vec![
+                (
+                    "react".to_string(),
+                    Some("^18.2.0".to_string()),
+                    DependencyKind::Normal
+                ),
+                (
+                    "fsevents".to_string(),
+                    Some("~2.3.2".to_string()),
+                    DependencyKind::Normal
+                ),
+                (
+                    "typescript".to_string(),
+                    Some("5.4.5".to_string()),
+                    DependencyKind::Dev
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_malformed_manifests() {
+        let err = parse("package.json", "{ not json").unwrap_err();
+
+        assert_eq!(err.code, "422");
+        assert!(err.message.starts_with("Could not parse package.json"));
+    }
+}
@@ -0,0 +1,90 @@
+use similar::TextDiff;
+
+use crate::models::DiffStatus;
+
+/// Lines of unchanged context shown around each hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Builds a unified diff between two optional file contents; a missing side diffs as empty.
+pub fn unified_diff(
+    left: Option<&str>,
+    right: Option<&str>,
+    left_label: &str,
+    right_label: &str,
+) -> (DiffStatus, String) {
+    let status = match (left, right) {
+        (None, _) => DiffStatus::Added,
+        (_, None) => DiffStatus::Removed,
+        (Some(left), Some(right)) if left == right => DiffStatus::Unchanged,
+        _ => DiffStatus::Modified,
+    };
+
+    if status == DiffStatus::Unchanged {
+        return (status, String::new());
+    }
+
+    let old_label = if left.is_some() {
+        left_label
+    } else {
+        "/dev/null"
+    };
+    let new_label = if right.is_some() {
+        right_label
+    } else {
+        "/dev/null"
+    };
+
+    let diff = TextDiff::from_lines(left.unwrap_or_default(), right.unwrap_or_default())
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(old_label, new_label)
+        .to_string();
+
+    (status, diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified_diff;
+    use crate::models::DiffStatus;
+
+    #[test]
+    fn reports_modified_lines_across_repos() {
+        let (status, diff) = unified_diff(
+            Some("a\nb\nc\n"),
+            Some("a\nB\nc\n"),
+            "a/upstream/repo/src/lib.rs",
+            "b/fork/repo/src/lib.rs",
+        );
+
+        assert_eq!(status, DiffStatus::Modified);
+        assert!(diff.starts_with("--- a/upstream/repo/src/lib.rs\n+++ b/fork/repo/src/lib.rs\n"));
+        assert!(diff.contains("-b\n+B\n"));
+    }
+
+    #[test]
+    fn missing_left_side_is_an_addition() {
+        let (status, diff) = unified_diff(None, Some("new\n"), "a/x", "b/y");
+
+        assert_eq!(status, DiffStatus::Added);
+        assert!(diff.starts_with("--- /dev/null\n+++ b/y\n"));
+        assert!(diff.contains("+new\n"));
+    }
+
+    #[test]
+    fn missing_right_side_is_a_removal() {
+        let (status, diff) = unified_diff(Some("old\n"), None, "a/x", "b/y");
+
+        assert_eq!(status, DiffStatus::Removed);
+        assert!(diff.contains("+++ /dev/null\n"));
+        assert!(diff.contains("-old\n"));
+    }
+
+    #[test]
+    fn identical_files_have_no_diff() {
+        let (status, diff) = unified_diff(Some("same\n"), Some("same\n"), "a/x", "b/y");
+
+        assert_eq!(status, DiffStatus::Unchanged);
+        assert!(diff.is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+use serde_json::{Map, Value};
+
+/// Reads the `fields` argument of a tool call, if it names any.
+pub fn requested(arguments: Option<&Map<String, Value>>) -> Option<Vec<String>> {
+    let fields: Vec<String> = arguments?
+        .get("fields")?
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    (!fields.is_empty()).then_some(fields)
+}
+
+/// Trims a tool's structured result down to `fields` in the objects that tool supports
+/// filtering on; results of other tools pass through untouched.
+pub fn retain(tool: &str, result: &mut Value, fields: &[String]) {
+    match tool {
+        "tree" => {
+            if let Some(Value::Array(entries)) = result.get_mut("entries") {
+                retain_entries(entries, fields);
+            }
+        }
+        "get_repo" => {
+            if let Some(Value::Object(repo)) = result.get_mut("repo") {
+                retain_keys(repo, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Filters tree entries at every depth; `children` always stays so the nesting survives.
+fn retain_entries(entries: &mut [Value], fields: &[String]) {
+    for entry in entries {
+        let Value::Object(entry) = entry else {
+            continue;
+        };
+        entry.retain(|key, _| key == "children" || fields.iter().any(|field| field == key));
+        if let Some(Value::Array(children)) = entry.get_mut("children") {
+            retain_entries(children, fields);
+        }
+    }
+}
+
+fn retain_keys(object: &mut Map<String, Value>, fields: &[String]) {
+    object.retain(|key, _| fields.iter().any(|field| field == key));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{requested, retain};
+
+    fn fields(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn tree_entries_keep_only_requested_fields_at_every_depth() {
+        let mut result = json!({
+            "entries": [{
+                "type": "dir",
+                "name": "src",
+                "path": "src",
+                "children": [
+                    { "type": "file", "name": "lib.rs", "path": "src/lib.rs", "size": 120, "children": [] },
+                ],
+            }],
+            "depth": 2,
+        });
+
+        retain("tree", &mut result, &fields(&["name", "type"]));
+
+        assert_eq!(
+            result,
+            json!({
+                "entries": [{
+                    "type": "dir",
+                    "name": "src",
+                    "children": [{ "type": "file", "name": "lib.rs", "children": [] }],
+                }],
+                "depth": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn filters_repo_info_and_leaves_other_tools_alone() {
+        let mut repo = json!({ "repo": { "description": "cool", "stars": 7, "forks": 2 } });
+        retain("get_repo", &mut repo, &fields(&["stars"]));
+        assert_eq!(repo, json!({ "repo": { "stars": 7 } }));
+
+        let mut tags = json!({ "tags": ["v1.0.0"] });
+        retain("list_tags", &mut tags, &fields(&["name"]));
+        assert_eq!(tags, json!({ "tags": ["v1.0.0"] }));
+    }
+
+    #[test]
+    fn reads_non_empty_field_lists() {
+        let arguments = json!({ "fields": ["name", 3, "size"] });
+        assert_eq!(
+            requested(arguments.as_object()),
+            Some(fields(&["name", "size"]))
+        );
+        assert_eq!(requested(json!({ "fields": [] }).as_object()), None);
+        assert_eq!(requested(json!({ "owner": "octo" }).as_object()), None);
+        assert_eq!(requested(None), None);
+    }
+}
@@ -0,0 +1,99 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::models::{EntryType, TreeEntry};
+
+/// Builds a matcher from the text of a repository's root `.gitignore`.
+///
+/// Lines that fail to parse are skipped, as git itself does.
+pub fn parse(content: &str) -> Gitignore {
+    let mut builder = GitignoreBuilder::new("");
+    for line in content.lines() {
+        let _ = builder.add_line(None, line);
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Drops ignored entries, and everything under ignored directories, from a tree.
+///
+/// Paths are matched relative to the repository root. An excluded directory takes its
+/// children with it, so a negation cannot re-include a file under it, matching git.
+pub fn prune(entries: &mut Vec<TreeEntry>, ignore: &Gitignore) {
+    entries.retain(|entry| {
+        let is_dir = matches!(entry.r#type, EntryType::Dir);
+        !ignore.matched(&entry.path, is_dir).is_ignore()
+    });
+    for entry in entries {
+        prune(&mut entry.children, ignore);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, prune};
+    use crate::models::TreeEntry;
+
+    fn paths(entries: &[TreeEntry], out: &mut Vec<String>) {
+        for entry in entries {
+            out.push(entry.path.clone());
+            paths(&entry.children, out);
+        }
+    }
+
+    fn pruned(gitignore: &str, mut entries: Vec<TreeEntry>) -> Vec<String> {
+        prune(&mut entries, &parse(gitignore));
+        let mut out = Vec::new();
+        paths(&entries, &mut out);
+        out
+    }
+
+    #[test]
+    fn ignores_matching_files_at_any_depth() {
+        let kept = pruned(
+            "# build output\n*.log\n",
+            vec![
+                TreeEntry::stub_file("app.log"),
+                TreeEntry::stub_file("README.md"),
+                TreeEntry::stub_dir(
+                    "src",
+                    vec![
+                        TreeEntry::stub_file("src/debug.log"),
+                        TreeEntry::stub_file("src/lib.rs"),
+                    ],
+                ),
+            ],
+        );
+
+        assert_eq!(kept, vec!["README.md", "src", "src/lib.rs"]);
+    }
+
+    #[test]
+    fn negations_re_include_files() {
+        let kept = pruned(
+            "*.log\n!keep.log\n",
+            vec![
+                TreeEntry::stub_file("app.log"),
+                TreeEntry::stub_file("keep.log"),
+            ],
+        );
+
+        assert_eq!(kept, vec!["keep.log"]);
+    }
+
+    #[test]
+    fn directory_patterns_only_match_directories() {
+        let kept = pruned(
+            "build/\n/target\n",
+            vec![
+                TreeEntry::stub_dir("build", vec![TreeEntry::stub_file("build/out.o")]),
+                TreeEntry::stub_file("docs/build"),
+                TreeEntry::stub_dir("target", vec![TreeEntry::stub_file("target/debug")]),
+                TreeEntry::stub_dir(
+                    "crates",
+                    vec![TreeEntry::stub_dir("crates/target", Vec::new())],
+                ),
+            ],
+        );
+
+        assert_eq!(kept, vec!["docs/build", "crates", "crates/target"]);
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+
+use crate::models::{GraphFormat, TreeEntry};
+
+/// A rendered directory diagram and how much of the tree made it in.
+#[derive(Debug)]
+pub struct Graph {
+    pub text: String,
+    pub nodes: usize,
+    pub truncated: bool,
+}
+
+/// Renders `entries` below a `root` node as a parent→child hierarchy labelled with base
+/// names. Nodes are taken level by level, so past `max_nodes` the deepest entries go first.
+pub fn render(root: &str, entries: &[TreeEntry], format: GraphFormat, max_nodes: usize) -> Graph {
+    let mut labels = vec![root];
+    let mut edges = Vec::new();
+    let mut truncated = false;
+
+    let mut queue: VecDeque<(usize, &TreeEntry)> = entries.iter().map(|entry| (0, entry)).collect();
+    while let Some((parent, entry)) = queue.pop_front() {
+        if labels.len() >= max_nodes.max(1) {
+            truncated = true;
+            break;
+        }
+        let id = labels.len();
+        labels.push(&entry.name);
+        edges.push((parent, id));
+        queue.extend(entry.children.iter().map(|child| (id, child)));
+    }
+
+    let text = match format {
+        GraphFormat::Mermaid => {
+            let mut text = String::from("graph TD\n");
+            for (id, label) in labels.iter().enumerate() {
+                text.push_str(&format!(
+                    "    n{id}[\"{}\"]\n",
+                    label.replace('"', "#quot;")
+                ));
+            }
+            for (parent, child) in &edges {
+                text.push_str(&format!("    n{parent} --> n{child}\n"));
+            }
+            text
+        }
+        GraphFormat::Dot => {
+            let mut text = String::from("digraph tree {\n");
+            for (id, label) in labels.iter().enumerate() {
+                let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+                text.push_str(&format!("    n{id} [label=\"{label}\"];\n"));
+            }
+            for (parent, child) in &edges {
+                text.push_str(&format!("    n{parent} -> n{child};\n"));
+            }
+            text.push_str("}\n");
+            text
+        }
+    };
+
+    Graph {
+        text,
+        nodes: labels.len(),
+        truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::models::{GraphFormat, TreeEntry};
+
+    fn sample() -> Vec<TreeEntry> {
+        vec![
+            TreeEntry::stub_dir("src", vec![TreeEntry::stub_file("main.rs")]),
+            TreeEntry::stub_file("say \"hi\".txt"),
+        ]
+    }
+
+    #[test]
+    fn renders_mermaid_flowcharts() {
+        let graph = render("repo", &sample(), GraphFormat::Mermaid, 100);
+        assert_eq!(
+            graph.text,
+            "graph TD\n    n0[\"repo\"]\n    n1[\"src\"]\n    n2[\"say #quot;hi#quot;.txt\"]\n    n3[\"main.rs\"]\n    n0 --> n1\n    n0 --> n2\n    n1 --> n3\n"
+        );
+        assert_eq!(graph.nodes, 4);
+        assert!(!graph.truncated);
+    }
+
+    #[test]
+    fn renders_dot_digraphs() {
+        let graph = render("repo", &sample(), GraphFormat::Dot, 100);
+        assert_eq!(
+            graph.text,
+            "digraph tree {\n    n0 [label=\"repo\"];\n    n1 [label=\"src\"];\n    n2 [label=\"say \\\"hi\\\".txt\"];\n    n3 [label=\"main.rs\"];\n    n0 -> n1;\n    n0 -> n2;\n    n1 -> n3;\n}\n"
+        );
+    }
+
+    #[test]
+    fn drops_the_deepest_nodes_past_the_cap() {
+        let graph = render("repo", &sample(), GraphFormat::Dot, 3);
+        assert_eq!(graph.nodes, 3);
+        assert!(graph.truncated);
+        assert!(!graph.text.contains("main.rs"));
+    }
+}
@@ -0,0 +1,82 @@
+use regex::Regex;
+
+use crate::{error::ApiErrorBody, models::GrepMatch};
+
+/// Longest matched line returned as is; longer ones (minified code) are cut to this many chars.
+const MAX_LINE_CHARS: usize = 500;
+
+pub fn compile(pattern: &str) -> Result<Regex, ApiErrorBody> {
+    Regex::new(pattern)
+        .map_err(|err| ApiErrorBody::new(format!("Invalid pattern `{}`: {}", pattern, err), "400"))
+}
+
+/// Appends the lines of `text` matching `regex` to `out`, stopping once `out` holds `limit`
+/// matches; returns whether matches were left out.
+pub fn search(
+    path: &str,
+    text: &str,
+    regex: &Regex,
+    limit: usize,
+    out: &mut Vec<GrepMatch>,
+) -> bool {
+    for (index, line) in text.lines().enumerate() {
+        if !regex.is_match(line) {
+            continue;
+        }
+        if out.len() >= limit {
+            return true;
+        }
+        out.push(GrepMatch {
+            path: path.to_string(),
+            line_no: index + 1,
+            line: line.chars().take(MAX_LINE_CHARS).collect(),
+        });
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile, search};
+
+    #[test]
+    fn reports_one_based_line_numbers() {
+        let regex = compile(r"fn \w+\(").unwrap();
+        let mut matches = Vec::new();
+
+        let more = search(
+            "src/lib.rs",
+            "use std::fmt;\nfn parse() {}\n\npub fn render(x: u8) {}\n",
+            &regex,
+            10,
+            &mut matches,
+        );
+
+        assert!(!more);
+        let found: Vec<(usize, &str)> = matches
+            .iter()
+            .map(|m| (m.line_no, m.line.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(2, "fn parse() {}"), (4, "pub fn render(x: u8) {}")]
+        );
+    }
+
+    #[test]
+    fn stops_at_the_match_limit() {
+        let regex = compile("x").unwrap();
+        let mut matches = Vec::new();
+
+        assert!(search("a.txt", "x\nx\nx\n", &regex, 2, &mut matches));
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let err = compile("(unclosed").unwrap_err();
+
+        assert_eq!(err.code, "400");
+        assert!(err.message.contains("(unclosed"));
+    }
+}
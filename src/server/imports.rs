@@ -0,0 +1,424 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::language;
+
+/// Languages whose import statements can be mapped to files in the same repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportLanguage {
+    Rust,
+    Python,
+    /// JavaScript and TypeScript, which share module specifier syntax.
+    JavaScript,
+}
+
+impl ImportLanguage {
+    pub fn for_path(path: &str) -> Option<Self> {
+        match language::content_hints(path).language? {
+            "Rust" => Some(Self::Rust),
+            "Python" => Some(Self::Python),
+            "JavaScript" | "TypeScript" => Some(Self::JavaScript),
+            _ => None,
+        }
+    }
+}
+
+static RUST_MOD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_][A-Za-z0-9_]*)\s*;").unwrap()
+});
+static RUST_USE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").unwrap());
+static RUST_ALIAS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s+as\s+[A-Za-z_][A-Za-z0-9_]*").unwrap());
+static PYTHON_FROM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^[ \t]*from[ \t]+(\.*[\w.]*)[ \t]+import[ \t]+\(?([^)#\n]*)").unwrap()
+});
+static PYTHON_IMPORT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^[ \t]*import[ \t]+([^#\n]+)").unwrap());
+static JS_SPECIFIER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:\bfrom\s*|\bimport\s*\(?\s*|\brequire\s*\(\s*)['"]([^'"]+)['"]"#).unwrap()
+});
+
+/// Extensions tried, in order, for an extensionless JavaScript or TypeScript specifier.
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Module specifiers `content` imports, deduplicated in order of appearance.
+///
+/// Rust keeps only `crate::`, `self::`, and `super::` paths (with `mod foo;` read as
+/// `self::foo`) and JavaScript only `./` and `../` specifiers, since anything else names
+/// another package. Python keeps every module; [`candidates`] decides which are local.
+pub fn extract(language: ImportLanguage, content: &str) -> Vec<String> {
+    let mut specs = Vec::new();
+
+    match language {
+        ImportLanguage::Rust => {
+            for captures in RUST_MOD.captures_iter(content) {
+                specs.push(format!("self::{}", &captures[1]));
+            }
+            for captures in RUST_USE.captures_iter(content) {
+                let tree: String = RUST_ALIAS
+                    .replace_all(&captures[1], "")
+                    .split_whitespace()
+                    .collect();
+                expand_use_tree(&tree, &mut specs);
+            }
+            specs.retain(|spec| {
+                ["crate::", "self::", "super::"]
+                    .iter()
+                    .any(|prefix| spec.starts_with(prefix))
+            });
+        }
+        ImportLanguage::Python => {
+            for captures in PYTHON_FROM.captures_iter(content) {
+                let module = &captures[1];
+                if module.chars().all(|c| c == '.') {
+                    // `from . import a, b` imports sibling modules `a` and `b`.
+                    specs.extend(
+                        first_words(&captures[2])
+                            .filter(|name| *name != "*")
+                            .map(|name| format!("{}{}", module, name)),
+                    );
+                } else {
+                    specs.push(module.to_string());
+                }
+            }
+            for captures in PYTHON_IMPORT.captures_iter(content) {
+                specs.extend(first_words(&captures[1]).map(str::to_string));
+            }
+        }
+        ImportLanguage::JavaScript => {
+            specs.extend(
+                JS_SPECIFIER
+                    .captures_iter(content)
+                    .map(|captures| captures[1].to_string())
+                    .filter(|spec| spec.starts_with("./") || spec.starts_with("../")),
+            );
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    specs.retain(|spec| seen.insert(spec.clone()));
+    specs
+}
+
+/// Repository paths `spec`, imported from `file`, may refer to, most specific first.
+///
+/// Empty when the import cannot be local, e.g. a Python module outside the file's package.
+pub fn candidates(language: ImportLanguage, file: &str, spec: &str) -> Vec<String> {
+    match language {
+        ImportLanguage::Rust => rust_candidates(file, spec),
+        ImportLanguage::Python => python_candidates(file, spec),
+        ImportLanguage::JavaScript => js_candidates(file, spec),
+    }
+}
+
+/// Flattens `a::{b, c::{d, e}}` into `a::b`, `a::c::d`, and `a::c::e`.
+fn expand_use_tree(tree: &str, out: &mut Vec<String>) {
+    let Some(open) = tree.find('{') else {
+        out.push(tree.trim_end_matches("::*").to_string());
+        return;
+    };
+    let Some(close) = tree.rfind('}') else {
+        return;
+    };
+
+    let prefix = &tree[..open];
+    let mut depth = 0;
+    let mut start = open + 1;
+    for (offset, c) in tree[open + 1..close].char_indices() {
+        let index = open + 1 + offset;
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                expand_use_tree(&format!("{}{}", prefix, &tree[start..index]), out);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < close {
+        expand_use_tree(&format!("{}{}", prefix, &tree[start..close]), out);
+    }
+}
+
+fn first_words(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .filter_map(|part| part.split_whitespace().next())
+}
+
+fn rust_candidates(file: &str, spec: &str) -> Vec<String> {
+    let mut segments: Vec<&str> = spec.split("::").collect();
+    let mut base = match segments.first() {
+        Some(&"crate") => rust_crate_root(file),
+        Some(&"self") | Some(&"super") => rust_module_dir(file),
+        _ => return Vec::new(),
+    };
+    if segments[0] == "crate" || segments[0] == "self" {
+        segments.remove(0);
+    }
+    while segments.first() == Some(&"super") {
+        segments.remove(0);
+        base = parent_dir(&base).to_string();
+    }
+    segments.retain(|segment| !segment.is_empty() && *segment != "*" && *segment != "self");
+
+    let mut paths = Vec::new();
+    for len in (1..=segments.len()).rev() {
+        let module = join(&base, &segments[..len].join("/"));
+        paths.push(format!("{}.rs", module));
+        paths.push(format!("{}/mod.rs", module));
+    }
+    paths
+}
+
+/// Directory holding a Rust file's child modules: its own directory for `lib.rs`, `main.rs`,
+/// and `mod.rs`, otherwise a directory named after the file.
+fn rust_module_dir(file: &str) -> String {
+    let dir = parent_dir(file);
+    let stem = file_name(file).trim_end_matches(".rs");
+    match stem {
+        "lib" | "main" | "mod" => dir.to_string(),
+        _ => join(dir, stem),
+    }
+}
+
+/// The nearest enclosing `src` directory, or the file's own directory outside of one.
+fn rust_crate_root(file: &str) -> String {
+    let dir = parent_dir(file);
+    let segments: Vec<&str> = dir.split('/').collect();
+    match segments.iter().rposition(|segment| *segment == "src") {
+        Some(index) => segments[..=index].join("/"),
+        None => dir.to_string(),
+    }
+}
+
+fn python_candidates(file: &str, spec: &str) -> Vec<String> {
+    let dots = spec.chars().take_while(|c| *c == '.').count();
+    let segments: Vec<&str> = spec[dots..].split('.').filter(|s| !s.is_empty()).collect();
+
+    let base = if dots > 0 {
+        let mut base = parent_dir(file);
+        for _ in 1..dots {
+            if base.is_empty() {
+                return Vec::new();
+            }
+            base = parent_dir(base);
+        }
+        base.to_string()
+    } else {
+        // An absolute import is only local when it names a package the file sits in.
+        let Some(package) = segments.first() else {
+            return Vec::new();
+        };
+        let dirs: Vec<&str> = parent_dir(file).split('/').collect();
+        let Some(index) = dirs.iter().position(|dir| dir == package) else {
+            return Vec::new();
+        };
+        dirs[..index].join("/")
+    };
+
+    let module = join(&base, &segments.join("/"));
+    if segments.is_empty() {
+        return vec![join(&module, "__init__.py")];
+    }
+    vec![format!("{}.py", module), format!("{}/__init__.py", module)]
+}
+
+fn js_candidates(file: &str, spec: &str) -> Vec<String> {
+    let Some(target) = normalize(&join(parent_dir(file), spec)) else {
+        return Vec::new();
+    };
+
+    let has_extension = file_name(&target)
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| JS_EXTENSIONS.contains(&ext) || ext == "json");
+    if has_extension {
+        return vec![target];
+    }
+
+    let mut paths: Vec<String> = JS_EXTENSIONS
+        .iter()
+        .map(|ext| format!("{}.{}", target, ext))
+        .collect();
+    paths.extend(
+        JS_EXTENSIONS
+            .iter()
+            .map(|ext| format!("{}/index.{}", target, ext)),
+    );
+    paths
+}
+
+pub fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn join(dir: &str, rest: &str) -> String {
+    match (dir.is_empty(), rest.is_empty()) {
+        (true, _) => rest.to_string(),
+        (_, true) => dir.to_string(),
+        _ => format!("{}/{}", dir, rest),
+    }
+}
+
+/// Resolves `.` and `..` segments; `None` when the path climbs above the repository root.
+fn normalize(path: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ImportLanguage, candidates, extract};
+
+    #[test]
+    fn extracts_local_rust_modules_and_uses() {
+        let source = "\
+mod parser;
+pub(crate) mod lexer;
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::models::{TreeEntry, tree::{Node, Leaf}};
+use super::helpers as h;
+pub use self::parser::*;
+";
+
+        assert_eq!(
+            extract(ImportLanguage::Rust, source),
+            vec![
+                "self::parser",
+                "self::lexer",
+                "crate::models::TreeEntry",
+                "crate::models::tree::Node",
+                "crate::models::tree::Leaf",
+                "super::helpers",
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_python_imports() {
+        let source = "\
+import os, json as j
+import mypkg.util
+from . import views, models
+from ..core.base import Thing
+from mypkg.sub import (a, b)
+    from .lazy import load  # inside a function
+";
+
+        assert_eq!(
+            extract(ImportLanguage::Python, source),
+            vec![
+                ".views",
+                ".models",
+                "..core.base",
+                "mypkg.sub",
+                ".lazy",
+                "os",
+                "json",
+                "mypkg.util",
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_relative_js_specifiers() {
+        let source = r#"
+import React from 'react';
+import { a,
+  b } from "./components/button";
+import './styles.css';
+export * from '../shared';
+const util = require('./util');
+const lazy = await import("./lazy.js");
+"#;
+
+        assert_eq!(
+            extract(ImportLanguage::JavaScript, source),
+            vec![
+                "./components/button",
+                "./styles.css",
+                "../shared",
+                "./util",
+                "./lazy.js",
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_rust_paths_to_module_files() {
+        assert_eq!(
+            candidates(
+                ImportLanguage::Rust,
+                "crates/core/src/server/mod.rs",
+                "self::gitignore"
+            ),
+            vec![
+                "crates/core/src/server/gitignore.rs",
+                "crates/core/src/server/gitignore/mod.rs",
+            ]
+        );
+        assert_eq!(
+            candidates(
+                ImportLanguage::Rust,
+                "src/client/retry.rs",
+                "crate::models::Tree"
+            ),
+            vec![
+                "src/models/Tree.rs",
+                "src/models/Tree/mod.rs",
+                "src/models.rs",
+                "src/models/mod.rs",
+            ]
+        );
+        assert_eq!(
+            candidates(
+                ImportLanguage::Rust,
+                "src/client/retry.rs",
+                "super::breaker"
+            ),
+            vec!["src/client/breaker.rs", "src/client/breaker/mod.rs"]
+        );
+    }
+
+    #[test]
+    fn maps_python_modules_within_the_package() {
+        assert_eq!(
+            candidates(ImportLanguage::Python, "lib/mypkg/sub/x.py", "..core.base"),
+            vec!["lib/mypkg/core/base.py", "lib/mypkg/core/base/__init__.py"]
+        );
+        assert_eq!(
+            candidates(ImportLanguage::Python, "lib/mypkg/sub/x.py", "mypkg.util"),
+            vec!["lib/mypkg/util.py", "lib/mypkg/util/__init__.py"]
+        );
+        assert!(candidates(ImportLanguage::Python, "lib/mypkg/x.py", "os").is_empty());
+    }
+
+    #[test]
+    fn maps_js_specifiers_to_files_and_indexes() {
+        let paths = candidates(ImportLanguage::JavaScript, "web/src/app.tsx", "../lib/api");
+        assert_eq!(paths.first().map(String::as_str), Some("web/lib/api.ts"));
+        assert!(paths.contains(&"web/lib/api/index.js".to_string()));
+
+        assert_eq!(
+            candidates(ImportLanguage::JavaScript, "web/app.js", "./util.js"),
+            vec!["web/util.js"]
+        );
+        assert!(candidates(ImportLanguage::JavaScript, "app.js", "../outside").is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+/// Language and MIME hints inferred from a file name, since the contents API reports neither.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentHints {
+    pub language: Option<&'static str>,
+    pub mime: Option<&'static str>,
+}
+
+/// Files recognized by their full name rather than their extension.
+const BY_NAME: &[(&str, &str, &str)] = &[
+    ("Dockerfile", "Dockerfile", "text/x-dockerfile"),
+    ("Makefile", "Makefile", "text/x-makefile"),
+    ("CMakeLists.txt", "CMake", "text/x-cmake"),
+    ("Gemfile", "Ruby", "text/x-ruby"),
+    ("Rakefile", "Ruby", "text/x-ruby"),
+];
+
+const BY_EXTENSION: &[(&str, &str, &str)] = &[
+    ("rs", "Rust", "text/x-rust"),
+    ("py", "Python", "text/x-python"),
+    ("pyi", "Python", "text/x-python"),
+    ("js", "JavaScript", "text/javascript"),
+    ("mjs", "JavaScript", "text/javascript"),
+    ("cjs", "JavaScript", "text/javascript"),
+    ("jsx", "JavaScript", "text/javascript"),
+    ("ts", "TypeScript", "text/x-typescript"),
+    ("tsx", "TypeScript", "text/x-typescript"),
+    ("go", "Go", "text/x-go"),
+    ("java", "Java", "text/x-java"),
+    ("kt", "Kotlin", "text/x-kotlin"),
+    ("swift", "Swift", "text/x-swift"),
+    ("c", "C", "text/x-c"),
+    ("h", "C", "text/x-c"),
+    ("cc", "C++", "text/x-c++"),
+    ("cpp", "C++", "text/x-c++"),
+    ("hpp", "C++", "text/x-c++"),
+    ("cs", "C#", "text/x-csharp"),
+    ("rb", "Ruby", "text/x-ruby"),
+    ("php", "PHP", "text/x-php"),
+    ("sh", "Shell", "text/x-shellscript"),
+    ("bash", "Shell", "text/x-shellscript"),
+    ("nix", "Nix", "text/x-nix"),
+    ("md", "Markdown", "text/markdown"),
+    ("rst", "reStructuredText", "text/x-rst"),
+    ("txt", "Text", "text/plain"),
+    ("json", "JSON", "application/json"),
+    ("toml", "TOML", "application/toml"),
+    ("yaml", "YAML", "application/yaml"),
+    ("yml", "YAML", "application/yaml"),
+    ("xml", "XML", "application/xml"),
+    ("html", "HTML", "text/html"),
+    ("css", "CSS", "text/css"),
+    ("sql", "SQL", "application/sql"),
+];
+
+pub fn content_hints(path: &str) -> ContentHints {
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    let found = BY_NAME
+        .iter()
+        .find(|(file_name, _, _)| *file_name == name)
+        .or_else(|| {
+            let (stem, extension) = name.rsplit_once('.')?;
+            if stem.is_empty() {
+                return None;
+            }
+            BY_EXTENSION
+                .iter()
+                .find(|(ext, _, _)| ext.eq_ignore_ascii_case(extension))
+        });
+
+    ContentHints {
+        language: found.map(|(_, language, _)| *language),
+        mime: found.map(|(_, _, mime)| *mime),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::content_hints;
+
+    #[test]
+    fn infers_common_languages_from_extension() {
+        let rust = content_hints("src/main.rs");
+        assert_eq!(rust.language, Some("Rust"));
+        assert_eq!(rust.mime, Some("text/x-rust"));
+
+        let python = content_hints("pkg/module.PY");
+        assert_eq!(python.language, Some("Python"));
+        assert_eq!(python.mime, Some("text/x-python"));
+
+        let markdown = content_hints("README.md");
+        assert_eq!(markdown.language, Some("Markdown"));
+        assert_eq!(markdown.mime, Some("text/markdown"));
+    }
+
+    #[test]
+    fn recognizes_well_known_file_names() {
+        assert_eq!(
+            content_hints("docker/Dockerfile").language,
+            Some("Dockerfile")
+        );
+    }
+
+    #[test]
+    fn leaves_unknown_files_unlabeled() {
+        for path in ["data.bin", "LICENSE", ".gitignore"] {
+            let hints = content_hints(path);
+            assert_eq!(hints.language, None, "{path}");
+            assert_eq!(hints.mime, None, "{path}");
+        }
+    }
+}
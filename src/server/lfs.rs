@@ -0,0 +1,60 @@
+use crate::models::LfsPointer;
+
+/// First line of every Git LFS pointer file.
+const POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+/// Pointer files are tiny; anything larger is real content that happens to look alike.
+const MAX_POINTER_BYTES: usize = 1024;
+
+/// Reads `text` as a Git LFS pointer file, returning the object it stands in for.
+pub fn parse_pointer(text: &str) -> Option<LfsPointer> {
+    if text.len() > MAX_POINTER_BYTES {
+        return None;
+    }
+
+    let mut lines = text.lines();
+    if lines.next()?.trim_end() != POINTER_VERSION {
+        return None;
+    }
+
+    let (mut oid, mut size) = (None, None);
+    for line in lines {
+        match line.trim_end().split_once(' ') {
+            Some(("oid", value)) if value.starts_with("sha256:") => oid = Some(value.to_string()),
+            Some(("size", value)) => size = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    Some(LfsPointer {
+        oid: oid?,
+        size: size?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_pointer;
+
+    #[test]
+    fn reads_oid_and_size() {
+        let pointer = parse_pointer(
+            "version https://git-lfs.github.com/spec/v1\n\
+             oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+             size 12345\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            pointer.oid,
+            "sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"
+        );
+        assert_eq!(pointer.size, 12345);
+    }
+
+    #[test]
+    fn ignores_lookalikes() {
+        assert!(parse_pointer("version https://git-lfs.github.com/spec/v1\nsize 3\n").is_none());
+        assert!(parse_pointer("# Notes\nversion https://git-lfs.github.com/spec/v1\n").is_none());
+        assert!(parse_pointer("").is_none());
+    }
+}
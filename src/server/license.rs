@@ -0,0 +1,126 @@
+use crate::models::Compatibility;
+
+/// License families the advisory matrix knows, keyed off SPDX ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    /// MIT, BSD, ISC and similar notice-only licenses.
+    Permissive,
+    Apache2,
+    Mpl2,
+    Lgpl21,
+    Lgpl3,
+    /// GPL 2.0 without the "or later" grant.
+    Gpl2,
+    Gpl2OrLater,
+    Gpl3,
+    Agpl3,
+}
+
+const PERMISSIVE: &[&str] = &[
+    "MIT",
+    "MIT-0",
+    "BSD-2-CLAUSE",
+    "BSD-3-CLAUSE",
+    "ISC",
+    "0BSD",
+    "UNLICENSE",
+    "ZLIB",
+    "BSL-1.0",
+    "CC0-1.0",
+];
+
+fn family(spdx_id: &str) -> Option<Family> {
+    let id = spdx_id.trim().to_ascii_uppercase();
+    if PERMISSIVE.contains(&id.as_str()) {
+        return Some(Family::Permissive);
+    }
+
+    let (base, or_later) = match id.strip_suffix("-OR-LATER") {
+        Some(base) => (base, true),
+        None => (id.strip_suffix("-ONLY").unwrap_or(&id), false),
+    };
+    Some(match (base, or_later) {
+        ("APACHE-2.0", _) => Family::Apache2,
+        ("MPL-2.0", _) => Family::Mpl2,
+        ("LGPL-2.1", _) => Family::Lgpl21,
+        ("LGPL-3.0", _) => Family::Lgpl3,
+        ("GPL-2.0", false) => Family::Gpl2,
+        ("GPL-2.0", true) => Family::Gpl2OrLater,
+        ("GPL-3.0", _) => Family::Gpl3,
+        ("AGPL-3.0", _) => Family::Agpl3,
+        _ => return None,
+    })
+}
+
+/// Whether code under `license` can be included in a project distributed under `against`.
+///
+/// Advisory only: the matrix covers common licenses, says `unknown` rather than guess, and
+/// ignores exceptions, dual licensing, and how the code is linked.
+pub fn check(license: &str, against: &str) -> Compatibility {
+    use Family::*;
+
+    if license.eq_ignore_ascii_case(against) {
+        return Compatibility::Compatible;
+    }
+    let (Some(inbound), Some(outbound)) = (family(license), family(against)) else {
+        return Compatibility::Unknown;
+    };
+
+    match (inbound, outbound) {
+        (Permissive, _)
+        | (Apache2, Mpl2 | Lgpl3 | Gpl2OrLater | Gpl3 | Agpl3)
+        | (Mpl2, Lgpl21 | Lgpl3 | Gpl2 | Gpl2OrLater | Gpl3 | Agpl3)
+        | (Lgpl21, Gpl2 | Gpl2OrLater | Gpl3 | Agpl3)
+        | (Lgpl3, Gpl3 | Agpl3)
+        | (Gpl2OrLater, Gpl2 | Gpl3 | Agpl3)
+        | (Gpl3, Agpl3)
+        | (Agpl3, Gpl3) => Compatibility::Compatible,
+        (inbound, outbound) if inbound == outbound => Compatibility::Compatible,
+        (Apache2, Gpl2)
+        | (Lgpl3, Gpl2)
+        | (Gpl2, Gpl3 | Agpl3)
+        | (Gpl3 | Agpl3, Gpl2 | Gpl2OrLater)
+        | (Gpl2 | Gpl2OrLater | Gpl3 | Agpl3, Permissive | Apache2 | Mpl2 | Lgpl21 | Lgpl3) => {
+            Compatibility::Incompatible
+        }
+        _ => Compatibility::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check;
+    use crate::models::Compatibility;
+
+    #[test]
+    fn permissive_code_flows_into_anything_known() {
+        assert_eq!(check("MIT", "Apache-2.0"), Compatibility::Compatible);
+        assert_eq!(check("BSD-3-Clause", "GPL-3.0"), Compatibility::Compatible);
+        assert_eq!(check("mit", "MIT"), Compatibility::Compatible);
+    }
+
+    #[test]
+    fn flags_known_conflicts() {
+        assert_eq!(check("Apache-2.0", "GPL-2.0"), Compatibility::Incompatible);
+        assert_eq!(check("GPL-3.0", "MIT"), Compatibility::Incompatible);
+        assert_eq!(
+            check("GPL-2.0-only", "GPL-3.0"),
+            Compatibility::Incompatible
+        );
+        assert_eq!(
+            check("GPL-2.0-or-later", "GPL-3.0"),
+            Compatibility::Compatible
+        );
+        assert_eq!(
+            check("Apache-2.0", "GPL-3.0-only"),
+            Compatibility::Compatible
+        );
+    }
+
+    #[test]
+    fn stays_unknown_when_unsure() {
+        assert_eq!(check("Apache-2.0", "MIT"), Compatibility::Unknown);
+        assert_eq!(check("EPL-2.0", "MIT"), Compatibility::Unknown);
+        assert_eq!(check("NOASSERTION", "MIT"), Compatibility::Unknown);
+    }
+}
@@ -1,38 +1,203 @@
-use std::collections::HashSet;
+mod bundle;
+mod dependencies;
+mod diff;
+mod fields;
+mod gitignore;
+mod graph;
+mod grep;
+mod imports;
+mod language;
+mod lfs;
+mod license;
+mod profile;
+mod project;
+mod semver_tags;
 
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use futures::{StreamExt, stream};
+use globset::{Glob, GlobMatcher};
 use rmcp::{
-    ServerHandler,
+    ErrorData, RoleServer, ServerHandler,
     handler::server::{
         router::tool::ToolRouter,
+        tool::ToolCallContext,
         wrapper::{Json, Parameters},
     },
-    model::{ServerCapabilities, ServerInfo},
-    tool, tool_handler, tool_router,
+    model::{
+        CallToolRequestParam, CallToolResult, IntoContents, ListToolsResult, PaginatedRequestParam,
+        ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+    tool, tool_router,
 };
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::{sync::Semaphore, time::Instant};
+use tokio_util::task::TaskTracker;
 
 use crate::{
     cli::ToolSelection,
-    client::GithubClient,
-    error::ApiErrorBody,
+    client::{
+        self, CrawlLimits, DirSize, Freshness, GithubClient, PathContent, RepoLookup, RetryStats,
+    },
+    error::{ApiErrorBody, ErrorKind},
     models::{
-        BranchesResponse, FileResponse, GetFileArgs, LineRange, ListReposArgs, RepoArgs,
-        RepoResponse, ReposResponse, SearchArgs, SearchResponse, StatsArgs, StatsResponse,
-        TagsResponse, TreeArgs, TreeResponse,
+        ArchiveUrlArgs, ArchiveUrlResponse, BlobResponse, BranchesResponse, BundleArgs,
+        BundleResponse, ChunkInfo, CollaboratorsResponse, CommitActivityResponse, CommitDiffArgs,
+        CommitDiffResponse, CommitsResponse, CommunityFileArgs, CommunityFileResponse,
+        Compatibility, DependenciesArgs, DependenciesResponse, DeploymentsResponse, DepthEstimate,
+        DescribeToolsResponse, DiffFilesArgs, DiffFilesResponse, DiffSide, DirProfileArgs,
+        DirProfileResponse, DirSizeArgs, DirSizeResponse, EntryKind, EntryType, FileLocator,
+        FileResponse, FileWithImportsArgs, FileWithImportsResponse, GetBlobArgs, GetFileArgs,
+        GetRepoArgs, GetReposArgs, GetReposResponse, GistArgs, GistResponse, GrepRepoArgs,
+        GrepResponse, IssueCountsResponse, IssuesResponse, LicenseCheckArgs, LicenseCheckResponse,
+        LicenseResponse, LineRange, ListBranchesArgs, ListCollaboratorsArgs, ListCommitsArgs,
+        ListDeploymentsArgs, ListIssuesArgs, ListMilestonesArgs, ListPullFilesArgs, ListPullsArgs,
+        ListReposArgs, ListTagsArgs, MainFileArgs, MainFileResponse, MergeBaseFileArgs,
+        MergeBaseFileResponse, Meta, MilestonesResponse, Order, PathExistsArgs, PathExistsResponse,
+        ProjectTypeArgs, ProjectTypeResponse, PullFilesResponse, PullsResponse,
+        RefreshRepoCacheResponse, RepoArgs, RepoResponse, RepoResult, ReposResponse,
+        ResolveRefsArgs, ResolveRefsResponse, ResolvedRef, SearchArgs, SearchResponse,
+        SemverTagsArgs, SemverTagsResponse, StatsArgs, StatsResponse, TagsResponse,
+        ToolDescription, TreeArgs, TreeEntry, TreeEstimateArgs, TreeEstimateResponse,
+        TreeGraphArgs, TreeGraphResponse, TreeResponse, WebUrlArgs, WebUrlResponse,
     },
 };
 
+/// Query parameters `search` sets itself, which `extra_params` may not override.
+const RESERVED_SEARCH_PARAMS: [&str; 3] = ["q", "page", "per_page"];
+/// Most refs a single `resolve_refs` call may check.
+const MAX_RESOLVE_REFS: usize = 50;
+/// Refs resolved concurrently by `resolve_refs`.
+const RESOLVE_REFS_CONCURRENCY: usize = 8;
+/// Levels `tree_graph` draws when a call names no depth.
+const DEFAULT_GRAPH_DEPTH: usize = 2;
+/// Most nodes in a `tree_graph` diagram, the root included, to keep it readable.
+const MAX_GRAPH_NODES: usize = 100;
+/// Most repositories a single `get_repos` call may look up.
+const MAX_GET_REPOS: usize = 50;
+/// Repositories looked up concurrently by `get_repos`.
+const GET_REPOS_CONCURRENCY: usize = 8;
+/// Most tags `list_tags` dates when `detailed`, one commit request each.
+const MAX_DATED_TAGS: usize = 100;
+/// Commits fetched concurrently while dating tags.
+const TAG_DATE_CONCURRENCY: usize = 8;
+/// Directories searched, in order, for community health files; GitHub uses the same ones.
+const COMMUNITY_FILE_DIRS: [&str; 3] = ["", ".github", "docs"];
+/// Symlinks `get_file` and `get_stats` follow before giving up on a chain.
+const MAX_SYMLINK_HOPS: usize = 8;
+/// Most imported files `get_file_with_imports` fetches, whatever the caller asks for.
+const MAX_IMPORTED_FILES: usize = 25;
+/// Directories `get_file_with_imports` lists while checking which imports exist.
+const MAX_IMPORT_LISTINGS: usize = 20;
+/// Most files a single `grep_repo` call searches, whatever the caller asks for.
+const MAX_GREP_FILES: usize = 500;
+/// Most bytes of text a single `grep_repo` call searches; binary files are not counted.
+const MAX_GREP_BYTES: u64 = 4 * 1024 * 1024;
+/// Most matching lines `grep_repo` returns.
+const MAX_GREP_MATCHES: usize = 500;
+/// Most `api_base` overrides that keep a client between calls; others get a fresh one each time.
+const MAX_OVERRIDE_CLIENTS: usize = 16;
+
+/// Server behavior configured at startup, independent of the GitHub client.
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// Directory that `path` arguments are resolved under, e.g. a monorepo subproject.
+    pub path_prefix: Option<String>,
+    /// Deepest `tree` expansion any call may request.
+    pub max_tree_depth: usize,
+    /// Repository paths no tool may read; a match on a directory covers everything under it.
+    pub deny_paths: Vec<Glob>,
+    /// Add a `meta` provenance object to every successful tool result.
+    pub include_meta: bool,
+    /// Appended to `get_file` content that was cut short, with `{n}` replaced by the number
+    /// of chars left out.
+    pub truncation_marker: Option<String>,
+    /// Ref used by content tools whose call names none.
+    pub default_ref: Option<String>,
+    /// Let calls send their requests to another API base through an `api_base` argument.
+    pub allow_base_override: bool,
+    /// Most files `bundle`, `grep_repo`, and `get_file_with_imports` fetch at once, across
+    /// all of their calls together.
+    pub batch_concurrency: usize,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            path_prefix: None,
+            max_tree_depth: 20,
+            deny_paths: Vec::new(),
+            include_meta: false,
+            truncation_marker: None,
+            default_ref: None,
+            allow_base_override: false,
+            batch_concurrency: 8,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct GithubServer {
     pub client: GithubClient,
     pub tool_router: ToolRouter<Self>,
+    options: ServerOptions,
+    denied_paths: Vec<GlobMatcher>,
+    /// Permits for batch file fetches, shared by every call so together they stay within
+    /// `batch_concurrency`.
+    batch_permits: Arc<Semaphore>,
+    /// Clients for `api_base` overrides by base, so each host's rate-limit and failure
+    /// tracking carries over from one call to the next.
+    override_clients: Arc<Mutex<HashMap<String, GithubClient>>>,
+    /// Tool calls in flight; closed once shutdown starts so no new ones are accepted.
+    calls: TaskTracker,
 }
 
 #[tool_router]
 impl GithubServer {
     pub fn new(client: GithubClient, allowed_tools: HashSet<ToolSelection>) -> Self {
+        Self::with_options(client, allowed_tools, ServerOptions::default())
+    }
+
+    pub fn with_options(
+        client: GithubClient,
+        allowed_tools: HashSet<ToolSelection>,
+        options: ServerOptions,
+    ) -> Self {
+        let path_prefix = options
+            .path_prefix
+            .map(|prefix| prefix.trim_matches('/').to_string())
+            .filter(|prefix| !prefix.is_empty());
+
+        let denied_paths = options
+            .deny_paths
+            .iter()
+            .map(Glob::compile_matcher)
+            .collect();
+
         let mut server = Self {
             client,
             tool_router: Self::tool_router(),
+            options: ServerOptions {
+                path_prefix,
+                max_tree_depth: options.max_tree_depth.max(1),
+                deny_paths: options.deny_paths,
+                include_meta: options.include_meta,
+                truncation_marker: options.truncation_marker,
+                default_ref: options.default_ref,
+                allow_base_override: options.allow_base_override,
+                batch_concurrency: options.batch_concurrency.max(1),
+            },
+            denied_paths,
+            batch_permits: Arc::new(Semaphore::new(options.batch_concurrency.max(1))),
+            override_clients: Arc::default(),
+            calls: TaskTracker::new(),
         };
 
         for tool in ToolSelection::ALL {
@@ -44,23 +209,237 @@ impl GithubServer {
         server
     }
 
-    #[tool(name = "get_repo", description = "Fetch repository metadata.")]
+    /// This server with its requests sent to `api_base`, for a call that overrides it.
+    fn with_api_base(&self, api_base: &str) -> Result<Self, ApiErrorBody> {
+        if !self.options.allow_base_override {
+            return Err(ApiErrorBody::new(
+                "api_base overrides are disabled; start the server with --allow-base-override to allow them.",
+                "400",
+            ));
+        }
+        let key = api_base.trim().trim_end_matches('/').to_string();
+        let mut clients = self
+            .override_clients
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let client = match clients.get(&key) {
+            Some(client) => client.clone(),
+            None => {
+                let client = self.client.with_api_base(api_base)?;
+                if clients.len() < MAX_OVERRIDE_CLIENTS {
+                    clients.insert(key, client.clone());
+                }
+                client
+            }
+        };
+        Ok(Self {
+            client,
+            ..self.clone()
+        })
+    }
+
+    /// Stops accepting tool calls and waits for those already running to finish.
+    pub async fn drain(&self) {
+        self.calls.close();
+        self.calls.wait().await;
+    }
+
+    #[tool(
+        name = "get_repo",
+        description = "Fetch repository metadata. Pass fields (array of strings) to keep only those keys of repo."
+    )]
     async fn get_repo(
         &self,
-        Parameters(args): Parameters<RepoArgs>,
+        Parameters(mut args): Parameters<GetRepoArgs>,
     ) -> Result<Json<RepoResponse>, ApiErrorBody> {
-        let repo = self.client.get_repo(&args.owner, &args.repo).await?;
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let lookup = self
+            .client
+            .get_repo_with_etag(&args.owner, &args.repo, args.etag.as_deref().unwrap_or(""))
+            .await?;
+        let caller_etag = args.etag.is_some();
+
+        Ok(Json(match lookup {
+            RepoLookup::Found(fetched) => RepoResponse {
+                repo: Some(fetched.info),
+                redirected_to: fetched.redirected_to,
+                not_modified: false,
+                etag: fetched.etag.filter(|_| caller_etag),
+            },
+            RepoLookup::NotModified => RepoResponse {
+                repo: None,
+                redirected_to: None,
+                not_modified: true,
+                etag: args.etag,
+            },
+            RepoLookup::Missing => RepoResponse {
+                repo: None,
+                redirected_to: None,
+                not_modified: false,
+                etag: None,
+            },
+        }))
+    }
+
+    #[tool(
+        name = "get_repos",
+        description = "Fetch metadata for several repositories at once, each given as `owner/repo`; one failing does not fail the others."
+    )]
+    async fn get_repos(
+        &self,
+        Parameters(args): Parameters<GetReposArgs>,
+    ) -> Result<Json<GetReposResponse>, ApiErrorBody> {
+        if args.repos.len() > MAX_GET_REPOS {
+            return Err(ApiErrorBody::new(
+                format!(
+                    "At most {} repositories can be fetched per call.",
+                    MAX_GET_REPOS
+                ),
+                "400",
+            ));
+        }
+
+        let repos = stream::iter(args.repos)
+            .map(|requested| async move {
+                let fetched = match parse_full_name(&requested) {
+                    Ok((owner, repo)) => self.client.get_repo(&owner, &repo).await,
+                    Err(err) => Err(err),
+                };
+                match fetched {
+                    Ok(Some(fetched)) => RepoResult {
+                        repo: requested,
+                        info: Some(fetched.info),
+                        redirected_to: fetched.redirected_to,
+                        error: None,
+                    },
+                    Ok(None) => RepoResult {
+                        error: Some(ApiErrorBody::new(
+                            format!("Repository `{}` was not found.", requested),
+                            "404",
+                        )),
+                        repo: requested,
+                        info: None,
+                        redirected_to: None,
+                    },
+                    Err(err) => RepoResult {
+                        repo: requested,
+                        info: None,
+                        redirected_to: None,
+                        error: Some(err),
+                    },
+                }
+            })
+            .buffered(GET_REPOS_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(Json(GetReposResponse { repos }))
+    }
+
+    #[tool(
+        name = "refresh_repo_cache",
+        description = "Drop cached responses for a repository and fetch its metadata fresh, e.g. after its default branch changed."
+    )]
+    async fn refresh_repo_cache(
+        &self,
+        Parameters(mut args): Parameters<RepoArgs>,
+    ) -> Result<Json<RefreshRepoCacheResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let cleared = self.client.forget_repo(&args.owner, &args.repo)?;
+        let fetched = self.client.get_repo(&args.owner, &args.repo).await?;
 
-        Ok(Json(RepoResponse { repo }))
+        Ok(Json(match fetched {
+            Some(fetched) => RefreshRepoCacheResponse {
+                repo: Some(fetched.info),
+                redirected_to: fetched.redirected_to,
+                default_branch: Some(fetched.default_branch),
+                cleared,
+            },
+            None => RefreshRepoCacheResponse {
+                repo: None,
+                redirected_to: None,
+                default_branch: None,
+                cleared,
+            },
+        }))
     }
 
     #[tool(name = "list_tags", description = "List all tags for a repository.")]
     async fn list_tags(
         &self,
-        Parameters(args): Parameters<RepoArgs>,
+        Parameters(mut args): Parameters<ListTagsArgs>,
     ) -> Result<Json<TagsResponse>, ApiErrorBody> {
-        let tags = self.client.list_tags(&args.owner, &args.repo).await?;
-        Ok(Json(TagsResponse { tags }))
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        if !args.detailed {
+            let tags = self
+                .client
+                .list_tags(&args.owner, &args.repo, args.page, args.per_page)
+                .await?;
+            return Ok(Json(TagsResponse {
+                fetched: tags.truncated.then_some(tags.items.len()),
+                truncated: tags.truncated,
+                next_page: tags.links.next,
+                last_page: tags.links.last,
+                tags: tags.items,
+                details: None,
+                details_truncated: false,
+            }));
+        }
+
+        let (owner, repo) = (&args.owner, &args.repo);
+        let tags = self
+            .client
+            .list_tag_details(owner, repo, args.page, args.per_page)
+            .await?;
+        let names = tags.items.iter().map(|tag| tag.name.clone()).collect();
+        let fetched = tags.truncated.then_some(tags.items.len());
+        let details_truncated = tags.items.len() > MAX_DATED_TAGS;
+        let mut details = stream::iter(tags.items.into_iter().take(MAX_DATED_TAGS))
+            .map(|mut tag| async move {
+                tag.date = self
+                    .client
+                    .commit_date(owner, repo, &tag.commit_sha)
+                    .await?;
+                Ok::<_, ApiErrorBody>(tag)
+            })
+            .buffered(TAG_DATE_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        // RFC 3339 UTC timestamps sort chronologically as strings; undated tags go last.
+        details.sort_by(|a, b| b.date.cmp(&a.date));
+
+        Ok(Json(TagsResponse {
+            tags: names,
+            details: Some(details),
+            details_truncated,
+            fetched,
+            truncated: tags.truncated,
+            next_page: tags.links.next,
+            last_page: tags.links.last,
+        }))
+    }
+
+    #[tool(
+        name = "list_tags_semver",
+        description = "List a repository's semver tags (a leading `v` allowed) matching a version range, lowest first."
+    )]
+    async fn list_tags_semver(
+        &self,
+        Parameters(mut args): Parameters<SemverTagsArgs>,
+    ) -> Result<Json<SemverTagsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let range = semver_tags::parse_range(&args.range)?;
+        let tags = self
+            .client
+            .list_tags(&args.owner, &args.repo, None, None)
+            .await?;
+
+        Ok(Json(SemverTagsResponse {
+            tags: semver_tags::matching(tags.items, &range),
+            truncated: tags.truncated,
+        }))
     }
 
     #[tool(
@@ -69,10 +448,41 @@ impl GithubServer {
     )]
     async fn list_branches(
         &self,
-        Parameters(args): Parameters<RepoArgs>,
+        Parameters(mut args): Parameters<ListBranchesArgs>,
     ) -> Result<Json<BranchesResponse>, ApiErrorBody> {
-        let branches = self.client.list_branches(&args.owner, &args.repo).await?;
-        Ok(Json(BranchesResponse { branches }))
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        if !args.detailed {
+            let branches = self
+                .client
+                .list_branches(&args.owner, &args.repo, args.page, args.per_page)
+                .await?;
+            return Ok(Json(BranchesResponse {
+                fetched: branches.truncated.then_some(branches.items.len()),
+                truncated: branches.truncated,
+                next_page: branches.links.next,
+                last_page: branches.links.last,
+                branches: branches.items,
+                details: None,
+            }));
+        }
+
+        let details = self
+            .client
+            .list_branch_details(&args.owner, &args.repo, args.page, args.per_page)
+            .await?;
+
+        Ok(Json(BranchesResponse {
+            branches: details
+                .items
+                .iter()
+                .map(|branch| branch.name.clone())
+                .collect(),
+            fetched: details.truncated.then_some(details.items.len()),
+            truncated: details.truncated,
+            next_page: details.links.next,
+            last_page: details.links.last,
+            details: Some(details.items),
+        }))
     }
 
     #[tool(
@@ -81,35 +491,254 @@ impl GithubServer {
     )]
     async fn list_repos(
         &self,
-        Parameters(args): Parameters<ListReposArgs>,
+        Parameters(mut args): Parameters<ListReposArgs>,
     ) -> Result<Json<ReposResponse>, ApiErrorBody> {
-        let repos = self
-            .client
-            .list_repos(&args.owner, args.page, args.per_page)
-            .await?;
+        validate_name("owner", &mut args.owner)?;
+        let (mut repos, truncated) = if args.all_pages {
+            let listing = self
+                .client
+                .list_all_repos(&args.owner, args.owner_type)
+                .await?;
+            (listing.items, listing.truncated)
+        } else {
+            let repos = self
+                .client
+                .list_repos(&args.owner, args.owner_type, args.page, args.per_page)
+                .await?;
+            (repos, false)
+        };
+        let fetched = truncated.then_some(repos.len());
+        repos.retain(|repo| {
+            !(args.exclude_forks && repo.fork || args.exclude_archived && repo.archived)
+        });
 
-        Ok(Json(ReposResponse { repos }))
+        Ok(Json(ReposResponse {
+            repos,
+            truncated,
+            fetched,
+        }))
     }
 
-    #[tool(name = "tree", description = "List files and folders under a path.")]
+    #[tool(
+        name = "tree",
+        description = "List files and folders under a path. Pass fields (array of strings) to keep only those keys on every entry; children always stays."
+    )]
     async fn tree(
         &self,
-        Parameters(args): Parameters<TreeArgs>,
+        Parameters(mut args): Parameters<TreeArgs>,
     ) -> Result<Json<TreeResponse>, ApiErrorBody> {
-        let depth = args.depth.max(1);
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let depth = args.depth.clamp(1, self.options.max_tree_depth);
         let r#ref = args.r#ref.as_deref();
-        let entries = self
+        let path = self.upstream_path(args.path.as_deref().unwrap_or_default())?;
+        self.ensure_path_allowed(&path)?;
+        // A deadline too far out to represent is no deadline at all.
+        let deadline = args
+            .deadline_secs
+            .and_then(|secs| Instant::now().checked_add(Duration::from_secs(secs as u64)));
+        let limits = CrawlLimits {
+            deadline,
+            max_entries: None,
+        };
+        let result = self
             .client
-            .tree(
+            .tree_within(&args.owner, &args.repo, &path, depth, r#ref, limits)
+            .await;
+
+        match result {
+            Ok((mut entries, mut truncated)) => {
+                let mut timed_out = truncated;
+                self.prune_denied(&mut entries);
+                if args.respect_gitignore {
+                    let prune = self.prune_gitignored(&args.owner, &args.repo, r#ref, &mut entries);
+                    match deadline {
+                        Some(deadline) => match tokio::time::timeout_at(deadline, prune).await {
+                            Ok(result) => result?,
+                            // Unpruned entries would pass for the filtered tree, so none are returned.
+                            Err(_) => {
+                                entries.clear();
+                                truncated = true;
+                                timed_out = true;
+                            }
+                        },
+                        None => prune.await?,
+                    }
+                }
+                if let Some(kind) = args.only {
+                    let mut flat = Vec::new();
+                    flatten_kind(entries, kind, &path, &mut flat);
+                    entries = flat;
+                } else if args.collapse_chains {
+                    collapse_chains(&mut entries);
+                }
+                if args.include_paths {
+                    fill_rel_paths(&mut entries, &path);
+                }
+                Ok(Json(TreeResponse {
+                    entries,
+                    depth,
+                    empty: false,
+                    truncated,
+                    timed_out,
+                }))
+            }
+            Err(err) if err.kind == Some(ErrorKind::EmptyRepository) => Ok(Json(TreeResponse {
+                entries: Vec::new(),
+                depth,
+                empty: true,
+                truncated: false,
+                timed_out: false,
+            })),
+            Err(err) => Err(err),
+        }
+    }
+
+    #[tool(
+        name = "tree_estimate",
+        description = "Estimate how many API calls a tree crawl of each depth would take, from a single listing of the top level."
+    )]
+    async fn tree_estimate(
+        &self,
+        Parameters(mut args): Parameters<TreeEstimateArgs>,
+    ) -> Result<Json<TreeEstimateResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let depth = args
+            .depth
+            .unwrap_or(self.options.max_tree_depth)
+            .clamp(1, self.options.max_tree_depth);
+        let path = self.upstream_path(args.path.as_deref().unwrap_or_default())?;
+        self.ensure_path_allowed(&path)?;
+        let counts = self
+            .client
+            .count_level(&args.owner, &args.repo, &path, args.r#ref.as_deref())
+            .await?;
+
+        Ok(Json(TreeEstimateResponse {
+            files: counts.files,
+            directories: counts.directories,
+            estimates: estimate_tree_calls(counts.directories, depth),
+        }))
+    }
+
+    #[tool(
+        name = "dir_size",
+        description = "Total the file sizes, files, and directories under a path."
+    )]
+    async fn dir_size(
+        &self,
+        Parameters(mut args): Parameters<DirSizeArgs>,
+    ) -> Result<Json<DirSizeResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let depth = args
+            .max_depth
+            .unwrap_or(self.options.max_tree_depth)
+            .clamp(1, self.options.max_tree_depth);
+        let path = self.upstream_path(args.path.as_deref().unwrap_or_default())?;
+        self.ensure_path_allowed(&path)?;
+        let result = self
+            .client
+            .dir_size(
                 &args.owner,
                 &args.repo,
-                args.path.as_deref().unwrap_or_default(),
+                &path,
                 depth,
-                r#ref,
+                args.r#ref.as_deref(),
+                |entry| self.is_path_denied(entry),
+            )
+            .await;
+
+        let totals = match result {
+            Ok(totals) => totals,
+            Err(err) if err.kind == Some(ErrorKind::EmptyRepository) => DirSize::default(),
+            Err(err) => return Err(err),
+        };
+        Ok(Json(DirSizeResponse {
+            total_bytes: totals.bytes,
+            file_count: totals.files,
+            dir_count: totals.directories,
+            depth,
+        }))
+    }
+
+    #[tool(
+        name = "dir_profile",
+        description = "Profile a directory before reading it: file counts and bytes per extension, plus total files and folders."
+    )]
+    async fn dir_profile(
+        &self,
+        Parameters(mut args): Parameters<DirProfileArgs>,
+    ) -> Result<Json<DirProfileResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let depth = args
+            .depth
+            .unwrap_or(self.options.max_tree_depth)
+            .clamp(1, self.options.max_tree_depth);
+        let path = self.upstream_path(args.path.as_deref().unwrap_or_default())?;
+        let (entries, _) = self
+            .crawl_allowed(
+                &args.owner,
+                &args.repo,
+                &path,
+                depth,
+                args.r#ref.as_deref(),
+                None,
+            )
+            .await?;
+
+        let profile = profile::profile(&entries);
+        Ok(Json(DirProfileResponse {
+            by_extension: profile.by_extension,
+            total_files: profile.files,
+            total_dirs: profile.dirs,
+            depth,
+        }))
+    }
+
+    #[tool(
+        name = "tree_graph",
+        description = "Render a directory hierarchy as a Mermaid or Graphviz DOT diagram for documentation."
+    )]
+    async fn tree_graph(
+        &self,
+        Parameters(mut args): Parameters<TreeGraphArgs>,
+    ) -> Result<Json<TreeGraphResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let depth = args
+            .depth
+            .unwrap_or(DEFAULT_GRAPH_DEPTH)
+            .clamp(1, self.options.max_tree_depth);
+        let path = self.upstream_path(args.path.as_deref().unwrap_or_default())?;
+        // Entries past the node cap would not be drawn, so they are not fetched either.
+        let (entries, capped) = self
+            .crawl_allowed(
+                &args.owner,
+                &args.repo,
+                &path,
+                depth,
+                args.r#ref.as_deref(),
+                Some(MAX_GRAPH_NODES),
             )
             .await?;
 
-        Ok(Json(TreeResponse { entries }))
+        let root = args
+            .path
+            .as_deref()
+            .and_then(|path| path.trim_matches('/').rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&args.repo);
+        let graph = graph::render(root, &entries, args.format, MAX_GRAPH_NODES);
+        Ok(Json(TreeGraphResponse {
+            graph: graph.text,
+            format: args.format,
+            nodes: graph.nodes,
+            truncated: graph.truncated || capped,
+            depth,
+        }))
     }
 
     #[tool(
@@ -118,18 +747,156 @@ impl GithubServer {
     )]
     async fn get_file(
         &self,
-        Parameters(args): Parameters<GetFileArgs>,
+        Parameters(mut args): Parameters<GetFileArgs>,
     ) -> Result<Json<FileResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
         let r#ref = args.r#ref.as_deref();
+        let path = self.upstream_path(&args.path)?;
+        if args.line_range.is_some() && args.line_ranges.is_some() {
+            return Err(ApiErrorBody::new(
+                "Set either line_range or line_ranges, not both.",
+                "400",
+            ));
+        }
+        if args.chunk_index.is_some() && args.chunk_size.is_none() {
+            return Err(ApiErrorBody::new(
+                "chunk_index needs chunk_size to say how large each chunk is.",
+                "400",
+            ));
+        }
 
-        let content = self
-            .client
-            .get_file(&args.owner, &args.repo, &args.path, r#ref)
-            .await?;
+        ensure_single_condition(&args.if_modified_since, &args.etag)?;
 
-        let content = apply_content_limits(&content, args.line_range, args.max_chars);
+        let resolve = args.resolve_symlink;
+        let mut fetched = None;
+        if let Some(since) = &args.if_modified_since {
+            self.ensure_path_allowed(&path)?;
+            match self
+                .modified_since(&args.owner, &args.repo, &path, r#ref, since)
+                .await?
+            {
+                Freshness::Unchanged => {
+                    return Ok(Json(FileResponse {
+                        not_modified: true,
+                        ..FileResponse::default()
+                    }));
+                }
+                Freshness::Changed { content, .. } => fetched = content,
+            }
+        }
+        let mut etag = None;
+        if let Some(sent) = &args.etag {
+            self.ensure_path_allowed(&path)?;
+            match self
+                .etag_freshness(&args.owner, &args.repo, &path, r#ref, sent)
+                .await?
+            {
+                Freshness::Unchanged => {
+                    return Ok(Json(FileResponse {
+                        not_modified: true,
+                        etag: Some(sent.clone()),
+                        ..FileResponse::default()
+                    }));
+                }
+                Freshness::Changed {
+                    etag: current,
+                    content,
+                    ..
+                } => {
+                    etag = current;
+                    fetched = content;
+                }
+            }
+        }
+
+        let ((content, resolved_path), used_ref) = match self
+            .read_file_from(&args.owner, &args.repo, &path, r#ref, resolve, fetched)
+            .await
+        {
+            Err(err) if args.ref_fallback && r#ref.is_some() && err.is_not_found() => {
+                let default_branch = self.client.default_branch(&args.owner, &args.repo).await?;
+                if r#ref == Some(default_branch.as_str()) {
+                    return Err(err);
+                }
+
+                let file = self
+                    .read_file(
+                        &args.owner,
+                        &args.repo,
+                        &path,
+                        Some(&default_branch),
+                        resolve,
+                    )
+                    .await?;
+                (file, Some(default_branch))
+            }
+            result => (result?, None),
+        };
+        let resolved_path = resolved_path.map(|resolved| self.relative_path(&resolved));
+        let content_sha256 = args.with_hash.then(|| sha256_hex(&content));
+        let (content, had_invalid_utf8) = decode_text(content, args.lossy)?;
+        let lfs = lfs::parse_pointer(&content);
+
+        // Normalize first so line numbers and char counts refer to the cleaned text.
+        let content = normalize_content(content, args.normalize_eol, args.trim_trailing_ws);
+        let original_length = content.chars().count();
+        let (content, line_ranges) = match &args.line_ranges {
+            Some(ranges) => {
+                let limited = apply_content_limits(&content, None, args.max_chars);
+                let (content, merged) = extract_line_ranges(&limited, ranges);
+                (content, Some(merged))
+            }
+            None => (
+                apply_content_limits(&content, args.line_range, args.max_chars),
+                None,
+            ),
+        };
+        // The dropped newline is a formatting choice, not part of the file left out.
+        let sliced = args.line_range.is_some() || args.line_ranges.is_some();
+        let (content, trimmed_newline) = match content.strip_suffix('\n') {
+            Some(trimmed) if args.trim_final_newline && sliced => (trimmed.to_string(), 1),
+            _ => (content, 0),
+        };
+        let (content, chunk) = match args.chunk_size {
+            Some(chunk_size) => {
+                let (content, chunk) =
+                    take_chunk(&content, args.chunk_index.unwrap_or(0), chunk_size)?;
+                (content, Some(chunk))
+            }
+            None => (content, None),
+        };
+        let returned_length = content.chars().count();
+        let omitted = original_length.saturating_sub(returned_length + trimmed_newline);
+        // A chunk's `has_more` already says what follows; a marker would end up spliced
+        // into the file when the chunks are joined.
+        let content = match &self.options.truncation_marker {
+            Some(marker) if omitted > 0 && chunk.is_none() => {
+                content + &marker.replace("{n}", &omitted.to_string())
+            }
+            _ => content,
+        };
+        let hints = language::content_hints(resolved_path.as_deref().unwrap_or(&args.path));
 
-        Ok(Json(FileResponse { content }))
+        Ok(Json(FileResponse {
+            content,
+            used_fallback: used_ref.is_some(),
+            used_ref,
+            resolved_path,
+            language: hints.language.map(str::to_string),
+            mime: hints.mime.map(str::to_string),
+            line_ranges,
+            chunk,
+            had_invalid_utf8,
+            content_sha256,
+            original_length,
+            returned_length,
+            truncated: omitted > 0,
+            not_modified: false,
+            etag,
+            is_lfs: lfs.is_some(),
+            lfs,
+        }))
     }
 
     #[tool(
@@ -140,12 +907,41 @@ impl GithubServer {
         &self,
         Parameters(args): Parameters<SearchArgs>,
     ) -> Result<Json<SearchResponse>, ApiErrorBody> {
-        let results = self
-            .client
-            .search_code(&args.query, args.page, args.per_page)
-            .await?;
+        let extra_params = args.extra_params.unwrap_or_default();
+        if let Some(key) = extra_params
+            .keys()
+            .find(|key| key.is_empty() || RESERVED_SEARCH_PARAMS.contains(&key.as_str()))
+        {
+            return Err(ApiErrorBody::new(
+                format!(
+                    "Invalid extra_params key `{}`: `q`, `page`, and `per_page` are set from query, page, and per_page.",
+                    key
+                ),
+                "400",
+            ));
+        }
 
-        Ok(Json(SearchResponse { results }))
+        let page = if args.all_pages {
+            self.client
+                .search_code_all(&args.query, &extra_params, args.per_page)
+                .await?
+        } else {
+            self.client
+                .search_code(&args.query, &extra_params, args.page, args.per_page)
+                .await?
+        };
+
+        let note = page.incomplete.then(|| {
+            "GitHub's search timed out and returned partial results; retry with a narrower query (e.g. add repo:, path:, or language: qualifiers).".to_string()
+        });
+
+        Ok(Json(SearchResponse {
+            collected: page.results.len(),
+            total_count: page.total_count,
+            incomplete: page.incomplete,
+            note,
+            results: page.results,
+        }))
     }
 
     #[tool(
@@ -154,110 +950,4839 @@ impl GithubServer {
     )]
     async fn get_stats(
         &self,
-        Parameters(args): Parameters<StatsArgs>,
+        Parameters(mut args): Parameters<StatsArgs>,
     ) -> Result<Json<StatsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
         let r#ref = args.r#ref.as_deref();
-        let item = self
-            .client
-            .get_stats(&args.owner, &args.repo, &args.path, r#ref)
-            .await?;
-
-        Ok(Json(StatsResponse { item }))
-    }
-}
-
-#[tool_handler]
-impl ServerHandler for GithubServer {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            instructions: Some(
-                "GitHub crawler: query repository metadata, tags, branches, trees, and file contents."
-                    .to_string(),
-            ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            ..ServerInfo::default()
+        let path = self.upstream_path(&args.path)?;
+        self.ensure_path_allowed(&path)?;
+        ensure_single_condition(&args.if_modified_since, &args.etag)?;
+        let mut fetched = None;
+        if let Some(since) = &args.if_modified_since {
+            match self
+                .modified_since(&args.owner, &args.repo, &path, r#ref, since)
+                .await?
+            {
+                Freshness::Unchanged => {
+                    return Ok(Json(StatsResponse {
+                        item: None,
+                        not_modified: true,
+                        etag: None,
+                    }));
+                }
+                Freshness::Changed { stats, .. } => fetched = stats,
+            }
+        }
+        let mut etag = None;
+        if let Some(sent) = &args.etag {
+            match self
+                .etag_freshness(&args.owner, &args.repo, &path, r#ref, sent)
+                .await?
+            {
+                Freshness::Unchanged => {
+                    return Ok(Json(StatsResponse {
+                        item: None,
+                        not_modified: true,
+                        etag: Some(sent.clone()),
+                    }));
+                }
+                Freshness::Changed {
+                    etag: current,
+                    stats,
+                    ..
+                } => {
+                    etag = current;
+                    fetched = stats;
+                }
+            }
         }
-    }
-}
-
-fn apply_content_limits(
-    content: &str,
-    line_range: Option<LineRange>,
-    max_chars: Option<usize>,
-) -> String {
-    let mut output: String = match max_chars {
-        Some(limit) => content.chars().take(limit).collect(),
-        None => content.to_string(),
-    };
 
-    if let Some(range) = line_range {
-        let (start, end) = range.bounds();
-        output = match end {
-            Some(end) => slice_lines(&output, start, end),
-            None => slice_lines(&output, start, usize::MAX),
+        let mut item = match fetched {
+            Some(item) => item,
+            None => {
+                self.client
+                    .get_stats(&args.owner, &args.repo, &path, r#ref)
+                    .await?
+            }
         };
-    }
-
-    output
-}
+        if args.resolve_target
+            && let (EntryType::Symlink, Some(target)) = (item.r#type, &item.target)
+        {
+            item.target_type = self
+                .symlink_target_type(&args.owner, &args.repo, &path, target, r#ref)
+                .await?;
+        }
+        item.path = self.relative_path(&item.path);
 
-fn slice_lines(content: &str, start: usize, end: usize) -> String {
-    if start == 0 || end == 0 || end < start {
-        return String::new();
+        Ok(Json(StatsResponse {
+            item: Some(item),
+            not_modified: false,
+            etag,
+        }))
     }
 
-    let mut limited = String::new();
+    #[tool(
+        name = "list_issues",
+        description = "List a repository's issues, excluding pull requests unless include_prs is set."
+    )]
+    async fn list_issues(
+        &self,
+        Parameters(mut args): Parameters<ListIssuesArgs>,
+    ) -> Result<Json<IssuesResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let mut issues = self
+            .client
+            .list_issues(
+                &args.owner,
+                &args.repo,
+                args.state,
+                args.labels.as_deref().unwrap_or_default(),
+                args.page,
+                args.per_page,
+            )
+            .await?;
 
-    for (idx, line) in content.split_inclusive('\n').enumerate() {
-        let line_no = idx + 1;
-        if line_no < start {
-            continue;
+        // The issues endpoint also returns pull requests; drop them unless asked for.
+        if !args.include_prs {
+            issues.retain(|issue| !issue.is_pull_request);
         }
-        if line_no > end {
-            break;
-        }
-        limited.push_str(line);
-    }
 
-    // When the content lacks a trailing newline, split_inclusive will still return
-    // the final segment without a newline, which we already appended above.
-    limited
-}
+        Ok(Json(IssuesResponse { issues }))
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::apply_content_limits;
-    use crate::models::LineRange;
+    #[tool(
+        name = "issue_counts",
+        description = "Count a repository's open and closed issues without listing them (two search requests)."
+    )]
+    async fn issue_counts(
+        &self,
+        Parameters(mut args): Parameters<RepoArgs>,
+    ) -> Result<Json<IssueCountsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let scope = format!("repo:{}/{} is:issue", args.owner, args.repo);
+        let open = self
+            .client
+            .search_issues_count(&format!("{scope} is:open"))
+            .await?;
+        let closed = self
+            .client
+            .search_issues_count(&format!("{scope} is:closed"))
+            .await?;
+        Ok(Json(IssueCountsResponse { open, closed }))
+    }
 
-    #[test]
-    fn enforces_character_limit_without_splitting_codepoints() {
-        let content = "héllo";
-        let limited = apply_content_limits(content, None, Some(3));
+    #[tool(
+        name = "list_pulls",
+        description = "List a repository's pull requests with their branches, author, and draft/merged status."
+    )]
+    async fn list_pulls(
+        &self,
+        Parameters(mut args): Parameters<ListPullsArgs>,
+    ) -> Result<Json<PullsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let pulls = self
+            .client
+            .list_pulls(
+                &args.owner,
+                &args.repo,
+                args.state,
+                args.page,
+                args.per_page,
+            )
+            .await?;
 
-        assert_eq!(limited, "hél");
+        Ok(Json(PullsResponse { pulls }))
     }
 
-    #[test]
-    fn trims_to_requested_number_of_lines() {
-        let content = "one\ntwo\nthree\nfour\n";
-        let limited = apply_content_limits(content, Some(LineRange::End(2)), None);
+    #[tool(
+        name = "list_deployments",
+        description = "List a repository's deployments, newest first, optionally for one environment or ref."
+    )]
+    async fn list_deployments(
+        &self,
+        Parameters(mut args): Parameters<ListDeploymentsArgs>,
+    ) -> Result<Json<DeploymentsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let deployments = self
+            .client
+            .list_deployments(
+                &args.owner,
+                &args.repo,
+                args.environment.as_deref(),
+                args.r#ref.as_deref(),
+                args.page,
+                args.per_page,
+            )
+            .await?;
 
-        assert_eq!(limited, "one\ntwo\n");
+        Ok(Json(DeploymentsResponse { deployments }))
     }
 
-    #[test]
-    fn applies_both_limits_when_set() {
-        let content = "1\n2\n3\n4";
-        let limited = apply_content_limits(content, Some(LineRange::End(2)), Some(5));
+    #[tool(
+        name = "list_collaborators",
+        description = "List a repository's collaborators with their permissions; needs a token with push access."
+    )]
+    async fn list_collaborators(
+        &self,
+        Parameters(mut args): Parameters<ListCollaboratorsArgs>,
+    ) -> Result<Json<CollaboratorsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let collaborators = self
+            .client
+            .list_collaborators(
+                &args.owner,
+                &args.repo,
+                args.affiliation,
+                args.page,
+                args.per_page,
+            )
+            .await?;
 
-        assert_eq!(limited, "1\n2\n");
+        Ok(Json(CollaboratorsResponse { collaborators }))
     }
 
-    #[test]
-    fn returns_empty_when_line_limit_is_zero() {
-        let content = "content";
-        let limited = apply_content_limits(content, Some(LineRange::End(0)), Some(10));
-
+    #[tool(
+        name = "list_milestones",
+        description = "List a repository's milestones (open by default) with issue counts and due dates."
+    )]
+    async fn list_milestones(
+        &self,
+        Parameters(mut args): Parameters<ListMilestonesArgs>,
+    ) -> Result<Json<MilestonesResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let milestones = self
+            .client
+            .list_milestones(
+                &args.owner,
+                &args.repo,
+                args.state,
+                args.page,
+                args.per_page,
+            )
+            .await?;
+
+        Ok(Json(MilestonesResponse {
+            issues_disabled: milestones.is_none(),
+            milestones: milestones.unwrap_or_default(),
+        }))
+    }
+
+    #[tool(
+        name = "list_pull_files",
+        description = "List the files a pull request changes with their status and line counts; include_patch adds each file's diff."
+    )]
+    async fn list_pull_files(
+        &self,
+        Parameters(mut args): Parameters<ListPullFilesArgs>,
+    ) -> Result<Json<PullFilesResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let mut files = self
+            .client
+            .list_pull_files(
+                &args.owner,
+                &args.repo,
+                args.number,
+                args.page,
+                args.per_page,
+            )
+            .await?;
+
+        if !args.include_patch {
+            for file in &mut files.items {
+                file.patch = None;
+            }
+        }
+
+        Ok(Json(PullFilesResponse {
+            files: files.items,
+            truncated: files.truncated,
+            next_page: files.links.next,
+            last_page: files.links.last,
+        }))
+    }
+
+    #[tool(
+        name = "list_commits",
+        description = "List a repository's commits, newest first, optionally limited to a since/until time window."
+    )]
+    async fn list_commits(
+        &self,
+        Parameters(mut args): Parameters<ListCommitsArgs>,
+    ) -> Result<Json<CommitsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let since = args.since.as_deref().map(str::trim);
+        let until = args.until.as_deref().map(str::trim);
+        for (name, value) in [("since", since), ("until", until)] {
+            if let Some(value) = value {
+                validate_timestamp(name, value)?;
+            }
+        }
+
+        let mut commits = self
+            .client
+            .list_commits(
+                &args.owner,
+                &args.repo,
+                args.r#ref.as_deref(),
+                since,
+                until,
+                args.page,
+                args.per_page,
+            )
+            .await?;
+        // The commits endpoint has no ascending sort, so oldest-first reverses the page.
+        if args.order.unwrap_or_default() == Order::Asc {
+            commits.reverse();
+        }
+
+        Ok(Json(CommitsResponse { commits }))
+    }
+
+    #[tool(
+        name = "commit_activity",
+        description = "Weekly commit counts for a repository's last year, oldest week first, for gauging project activity."
+    )]
+    async fn commit_activity(
+        &self,
+        Parameters(mut args): Parameters<RepoArgs>,
+    ) -> Result<Json<CommitActivityResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let weeks = self.client.commit_activity(&args.owner, &args.repo).await?;
+        Ok(Json(CommitActivityResponse { weeks }))
+    }
+
+    #[tool(
+        name = "get_license",
+        description = "Fetch the full text of a repository's detected license."
+    )]
+    async fn get_license(
+        &self,
+        Parameters(mut args): Parameters<RepoArgs>,
+    ) -> Result<Json<LicenseResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let license = self.client.get_license(&args.owner, &args.repo).await?;
+        Ok(Json(LicenseResponse { license }))
+    }
+
+    #[tool(
+        name = "get_gist",
+        description = "Fetch a gist's description and files."
+    )]
+    async fn get_gist(
+        &self,
+        Parameters(mut args): Parameters<GistArgs>,
+    ) -> Result<Json<GistResponse>, ApiErrorBody> {
+        args.id = args.id.trim().to_string();
+        if args.id.is_empty() || !args.id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(ApiErrorBody::new(
+                format!(
+                    "Invalid gist id `{}`: pass the id at the end of the gist's URL.",
+                    args.id
+                ),
+                "400",
+            ));
+        }
+
+        let gist = self.client.get_gist(&args.id).await?;
+        Ok(Json(GistResponse { gist }))
+    }
+
+    #[tool(
+        name = "license_check",
+        description = "Report a repository's SPDX license id and, given `against`, an advisory verdict on whether its code can be included in a project under that license."
+    )]
+    async fn license_check(
+        &self,
+        Parameters(mut args): Parameters<LicenseCheckArgs>,
+    ) -> Result<Json<LicenseCheckResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let fetched = self
+            .client
+            .get_repo(&args.owner, &args.repo)
+            .await?
+            .ok_or_else(|| {
+                ApiErrorBody::new(
+                    format!("Repository `{}/{}` not found.", args.owner, args.repo),
+                    "404",
+                )
+            })?;
+        let spdx_id = fetched.info.license.and_then(|license| license.spdx_id);
+
+        let verdict = args.against.as_deref().map(|against| {
+            spdx_id
+                .as_deref()
+                .map_or(Compatibility::Unknown, |spdx_id| {
+                    license::check(spdx_id, against)
+                })
+        });
+        Ok(Json(LicenseCheckResponse {
+            spdx_id,
+            against: args.against,
+            note: verdict.map(|_| {
+                "Advisory only, from a small built-in matrix; not legal advice.".to_string()
+            }),
+            verdict,
+        }))
+    }
+
+    #[tool(
+        name = "resolve_refs",
+        description = "Check which branches, tags, or shas exist and resolve each to its commit sha."
+    )]
+    async fn resolve_refs(
+        &self,
+        Parameters(mut args): Parameters<ResolveRefsArgs>,
+    ) -> Result<Json<ResolveRefsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        if args.refs.len() > MAX_RESOLVE_REFS {
+            return Err(ApiErrorBody::new(
+                format!(
+                    "At most {} refs can be resolved per call.",
+                    MAX_RESOLVE_REFS
+                ),
+                "400",
+            ));
+        }
+
+        let (owner, repo) = (&args.owner, &args.repo);
+        let refs = stream::iter(args.refs)
+            .map(|r#ref| async move {
+                let sha = self.client.resolve_ref(owner, repo, &r#ref).await?;
+                Ok::<_, ApiErrorBody>(ResolvedRef {
+                    r#ref,
+                    exists: sha.is_some(),
+                    sha,
+                })
+            })
+            .buffered(RESOLVE_REFS_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Json(ResolveRefsResponse { refs }))
+    }
+
+    #[tool(
+        name = "project_type",
+        description = "Classify a repository's project type from the manifest files at its root."
+    )]
+    async fn project_type(
+        &self,
+        Parameters(mut args): Parameters<ProjectTypeArgs>,
+    ) -> Result<Json<ProjectTypeResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let entries = match self
+            .client
+            .tree(&args.owner, &args.repo, "", 1, args.r#ref.as_deref())
+            .await
+        {
+            Ok(entries) => entries,
+            Err(err) if err.kind == Some(ErrorKind::EmptyRepository) => Vec::new(),
+            Err(err) => return Err(err),
+        };
+
+        let (detected, manifests) = project::classify(
+            entries
+                .iter()
+                .filter(|entry| matches!(entry.r#type, EntryType::File))
+                .map(|entry| entry.name.as_str()),
+        );
+
+        Ok(Json(ProjectTypeResponse {
+            detected,
+            manifests,
+        }))
+    }
+
+    #[tool(
+        name = "get_dependencies",
+        description = "List the dependencies declared by a repository's root Cargo.toml or package.json, with their version requirements and kind (normal, dev, build)."
+    )]
+    async fn get_dependencies(
+        &self,
+        Parameters(mut args): Parameters<DependenciesArgs>,
+    ) -> Result<Json<DependenciesResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let r#ref = args.r#ref.as_deref();
+        let root = self.upstream_path("")?;
+        let entries = match self
+            .client
+            .tree(&args.owner, &args.repo, &root, 1, r#ref)
+            .await
+        {
+            Ok(entries) => entries,
+            Err(err) if err.kind == Some(ErrorKind::EmptyRepository) => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        let names: Vec<&str> = entries
+            .iter()
+            .filter(|entry| matches!(entry.r#type, EntryType::File))
+            .map(|entry| entry.name.as_str())
+            .collect();
+
+        let Some(manifest) = dependencies::MANIFESTS
+            .iter()
+            .find(|manifest| names.contains(manifest))
+        else {
+            let (_, found) = project::classify(names);
+            let found = if found.is_empty() {
+                String::new()
+            } else {
+                format!("; found {}, which is not supported yet", found.join(", "))
+            };
+            return Err(ApiErrorBody::new(
+                format!(
+                    "No Cargo.toml or package.json at the root of {}/{}{}.",
+                    args.owner, args.repo, found
+                ),
+                "404",
+            ));
+        };
+
+        let path = self.upstream_path(manifest)?;
+        let (content, _) = self
+            .read_file(&args.owner, &args.repo, &path, r#ref, false)
+            .await?;
+        let (content, _) = decode_text(content, false)?;
+
+        Ok(Json(DependenciesResponse {
+            manifest: manifest.to_string(),
+            dependencies: dependencies::parse(manifest, &content)?,
+        }))
+    }
+
+    #[tool(
+        name = "get_main_file",
+        description = "Find a repository's conventional entry file (e.g. src/main.rs, index.js, main.py) and return its content."
+    )]
+    async fn get_main_file(
+        &self,
+        Parameters(mut args): Parameters<MainFileArgs>,
+    ) -> Result<Json<MainFileResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let r#ref = args.r#ref.as_deref();
+        let root = self.upstream_path("")?;
+        let mut entries = self
+            .client
+            .tree(&args.owner, &args.repo, &root, 1, r#ref)
+            .await?;
+        self.prune_denied(&mut entries);
+
+        let mut paths: Vec<String> = entries
+            .iter()
+            .filter(|entry| matches!(entry.r#type, EntryType::File))
+            .map(|entry| entry.name.clone())
+            .collect();
+
+        let has_source_dir = entries.iter().any(|entry| {
+            matches!(entry.r#type, EntryType::Dir) && entry.name == project::ENTRY_SOURCE_DIR
+        });
+        if has_source_dir {
+            let source_dir = self.upstream_path(project::ENTRY_SOURCE_DIR)?;
+            let mut source = self
+                .client
+                .tree(&args.owner, &args.repo, &source_dir, 1, r#ref)
+                .await?;
+            self.prune_denied(&mut source);
+            paths.extend(
+                source
+                    .iter()
+                    .filter(|entry| matches!(entry.r#type, EntryType::File))
+                    .map(|entry| format!("{}/{}", project::ENTRY_SOURCE_DIR, entry.name)),
+            );
+        }
+
+        let Some(path) = project::entry_file(paths.iter().map(String::as_str)) else {
+            return Err(ApiErrorBody::new(
+                "No conventional entry file found (looked for e.g. src/main.rs, src/lib.rs, index.js, src/index.ts, main.py, __init__.py).",
+                "404",
+            ));
+        };
+
+        let (content, _) = self
+            .read_file(
+                &args.owner,
+                &args.repo,
+                &self.upstream_path(path)?,
+                r#ref,
+                false,
+            )
+            .await?;
+        let content = client::utf8_text(content)?;
+
+        Ok(Json(MainFileResponse {
+            path: path.to_string(),
+            content,
+            language: language::content_hints(path).language.map(str::to_string),
+        }))
+    }
+
+    #[tool(
+        name = "get_file_with_imports",
+        description = "Fetch a Rust, Python, or JavaScript/TypeScript file plus the repository files it imports, one level deep. Imports are found heuristically."
+    )]
+    async fn get_file_with_imports(
+        &self,
+        Parameters(mut args): Parameters<FileWithImportsArgs>,
+    ) -> Result<Json<FileWithImportsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let (owner, repo) = (&args.owner, &args.repo);
+        let r#ref = args.r#ref.as_deref();
+        let path = self.upstream_path(&args.path)?;
+        let (content, _) = self.read_file(owner, repo, &path, r#ref, false).await?;
+        let content = client::utf8_text(content)?;
+        let hints = language::content_hints(&path);
+
+        let mut resolved = Vec::new();
+        let mut unresolved = Vec::new();
+        let mut truncated = false;
+        if let Some(language) = imports::ImportLanguage::for_path(&path) {
+            let max_imports = args.max_imports.min(MAX_IMPORTED_FILES);
+            let mut listings: HashMap<String, HashSet<String>> = HashMap::new();
+
+            for spec in imports::extract(language, &content) {
+                let candidates: Vec<String> = imports::candidates(language, &path, &spec)
+                    .into_iter()
+                    .filter(|candidate| {
+                        *candidate != path
+                            && self.within_path_prefix(candidate)
+                            && !self.is_path_denied(candidate)
+                    })
+                    .collect();
+                if candidates.is_empty() {
+                    continue;
+                }
+
+                let mut found = None;
+                for candidate in candidates {
+                    let dir = imports::parent_dir(&candidate);
+                    if !listings.contains_key(dir) {
+                        if listings.len() >= MAX_IMPORT_LISTINGS {
+                            break;
+                        }
+                        let files = self.list_dir_files(owner, repo, dir, r#ref).await?;
+                        listings.insert(dir.to_string(), files);
+                    }
+                    if listings[dir].contains(&candidate) {
+                        found = Some(candidate);
+                        break;
+                    }
+                }
+
+                match found {
+                    Some(found) if resolved.contains(&found) => {}
+                    Some(_) if resolved.len() >= max_imports => truncated = true,
+                    Some(found) => resolved.push(found),
+                    None => unresolved.push(spec),
+                }
+            }
+        }
+
+        let imports = stream::iter(resolved)
+            .map(|import| async move {
+                let content = self.batch_get_file(owner, repo, &import, r#ref).await?;
+                Ok::<_, ApiErrorBody>((self.relative_path(&import), content))
+            })
+            .buffered(self.batch_concurrency(args.concurrency))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        Ok(Json(FileWithImportsResponse {
+            path: args.path,
+            content,
+            language: hints.language.map(str::to_string),
+            imports,
+            unresolved,
+            truncated,
+        }))
+    }
+
+    #[tool(
+        name = "get_community_file",
+        description = "Fetch a community health file (security policy, contributing guide, or code of conduct) from the repository root, .github/, or docs/."
+    )]
+    async fn get_community_file(
+        &self,
+        Parameters(mut args): Parameters<CommunityFileArgs>,
+    ) -> Result<Json<CommunityFileResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let r#ref = args.r#ref.as_deref();
+        let file_name = args.kind.file_name();
+
+        for dir in COMMUNITY_FILE_DIRS {
+            let path = if dir.is_empty() {
+                file_name.to_string()
+            } else {
+                format!("{}/{}", dir, file_name)
+            };
+            let upstream = self.upstream_path(&path)?;
+            if self.is_path_denied(&upstream) {
+                continue;
+            }
+
+            match self
+                .client
+                .get_file(&args.owner, &args.repo, &upstream, r#ref)
+                .await
+            {
+                Ok(content) => return Ok(Json(CommunityFileResponse { path, content })),
+                Err(err) if err.is_not_found() => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(ApiErrorBody::new(
+            format!(
+                "No {} found in the repository root, .github/, or docs/.",
+                file_name
+            ),
+            "404",
+        ))
+    }
+
+    #[tool(
+        name = "get_archive_url",
+        description = "Resolve the download URL of a repository's zip archive at a ref, without downloading it."
+    )]
+    async fn get_archive_url(
+        &self,
+        Parameters(mut args): Parameters<ArchiveUrlArgs>,
+    ) -> Result<Json<ArchiveUrlResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let url = self
+            .client
+            .archive_url(&args.owner, &args.repo, args.r#ref.as_deref())
+            .await?;
+
+        Ok(Json(ArchiveUrlResponse { url }))
+    }
+
+    #[tool(
+        name = "web_url",
+        description = "Build the github.com (or Enterprise) web link for a file or directory in a repository."
+    )]
+    async fn web_url(
+        &self,
+        Parameters(mut args): Parameters<WebUrlArgs>,
+    ) -> Result<Json<WebUrlResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let r#ref = args.r#ref.as_deref();
+        let path = self.upstream_path(args.path.as_deref().unwrap_or(""))?;
+        self.ensure_path_allowed(&path)?;
+
+        let r#type = if path.trim_matches('/').is_empty() {
+            EntryType::Dir
+        } else {
+            self.client
+                .get_stats(&args.owner, &args.repo, &path, r#ref)
+                .await?
+                .r#type
+        };
+        let url = self
+            .client
+            .web_url_for(&args.owner, &args.repo, r#type, r#ref, &path);
+
+        Ok(Json(WebUrlResponse { url, r#type }))
+    }
+
+    #[tool(
+        name = "path_exists",
+        description = "Check whether a file or directory exists at a ref without fetching its content."
+    )]
+    async fn path_exists(
+        &self,
+        Parameters(mut args): Parameters<PathExistsArgs>,
+    ) -> Result<Json<PathExistsResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let path = self.upstream_path(&args.path)?;
+        self.ensure_path_allowed(&path)?;
+
+        let r#type = match self
+            .client
+            .get_stats(&args.owner, &args.repo, &path, args.r#ref.as_deref())
+            .await
+        {
+            Ok(item) => Some(item.r#type),
+            Err(err) if err.is_not_found() => None,
+            Err(err) => return Err(err),
+        };
+
+        Ok(Json(PathExistsResponse {
+            exists: r#type.is_some(),
+            r#type,
+        }))
+    }
+
+    #[tool(
+        name = "get_commit_diff",
+        description = "Fetch the raw unified diff or patch text of a commit."
+    )]
+    async fn get_commit_diff(
+        &self,
+        Parameters(mut args): Parameters<CommitDiffArgs>,
+    ) -> Result<Json<CommitDiffResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let diff = self
+            .client
+            .commit_diff(&args.owner, &args.repo, &args.r#ref, args.format)
+            .await?;
+
+        let truncated = args
+            .max_chars
+            .is_some_and(|limit| diff.chars().nth(limit).is_some());
+        let content = apply_content_limits(&diff, None, args.max_chars);
+
+        Ok(Json(CommitDiffResponse { content, truncated }))
+    }
+
+    #[tool(
+        name = "get_blob",
+        description = "Fetch a file's text by its git blob sha, as listed by a tree or commit."
+    )]
+    async fn get_blob(
+        &self,
+        Parameters(mut args): Parameters<GetBlobArgs>,
+    ) -> Result<Json<BlobResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        // A blob has no path to check against the policy, so none can be read under one.
+        if !self.denied_paths.is_empty() || self.options.path_prefix.is_some() {
+            return Err(ApiErrorBody::new(
+                "get_blob is disabled while --deny-path or --path-prefix is set, since a blob sha carries no path to check.",
+                "403",
+            ));
+        }
+        let sha = args.sha.trim().to_ascii_lowercase();
+        if !matches!(sha.len(), 40 | 64) || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ApiErrorBody::new(
+                format!(
+                    "Invalid blob sha `{}`: expected 40 (or 64) hexadecimal characters.",
+                    args.sha
+                ),
+                "400",
+            ));
+        }
+
+        let bytes = self.client.get_blob(&args.owner, &args.repo, &sha).await?;
+        let size = bytes.len() as u64;
+        let (text, had_invalid_utf8) = decode_text(bytes, args.lossy)?;
+        let truncated = args
+            .max_chars
+            .is_some_and(|limit| text.chars().nth(limit).is_some());
+        let content = apply_content_limits(&text, None, args.max_chars);
+
+        Ok(Json(BlobResponse {
+            sha,
+            size,
+            content,
+            truncated,
+            had_invalid_utf8,
+        }))
+    }
+
+    #[tool(
+        name = "bundle",
+        description = "Concatenate the text files under a path into one output with `==== path ====` headers, within a byte budget."
+    )]
+    async fn bundle(
+        &self,
+        Parameters(mut args): Parameters<BundleArgs>,
+    ) -> Result<Json<BundleResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let filter = bundle::PathFilter::new(&args.include, &args.exclude)?;
+        let r#ref = args.r#ref.as_deref();
+        let root = self.upstream_path(args.path.as_deref().unwrap_or_default())?;
+        let entries = self
+            .client
+            .tree(
+                &args.owner,
+                &args.repo,
+                &root,
+                self.options.max_tree_depth,
+                r#ref,
+            )
+            .await?;
+
+        let mut candidates = Vec::new();
+        collect_files(&entries, &mut candidates);
+        let candidates: Vec<(String, u64)> = candidates
+            .into_iter()
+            .filter(|(path, _)| !self.is_path_denied(path))
+            .map(|(path, size)| (self.relative_path(&path), size))
+            .filter(|(path, _)| filter.matches(path))
+            .collect();
+        let costs: Vec<u64> = candidates
+            .iter()
+            .map(|(path, size)| bundle::bundled_size(path, *size))
+            .collect();
+        let mut packer = bundle::Packer::new(&costs, args.max_total_bytes as u64);
+
+        // Binary files are only known once fetched, so files are fetched ahead in order and
+        // packed as they arrive until nothing left could fit.
+        let (owner, repo) = (&args.owner, &args.repo);
+        let mut fetched = stream::iter(candidates)
+            .map(|(path, _)| async move {
+                let upstream = self.upstream_path(&path)?;
+                let text = match self.batch_get_file(owner, repo, &upstream, r#ref).await {
+                    Ok(text) if !bundle::looks_binary(&text) => Some(text),
+                    Ok(_) => None,
+                    Err(err) if err.kind == Some(ErrorKind::Binary) => None,
+                    Err(err) => return Err(err),
+                };
+                Ok::<_, ApiErrorBody>((path, text))
+            })
+            .buffered(self.batch_concurrency(args.concurrency));
+
+        let mut response = BundleResponse {
+            content: String::new(),
+            files: Vec::new(),
+            skipped_binary: Vec::new(),
+            truncated: false,
+        };
+        while !packer.exhausted() {
+            let Some(result) = fetched.next().await else {
+                break;
+            };
+            let (path, text) = result?;
+            let Some(text) = text else {
+                packer.offer(None);
+                response.skipped_binary.push(path);
+                continue;
+            };
+            if !packer.offer(Some(bundle::bundled_size(&path, text.len() as u64))) {
+                continue;
+            }
+
+            response.content.push_str(&bundle::file_header(&path));
+            response.content.push_str(&text);
+            if !text.ends_with('\n') {
+                response.content.push('\n');
+            }
+            response.files.push(path);
+        }
+        response.truncated = packer.truncated;
+
+        Ok(Json(response))
+    }
+
+    #[tool(
+        name = "grep_repo",
+        description = "Search the text files of a small repository for lines matching a regex without using the rate-limited search API."
+    )]
+    async fn grep_repo(
+        &self,
+        Parameters(mut args): Parameters<GrepRepoArgs>,
+    ) -> Result<Json<GrepResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        self.apply_default_ref(&mut args.r#ref);
+        let regex = grep::compile(&args.pattern)?;
+        let filter = bundle::PathFilter::new(&args.include, &[])?;
+        let r#ref = args.r#ref.as_deref();
+        let root = self.upstream_path(args.path.as_deref().unwrap_or_default())?;
+        self.ensure_path_allowed(&root)?;
+        let entries = self
+            .client
+            .tree(
+                &args.owner,
+                &args.repo,
+                &root,
+                self.options.max_tree_depth,
+                r#ref,
+            )
+            .await?;
+
+        let mut files = Vec::new();
+        collect_files(&entries, &mut files);
+        let max_files = args.max_files.min(MAX_GREP_FILES);
+        let mut truncated = false;
+        let mut planned: Vec<(String, u64)> = files
+            .into_iter()
+            .filter(|(path, _)| {
+                !self.is_path_denied(path) && filter.matches(&self.relative_path(path))
+            })
+            .collect();
+        if planned.len() > max_files {
+            planned.truncate(max_files);
+            truncated = true;
+        }
+        let sizes: Vec<u64> = planned.iter().map(|(_, size)| *size).collect();
+        let mut packer = bundle::Packer::new(&sizes, MAX_GREP_BYTES);
+
+        let (owner, repo) = (&args.owner, &args.repo);
+        let mut fetched = stream::iter(planned)
+            .map(|(path, _)| async move {
+                let text = match self.batch_get_file(owner, repo, &path, r#ref).await {
+                    Ok(text) if !bundle::looks_binary(&text) => Some(text),
+                    Ok(_) => None,
+                    Err(err) if err.kind == Some(ErrorKind::Binary) => None,
+                    Err(err) => return Err(err),
+                };
+                Ok::<_, ApiErrorBody>((path, text))
+            })
+            .buffered(self.batch_concurrency(args.concurrency));
+
+        let mut matches = Vec::new();
+        let mut files_searched = 0;
+        while !packer.exhausted() {
+            let Some(result) = fetched.next().await else {
+                break;
+            };
+            let (path, text) = result?;
+            if !packer.offer(text.as_ref().map(|text| text.len() as u64)) {
+                continue;
+            }
+            let Some(text) = text else {
+                continue;
+            };
+            files_searched += 1;
+            let path = self.relative_path(&path);
+            if grep::search(&path, &text, &regex, MAX_GREP_MATCHES, &mut matches) {
+                truncated = true;
+                break;
+            }
+        }
+        truncated |= packer.truncated;
+
+        Ok(Json(GrepResponse {
+            matches,
+            files_searched,
+            truncated,
+        }))
+    }
+
+    #[tool(
+        name = "diff_files",
+        description = "Diff the same or different paths across two repositories (e.g. a fork and its upstream)."
+    )]
+    async fn diff_files(
+        &self,
+        Parameters(mut args): Parameters<DiffFilesArgs>,
+    ) -> Result<Json<DiffFilesResponse>, ApiErrorBody> {
+        for side in [&mut args.left, &mut args.right] {
+            validate_owner_repo(&mut side.owner, &mut side.repo)?;
+            self.apply_default_ref(&mut side.r#ref);
+        }
+        let (left, right) = tokio::join!(
+            self.fetch_optional_file(&args.left),
+            self.fetch_optional_file(&args.right)
+        );
+        let (left, right) = (left?, right?);
+
+        if left.is_none() && right.is_none() {
+            return Err(ApiErrorBody::new("Neither side of the diff exists.", "404"));
+        }
+
+        let (status, diff) = diff::unified_diff(
+            left.as_deref(),
+            right.as_deref(),
+            &diff_label("a", &args.left),
+            &diff_label("b", &args.right),
+        );
+
+        Ok(Json(DiffFilesResponse {
+            status,
+            diff,
+            left: diff_side(args.left, left.as_deref()),
+            right: diff_side(args.right, right.as_deref()),
+        }))
+    }
+
+    #[tool(
+        name = "describe_tools",
+        description = "Describe every enabled tool with its argument and result JSON schemas, for validating calls ahead of time."
+    )]
+    async fn describe_tools(&self) -> Result<Json<DescribeToolsResponse>, ApiErrorBody> {
+        let mut tools: Vec<ToolDescription> = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| ToolDescription {
+                name: tool.name.into_owned(),
+                description: tool.description.map(|text| text.into_owned()),
+                input_schema: (*tool.input_schema).clone(),
+                output_schema: tool.output_schema.map(|schema| (*schema).clone()),
+            })
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Json(DescribeToolsResponse { tools }))
+    }
+
+    #[tool(
+        name = "get_file_at_merge_base",
+        description = "Read a file as it was at the merge base (common ancestor) of two refs."
+    )]
+    async fn get_file_at_merge_base(
+        &self,
+        Parameters(mut args): Parameters<MergeBaseFileArgs>,
+    ) -> Result<Json<MergeBaseFileResponse>, ApiErrorBody> {
+        validate_owner_repo(&mut args.owner, &mut args.repo)?;
+        let path = self.upstream_path(&args.path)?;
+        self.ensure_path_allowed(&path)?;
+
+        let merge_base = self
+            .client
+            .merge_base(&args.owner, &args.repo, &args.base, &args.head)
+            .await?;
+        let content = match self
+            .client
+            .get_file(&args.owner, &args.repo, &path, Some(&merge_base))
+            .await
+        {
+            Ok(content) => Some(content),
+            Err(err) if err.is_not_found() => None,
+            Err(err) => return Err(err),
+        };
+
+        Ok(Json(MergeBaseFileResponse {
+            merge_base,
+            path: args.path,
+            exists: content.is_some(),
+            content,
+        }))
+    }
+}
+
+impl GithubServer {
+    /// Maps a tool `path` argument to its repository path under the configured prefix.
+    fn upstream_path(&self, path: &str) -> Result<String, ApiErrorBody> {
+        let Some(prefix) = &self.options.path_prefix else {
+            return Ok(path.to_string());
+        };
+
+        let path = path.trim_matches('/');
+        if path.split('/').any(|segment| segment == "..") {
+            return Err(ApiErrorBody::new(
+                "Paths may not leave the configured path prefix.",
+                "400",
+            ));
+        }
+
+        Ok(if path.is_empty() {
+            prefix.clone()
+        } else {
+            format!("{}/{}", prefix, path)
+        })
+    }
+
+    /// Inverse of [`Self::upstream_path`] for paths reported back by GitHub.
+    fn relative_path(&self, path: &str) -> String {
+        let Some(prefix) = &self.options.path_prefix else {
+            return path.to_string();
+        };
+
+        match path.strip_prefix(prefix.as_str()) {
+            Some("") => String::new(),
+            Some(rest) if rest.starts_with('/') => rest[1..].to_string(),
+            _ => path.to_string(),
+        }
+    }
+
+    /// Reads a file's bytes, following symlinks when `resolve_symlink` is set; also returns
+    /// the path actually read when it differs from `path`.
+    async fn read_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+        resolve_symlink: bool,
+    ) -> Result<(Vec<u8>, Option<String>), ApiErrorBody> {
+        self.read_file_from(owner, repo, path, r#ref, resolve_symlink, None)
+            .await
+    }
+
+    /// [`Self::read_file`] starting from `fetched`, what `path` held in a response the
+    /// caller already has, instead of asking GitHub again.
+    async fn read_file_from(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+        resolve_symlink: bool,
+        mut fetched: Option<PathContent>,
+    ) -> Result<(Vec<u8>, Option<String>), ApiErrorBody> {
+        if !resolve_symlink {
+            self.ensure_path_allowed(path)?;
+            let content = match fetched {
+                Some(content) => content.into_file()?,
+                None => self.client.get_file_bytes(owner, repo, path, r#ref).await?,
+            };
+            return Ok((content, None));
+        }
+
+        let requested = path.trim_matches('/');
+        let mut current = requested.to_string();
+        let mut visited = HashSet::new();
+
+        loop {
+            if !visited.insert(current.clone()) || visited.len() > MAX_SYMLINK_HOPS {
+                return Err(ApiErrorBody::new(
+                    format!(
+                        "Symlink `{}` does not resolve to a file (loop detected).",
+                        path
+                    ),
+                    "400",
+                ));
+            }
+
+            self.ensure_path_allowed(&current)?;
+            let read = match fetched.take() {
+                Some(content) => Ok(content),
+                None => self.client.read_path(owner, repo, &current, r#ref).await,
+            };
+            let link = match read {
+                Ok(PathContent::File(content)) => {
+                    let resolved = (current != requested).then_some(current);
+                    return Ok((content, resolved));
+                }
+                Ok(PathContent::Symlink { target }) => target,
+                Err(err) if err.is_not_found() && visited.len() > 1 => {
+                    return Err(ApiErrorBody::new(
+                        format!(
+                            "Symlink `{}` is dangling: its target `{}` does not exist.",
+                            path, current
+                        ),
+                        "404",
+                    ));
+                }
+                Err(err) => return Err(err),
+            };
+
+            current = resolve_link_target(&current, &link)
+                .filter(|target| self.within_path_prefix(target))
+                .ok_or_else(|| {
+                    ApiErrorBody::new(
+                        format!(
+                            "Symlink `{}` points to `{}`, outside the readable tree.",
+                            current, link
+                        ),
+                        "400",
+                    )
+                })?;
+        }
+    }
+
+    /// Follows the symlink at `path` to what it finally points at and returns that entry's
+    /// type, or `None` when the chain dangles, loops, or leaves the readable tree.
+    async fn symlink_target_type(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        target: &str,
+        r#ref: Option<&str>,
+    ) -> Result<Option<EntryType>, ApiErrorBody> {
+        let mut current = path.trim_matches('/').to_string();
+        let mut link = target.to_string();
+        let mut visited = HashSet::from([current.clone()]);
+
+        loop {
+            let Some(next) = resolve_link_target(&current, &link)
+                .filter(|next| self.within_path_prefix(next) && !self.is_path_denied(next))
+            else {
+                return Ok(None);
+            };
+            if !visited.insert(next.clone()) || visited.len() > MAX_SYMLINK_HOPS {
+                return Ok(None);
+            }
+
+            let stats = match self.client.get_stats(owner, repo, &next, r#ref).await {
+                Ok(stats) => stats,
+                Err(err) if err.is_not_found() => return Ok(None),
+                Err(err) => return Err(err),
+            };
+            match (stats.r#type, stats.target) {
+                (EntryType::Symlink, Some(target)) => (current, link) = (next, target),
+                (r#type, _) => return Ok(Some(r#type)),
+            }
+        }
+    }
+
+    /// Whether `path` (a repository path) or any directory above it matches a `--deny-path` glob.
+    fn is_path_denied(&self, path: &str) -> bool {
+        if self.denied_paths.is_empty() {
+            return false;
+        }
+
+        let path = path.trim_matches('/');
+        path.match_indices('/')
+            .map(|(index, _)| &path[..index])
+            .chain([path])
+            .any(|candidate| {
+                self.denied_paths
+                    .iter()
+                    .any(|matcher| matcher.is_match(candidate))
+            })
+    }
+
+    /// Fills in `--default-ref` when a call named no ref; an explicit `ref` always wins.
+    fn apply_default_ref(&self, r#ref: &mut Option<String>) {
+        if r#ref.is_none() {
+            r#ref.clone_from(&self.options.default_ref);
+        }
+        if let Some(value) = r#ref.as_mut() {
+            *value = client::normalize_ref(value).to_string();
+        }
+    }
+
+    fn ensure_path_allowed(&self, path: &str) -> Result<(), ApiErrorBody> {
+        if self.is_path_denied(path) {
+            return Err(ApiErrorBody::new(
+                format!(
+                    "Path `{}` is denied by server policy.",
+                    self.relative_path(path.trim_matches('/'))
+                ),
+                "403",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Crawls `path` like `tree`, without the entries `--deny-path` hides, stopping early
+    /// once `max_entries` are listed; an empty repository has no entries. Also returns
+    /// whether the crawl stopped early.
+    async fn crawl_allowed(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        depth: usize,
+        r#ref: Option<&str>,
+        max_entries: Option<usize>,
+    ) -> Result<(Vec<TreeEntry>, bool), ApiErrorBody> {
+        self.ensure_path_allowed(path)?;
+        let limits = CrawlLimits {
+            deadline: None,
+            max_entries,
+        };
+        match self
+            .client
+            .tree_within(owner, repo, path, depth, r#ref, limits)
+            .await
+        {
+            Ok((mut entries, stopped)) => {
+                self.prune_denied(&mut entries);
+                Ok((entries, stopped))
+            }
+            Err(err) if err.kind == Some(ErrorKind::EmptyRepository) => Ok((Vec::new(), false)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Drops denied entries, and everything beneath them, from a tree.
+    fn prune_denied(&self, entries: &mut Vec<TreeEntry>) {
+        entries.retain(|entry| !self.is_path_denied(&entry.path));
+        for entry in entries {
+            self.prune_denied(&mut entry.children);
+        }
+    }
+
+    /// Whether `path` changed after `since`, checked with a conditional request. A missing
+    /// path counts as changed, with nothing fetched, so the caller's regular read reports it
+    /// (or falls back).
+    async fn modified_since(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+        since: &str,
+    ) -> Result<Freshness, ApiErrorBody> {
+        let since = http_date("if_modified_since", since)?;
+        match self
+            .client
+            .modified_since(owner, repo, path, r#ref, &since)
+            .await
+        {
+            Err(err) if err.is_not_found() => Ok(Freshness::missing()),
+            result => result,
+        }
+    }
+
+    /// Whether `path` still has the caller's `etag`. A missing path counts as changed, with
+    /// no ETag, so the caller's regular read reports it (or falls back).
+    async fn etag_freshness(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+        etag: &str,
+    ) -> Result<Freshness, ApiErrorBody> {
+        match self
+            .client
+            .etag_freshness(owner, repo, path, r#ref, etag)
+            .await
+        {
+            Err(err) if err.is_not_found() => Ok(Freshness::missing()),
+            result => result,
+        }
+    }
+
+    /// Paths of the files directly under `dir`; a missing directory has none.
+    async fn list_dir_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        dir: &str,
+        r#ref: Option<&str>,
+    ) -> Result<HashSet<String>, ApiErrorBody> {
+        match self.client.tree(owner, repo, dir, 1, r#ref).await {
+            Ok(entries) => Ok(entries
+                .into_iter()
+                .filter(|entry| matches!(entry.r#type, EntryType::File))
+                .map(|entry| entry.path)
+                .collect()),
+            Err(err) if err.is_not_found() => Ok(HashSet::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Removes entries matched by the root `.gitignore`; a missing file ignores nothing.
+    async fn prune_gitignored(
+        &self,
+        owner: &str,
+        repo: &str,
+        r#ref: Option<&str>,
+        entries: &mut Vec<TreeEntry>,
+    ) -> Result<(), ApiErrorBody> {
+        if self.is_path_denied(".gitignore") {
+            return Ok(());
+        }
+
+        match self.client.get_file(owner, repo, ".gitignore", r#ref).await {
+            Ok(content) => {
+                gitignore::prune(entries, &gitignore::parse(&content));
+                Ok(())
+            }
+            Err(err) if err.is_not_found() => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn within_path_prefix(&self, path: &str) -> bool {
+        match &self.options.path_prefix {
+            Some(prefix) => path
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+            None => true,
+        }
+    }
+
+    /// Adds a top-level `meta` object to a successful structured result.
+    fn attach_meta(
+        &self,
+        result: &mut CallToolResult,
+        requested_ref: Option<String>,
+        retries: RetryStats,
+    ) {
+        if result.is_error == Some(true) {
+            return;
+        }
+        let Some(Value::Object(mut fields)) = result.structured_content.take() else {
+            return;
+        };
+
+        let used_ref = fields
+            .get("used_ref")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let meta = Meta {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            api_base: self.client.api_base().to_string(),
+            r#ref: used_ref.or(requested_ref),
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            retries: retries.retries,
+            backoff_ms: u64::try_from(retries.backoff.as_millis()).unwrap_or(u64::MAX),
+        };
+        fields.insert(
+            "meta".to_string(),
+            serde_json::to_value(meta).unwrap_or_default(),
+        );
+
+        *result = CallToolResult::structured(Value::Object(fields));
+    }
+
+    /// Files a batch tool fetches at once: the call's own `concurrency` if lower than the
+    /// server-wide limit.
+    fn batch_concurrency(&self, requested: Option<usize>) -> usize {
+        let limit = self.options.batch_concurrency;
+        requested.unwrap_or(limit).clamp(1, limit)
+    }
+
+    /// Reads a file for a batch tool while holding one of the shared batch permits.
+    async fn batch_get_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        r#ref: Option<&str>,
+    ) -> Result<String, ApiErrorBody> {
+        let _permit = self
+            .batch_permits
+            .acquire()
+            .await
+            .map_err(|err| ApiErrorBody::new(err.to_string(), "0"))?;
+        self.client.get_file(owner, repo, path, r#ref).await
+    }
+
+    /// Fetches a file, mapping a 404 to `None` so callers can treat it as absent.
+    async fn fetch_optional_file(
+        &self,
+        file: &FileLocator,
+    ) -> Result<Option<String>, ApiErrorBody> {
+        self.ensure_path_allowed(&file.path)?;
+        match self
+            .client
+            .get_file(&file.owner, &file.repo, &file.path, file.r#ref.as_deref())
+            .await
+        {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.is_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl ServerHandler for GithubServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some(
+                "GitHub crawler: query repository metadata, tags, branches, trees, and file contents."
+                    .to_string(),
+            ),
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..ServerInfo::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let requested_ref = request
+            .arguments
+            .as_ref()
+            .and_then(|arguments| arguments.get("ref"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let tool = request.name.clone();
+        let fields = fields::requested(request.arguments.as_ref());
+        let api_base = request
+            .arguments
+            .as_ref()
+            .and_then(|arguments| arguments.get("api_base"))
+            .and_then(Value::as_str);
+
+        if self.calls.is_closed() {
+            return Err(ErrorData::internal_error("Server is shutting down.", None));
+        }
+
+        let overridden;
+        let server = match api_base {
+            Some(api_base) => match self.with_api_base(api_base) {
+                Ok(server) => {
+                    overridden = server;
+                    &overridden
+                }
+                Err(err) => return Ok(CallToolResult::error(err.into_contents())),
+            },
+            None => self,
+        };
+
+        let tcc = ToolCallContext::new(server, request, context);
+        let (result, retries) = self
+            .calls
+            .track_future(
+                server
+                    .client
+                    .scoped_with_stats(server.tool_router.call(tcc)),
+            )
+            .await;
+        let mut result = result?;
+        if let Some(fields) = fields {
+            filter_fields(&mut result, &tool, &fields);
+        }
+        if self.options.include_meta {
+            server.attach_meta(&mut result, requested_ref, retries);
+        }
+        Ok(result)
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+}
+
+/// Applies a `fields` argument to a successful structured result. The filter runs on the
+/// serialized output, so tools build their full responses as usual.
+fn filter_fields(result: &mut CallToolResult, tool: &str, fields: &[String]) {
+    if result.is_error == Some(true) {
+        return;
+    }
+    let Some(mut output) = result.structured_content.take() else {
+        return;
+    };
+
+    fields::retain(tool, &mut output, fields);
+    *result = CallToolResult::structured(output);
+}
+
+/// Flattens a tree into its regular files as `(path, size)`, in tree order.
+fn collect_files(entries: &[TreeEntry], files: &mut Vec<(String, u64)>) {
+    for entry in entries {
+        match entry.r#type {
+            EntryType::File => files.push((entry.path.clone(), entry.size.unwrap_or(0))),
+            EntryType::Dir => collect_files(&entry.children, files),
+            EntryType::Symlink | EntryType::Submodule | EntryType::Unknown => {}
+        }
+    }
+}
+
+/// Flattens a tree into the entries of one `kind`, in tree order, renaming each to its
+/// path below `root`.
+fn flatten_kind(entries: Vec<TreeEntry>, kind: EntryKind, root: &str, out: &mut Vec<TreeEntry>) {
+    for mut entry in entries {
+        let children = std::mem::take(&mut entry.children);
+        let keep = match kind {
+            EntryKind::Files => matches!(entry.r#type, EntryType::File),
+            EntryKind::Dirs => matches!(entry.r#type, EntryType::Dir),
+        };
+        if keep {
+            entry.name = path_below(&entry.path, root).to_string();
+            out.push(entry);
+        }
+        flatten_kind(children, kind, root, out);
+    }
+}
+
+/// Sets `rel_path` on every entry to its path below `root`; the root itself, when the
+/// tree is of a single file, keeps its own name.
+fn fill_rel_paths(entries: &mut [TreeEntry], root: &str) {
+    for entry in entries {
+        let rel_path = match path_below(&entry.path, root) {
+            "" => entry.name.clone(),
+            rel_path => rel_path.to_string(),
+        };
+        entry.rel_path = Some(rel_path);
+        fill_rel_paths(&mut entry.children, root);
+    }
+}
+
+/// `path` relative to the directory `root`, both relative to the repository root.
+fn path_below<'a>(path: &'a str, root: &str) -> &'a str {
+    let root = root.trim_matches('/');
+    match path.strip_prefix(root) {
+        Some(rest) if !root.is_empty() => rest.trim_start_matches('/'),
+        _ => path,
+    }
+}
+
+/// Reads file bytes as text; with `lossy`, invalid UTF-8 becomes U+FFFD instead of an error,
+/// and the flag returned says whether that happened.
+fn decode_text(bytes: Vec<u8>, lossy: bool) -> Result<(String, bool), ApiErrorBody> {
+    if !lossy {
+        return client::utf8_text(bytes).map(|text| (text, false));
+    }
+    Ok(match String::from_utf8(bytes) {
+        Ok(text) => (text, false),
+        Err(err) => (String::from_utf8_lossy(err.as_bytes()).into_owned(), true),
+    })
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Folds each directory whose only child is another directory into that child, joining
+/// their names, so `src` -> `main` -> `java` becomes one `src/main/java` node.
+fn collapse_chains(entries: &mut [TreeEntry]) {
+    for entry in entries {
+        while matches!(entry.r#type, EntryType::Dir)
+            && let [child] = entry.children.as_mut_slice()
+            && matches!(child.r#type, EntryType::Dir)
+        {
+            let child = entry.children.pop().expect("matched a single child");
+            entry.name = format!("{}/{}", entry.name, child.name);
+            entry.path = child.path;
+            entry.children = child.children;
+        }
+        collapse_chains(&mut entry.children);
+    }
+}
+
+/// Resolves a symlink's `target` relative to the link's own directory, returning `None`
+/// when it is absolute or climbs above the repository root.
+fn resolve_link_target(link_path: &str, target: &str) -> Option<String> {
+    if target.starts_with('/') {
+        return None;
+    }
+
+    let mut segments: Vec<&str> = link_path.split('/').filter(|s| !s.is_empty()).collect();
+    segments.pop();
+
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    Some(segments.join("/"))
+}
+
+fn diff_label(prefix: &str, file: &FileLocator) -> String {
+    format!(
+        "{}/{}/{}/{}",
+        prefix,
+        file.owner,
+        file.repo,
+        file.path.trim_start_matches('/')
+    )
+}
+
+fn diff_side(file: FileLocator, content: Option<&str>) -> DiffSide {
+    DiffSide {
+        owner: file.owner,
+        repo: file.repo,
+        path: file.path,
+        r#ref: file.r#ref,
+        exists: content.is_some(),
+        lines: content.map(|content| content.lines().count()).unwrap_or(0),
+    }
+}
+
+fn normalize_content(content: String, normalize_eol: bool, trim_trailing_ws: bool) -> String {
+    let mut content = content;
+
+    if normalize_eol && content.contains('\r') {
+        content = content.replace("\r\n", "\n").replace('\r', "\n");
+    }
+
+    if trim_trailing_ws {
+        content = content
+            .split_inclusive('\n')
+            .map(|line| {
+                let body = line.trim_end_matches('\n').trim_end_matches('\r');
+                let terminator = &line[body.len()..];
+                format!("{}{}", body.trim_end(), terminator)
+            })
+            .collect();
+    }
+
+    content
+}
+
+fn apply_content_limits(
+    content: &str,
+    line_range: Option<LineRange>,
+    max_chars: Option<usize>,
+) -> String {
+    let mut output: String = match max_chars {
+        Some(limit) => content.chars().take(limit).collect(),
+        None => content.to_string(),
+    };
+
+    if let Some(range) = line_range {
+        let (start, end) = range.bounds();
+        output = match end {
+            Some(end) => slice_lines(&output, start, end),
+            None => slice_lines(&output, start, usize::MAX),
+        };
+    }
+
+    output
+}
+
+/// Reads several line ranges of `content` in one pass: ranges are sorted, overlapping or
+/// adjacent ones merged, and each clamped to the last line, so no line is returned twice.
+/// Returns the lines read and the ranges they came from.
+fn extract_line_ranges(content: &str, ranges: &[LineRange]) -> (String, Vec<LineRange>) {
+    let line_count = content.split_inclusive('\n').count();
+    let mut spans: Vec<(usize, usize)> = ranges
+        .iter()
+        .map(|range| {
+            let (start, end) = range.bounds();
+            (start, end.unwrap_or(usize::MAX).min(line_count))
+        })
+        .filter(|&(start, end)| start > 0 && start <= end)
+        .collect();
+    spans.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in spans {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let content = merged
+        .iter()
+        .map(|&(start, end)| slice_lines(content, start, end))
+        .collect();
+    let ranges = merged
+        .into_iter()
+        .map(|(start, end)| LineRange::Range { start, end })
+        .collect();
+    (content, ranges)
+}
+
+fn ensure_single_condition(
+    if_modified_since: &Option<String>,
+    etag: &Option<String>,
+) -> Result<(), ApiErrorBody> {
+    if if_modified_since.is_some() && etag.is_some() {
+        return Err(ApiErrorBody::new(
+            "Set either if_modified_since or etag, not both.",
+            "400",
+        ));
+    }
+    Ok(())
+}
+
+/// Calls a `tree` crawl makes per depth if every directory had `directories` subdirectories:
+/// one for the root, then one for each directory on every level above the last.
+fn estimate_tree_calls(directories: usize, max_depth: usize) -> Vec<DepthEstimate> {
+    let branching = directories as u64;
+    let mut level = 1u64;
+    let mut calls = 0u64;
+
+    (1..=max_depth)
+        .map(|depth| {
+            calls = calls.saturating_add(level);
+            level = level.saturating_mul(branching);
+            DepthEstimate { depth, calls }
+        })
+        .collect()
+}
+
+/// Trims `owner` and `repo` in place and rejects names GitHub could never resolve, so an
+/// agent gets a clear message instead of a 404.
+fn validate_owner_repo(owner: &mut String, repo: &mut String) -> Result<(), ApiErrorBody> {
+    resolve_owner_repo(owner, repo)?;
+    validate_name("owner", owner)?;
+    validate_name("repo", repo)
+}
+
+/// Parses and validates an `owner/repo` string.
+fn parse_full_name(full_name: &str) -> Result<(String, String), ApiErrorBody> {
+    if !full_name.contains('/') {
+        return Err(ApiErrorBody::new(
+            format!("Invalid repo `{}`: it must be `owner/repo`.", full_name),
+            "400",
+        ));
+    }
+    let (mut owner, mut repo) = (String::new(), full_name.to_string());
+    validate_owner_repo(&mut owner, &mut repo)?;
+    Ok((owner, repo))
+}
+
+/// Splits a combined `owner/repo` passed as `repo` while `owner` is left empty.
+fn resolve_owner_repo(owner: &mut String, repo: &mut String) -> Result<(), ApiErrorBody> {
+    if !owner.trim().is_empty() || !repo.contains('/') {
+        return Ok(());
+    }
+
+    match repo.trim().split_once('/') {
+        Some((name, rest)) if !name.is_empty() && !rest.is_empty() && !rest.contains('/') => {
+            (*owner, *repo) = (name.to_string(), rest.to_string());
+            Ok(())
+        }
+        _ => Err(ApiErrorBody::new(
+            format!(
+                "Invalid repo `{}`: with owner omitted it must be `owner/repo`.",
+                repo
+            ),
+            "400",
+        )),
+    }
+}
+
+fn validate_name(field: &str, name: &mut String) -> Result<(), ApiErrorBody> {
+    let trimmed = name.trim();
+    let problem = if trimmed.is_empty() {
+        "must not be empty"
+    } else if trimmed.contains('/') {
+        "must not contain `/`; pass owner and repo as separate arguments, or `owner/repo` as repo alone"
+    } else if trimmed.contains(char::is_whitespace) {
+        "must not contain spaces"
+    } else {
+        if trimmed.len() != name.len() {
+            *name = trimmed.to_string();
+        }
+        return Ok(());
+    };
+
+    Err(ApiErrorBody::new(
+        format!("Invalid {} `{}`: it {}.", field, name, problem),
+        "400",
+    ))
+}
+
+/// Reads an RFC 3339 timestamp, a bare date, or an HTTP date, and renders it as the HTTP
+/// date conditional request headers require.
+fn http_date(name: &str, value: &str) -> Result<String, ApiErrorBody> {
+    let value = value.trim();
+    let parsed = DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        });
+
+    match parsed {
+        Some(date) => Ok(date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()),
+        None => Err(ApiErrorBody::new(
+            format!(
+                "`{}` is not a valid date for {}; use RFC 3339 (`2024-05-01T12:00:00Z`) or an HTTP date (`Wed, 01 May 2024 12:00:00 GMT`).",
+                value, name
+            ),
+            "400",
+        )),
+    }
+}
+
+/// Accepts the ISO-8601 forms GitHub understands: a date, or a date and time with an
+/// optional offset, so a malformed value is reported before any request is made.
+fn validate_timestamp(name: &str, value: &str) -> Result<(), ApiErrorBody> {
+    let valid = DateTime::parse_from_rfc3339(value).is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").is_ok()
+        || NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok();
+    if valid {
+        return Ok(());
+    }
+
+    Err(ApiErrorBody::new(
+        format!(
+            "`{}` is not a valid ISO-8601 date for {}; use `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SSZ`.",
+            value, name
+        ),
+        "400",
+    ))
+}
+
+/// Returns the `index`th `size`-char slice of `content`, split at char boundaries.
+fn take_chunk(
+    content: &str,
+    index: usize,
+    size: usize,
+) -> Result<(String, ChunkInfo), ApiErrorBody> {
+    if size == 0 {
+        return Err(ApiErrorBody::new("chunk_size must be at least 1.", "400"));
+    }
+
+    // An empty file still has one (empty) chunk so that index 0 is always valid.
+    let total_chunks = content.chars().count().div_ceil(size).max(1);
+    if index >= total_chunks {
+        return Err(ApiErrorBody::new(
+            format!(
+                "chunk_index {} is out of range; the content has {} chunk(s).",
+                index, total_chunks
+            ),
+            "400",
+        ));
+    }
+
+    let chunk = content.chars().skip(index * size).take(size).collect();
+
+    Ok((
+        chunk,
+        ChunkInfo {
+            chunk_index: index,
+            total_chunks,
+            has_more: index + 1 < total_chunks,
+        },
+    ))
+}
+
+fn slice_lines(content: &str, start: usize, end: usize) -> String {
+    if start == 0 || end == 0 || end < start {
+        return String::new();
+    }
+
+    let mut limited = String::new();
+
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let line_no = idx + 1;
+        if line_no < start {
+            continue;
+        }
+        if line_no > end {
+            break;
+        }
+        limited.push_str(line);
+    }
+
+    // When the content lacks a trailing newline, split_inclusive will still return
+    // the final segment without a newline, which we already appended above.
+    limited
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use serde_json::json;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path, query_param},
+    };
+
+    use super::*;
+
+    fn mock_server(server: &MockServer) -> GithubServer {
+        let client = GithubClient::new(server.uri(), None).unwrap();
+        GithubServer::new(client, ToolSelection::ALL.into_iter().collect())
+    }
+
+    fn server_with(server: &MockServer, options: ServerOptions) -> GithubServer {
+        let client = GithubClient::new(server.uri(), None).unwrap();
+        GithubServer::with_options(client, ToolSelection::ALL.into_iter().collect(), options)
+    }
+
+    fn globs(patterns: &[&str]) -> Vec<Glob> {
+        patterns
+            .iter()
+            .map(|pattern| Glob::new(pattern).unwrap())
+            .collect()
+    }
+
+    fn file_body(path: &str, content: &str) -> serde_json::Value {
+        json!({
+            "path": path,
+            "type": "file",
+            "size": content.len(),
+            "content": STANDARD.encode(content),
+            "encoding": "base64",
+        })
+    }
+
+    fn file_args(value: serde_json::Value) -> Parameters<GetFileArgs> {
+        Parameters(serde_json::from_value(value).unwrap())
+    }
+
+    fn issues_body() -> serde_json::Value {
+        json!([
+            {
+                "number": 1,
+                "title": "Bug",
+                "state": "open",
+                "labels": [{ "name": "bug" }],
+                "comments": 2,
+                "html_url": "https://github.com/octo/repo/issues/1",
+                "created_at": "2024-01-01T00:00:00Z"
+            },
+            {
+                "number": 2,
+                "title": "Fix bug",
+                "state": "open",
+                "labels": [],
+                "comments": 0,
+                "html_url": "https://github.com/octo/repo/pull/2",
+                "created_at": "2024-01-02T00:00:00Z",
+                "pull_request": { "url": "https://api.github.com/repos/octo/repo/pulls/2" }
+            }
+        ])
+    }
+
+    #[tokio::test]
+    async fn tree_depth_is_clamped_to_the_configured_ceiling() {
+        let server = MockServer::start().await;
+        let dir = |path: &str| {
+            json!([{
+                "name": path.rsplit('/').next().unwrap(),
+                "path": path,
+                "type": "dir",
+            }])
+        };
+        // Only the root and `a` may be listed at depth 2.
+        for (listing, child, calls) in [("", "a", 1), ("a", "a/b", 1), ("a/b", "a/b/c", 0)] {
+            Mock::given(method("GET"))
+                .and(path(
+                    format!("/repos/octo/repo/contents/{}", listing).trim_end_matches('/'),
+                ))
+                .respond_with(ResponseTemplate::new(200).set_body_json(dir(child)))
+                .expect(calls)
+                .mount(&server)
+                .await;
+        }
+
+        let github = server_with(
+            &server,
+            ServerOptions {
+                max_tree_depth: 2,
+                ..ServerOptions::default()
+            },
+        );
+
+        let Json(response) = github
+            .tree(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "depth": 1_000_000,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.depth, 2);
+        assert_eq!(response.entries[0].children[0].name, "b");
+        assert!(response.entries[0].children[0].children.is_empty());
+    }
+
+    #[tokio::test]
+    async fn denied_files_are_refused_without_calling_github() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body(".env", "KEY=1")))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let github = server_with(
+            &server,
+            ServerOptions {
+                deny_paths: globs(&[".env", "**/secrets"]),
+                ..ServerOptions::default()
+            },
+        );
+
+        for denied in [".env", "config/secrets/key.pem"] {
+            let Err(err) = github
+                .get_file(file_args(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": denied,
+                })))
+                .await
+            else {
+                panic!("expected {} to be denied", denied);
+            };
+            assert_eq!(err.code, "403");
+            assert!(err.message.contains("denied by server policy"));
+        }
+    }
+
+    #[tokio::test]
+    async fn tree_prunes_denied_paths() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": ".env", "path": ".env", "type": "file", "size": 5 },
+                { "name": "config", "path": "config", "type": "dir" },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/config"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "app.toml", "path": "config/app.toml", "type": "file", "size": 10 },
+                { "name": "secrets", "path": "config/secrets", "type": "dir" },
+            ])))
+            .mount(&server)
+            .await;
+        let github = server_with(
+            &server,
+            ServerOptions {
+                deny_paths: globs(&[".env", "**/secrets"]),
+                ..ServerOptions::default()
+            },
+        );
+
+        let Json(response) = github
+            .tree(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "depth": 2,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.entries.len(), 1);
+        assert_eq!(response.entries[0].name, "config");
+        let children: Vec<&str> = response.entries[0]
+            .children
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(children, vec!["app.toml"]);
+    }
+
+    #[tokio::test]
+    async fn get_blob_is_refused_under_path_policies() {
+        let server = MockServer::start().await;
+        let args = || {
+            Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "sha": "3f786850e387550fdab836ed7e6dc881de23001b",
+                }))
+                .unwrap(),
+            )
+        };
+
+        for github in [
+            server_with(
+                &server,
+                ServerOptions {
+                    deny_paths: globs(&[".env"]),
+                    ..ServerOptions::default()
+                },
+            ),
+            server_with(
+                &server,
+                ServerOptions {
+                    path_prefix: Some("packages/app".to_string()),
+                    ..ServerOptions::default()
+                },
+            ),
+        ] {
+            let Err(err) = github.get_blob(args()).await else {
+                panic!("get_blob read a blob under a path policy");
+            };
+            assert_eq!(err.code, "403");
+        }
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_main_file_never_picks_a_denied_entry_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "src", "path": "src", "type": "dir" },
+                { "name": "index.js", "path": "index.js", "type": "file", "size": 5 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "main.rs", "path": "src/main.rs", "type": "file", "size": 5 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/index.js"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body("index.js", "run()")))
+            .mount(&server)
+            .await;
+        let args = || {
+            Parameters(serde_json::from_value(json!({ "owner": "octo", "repo": "repo" })).unwrap())
+        };
+
+        let Json(response) = server_with(
+            &server,
+            ServerOptions {
+                deny_paths: globs(&["src/main.rs"]),
+                ..ServerOptions::default()
+            },
+        )
+        .get_main_file(args())
+        .await
+        .unwrap();
+        assert_eq!(response.path, "index.js");
+        assert_eq!(response.content, "run()");
+
+        let Err(err) = server_with(
+            &server,
+            ServerOptions {
+                deny_paths: globs(&["src", "index.js"]),
+                ..ServerOptions::default()
+            },
+        )
+        .get_main_file(args())
+        .await
+        else {
+            panic!("a denied entry file was returned");
+        };
+        assert_eq!(err.code, "404");
+    }
+
+    #[tokio::test]
+    async fn tree_respects_the_root_gitignore() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "node_modules", "path": "node_modules", "type": "dir" },
+                { "name": "index.js", "path": "index.js", "type": "file", "size": 5 },
+                { "name": "debug.log", "path": "debug.log", "type": "file", "size": 5 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/.gitignore"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(file_body(".gitignore", "node_modules/\n*.log\n")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .tree(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "respect_gitignore": true,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = response
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["index.js"]);
+    }
+
+    #[tokio::test]
+    async fn tree_include_paths_adds_paths_below_the_root() {
+        let server = MockServer::start().await;
+        let entry = |path: &str, kind: &str| {
+            let name = path.rsplit('/').next().unwrap();
+            json!({ "name": name, "path": path, "type": kind, "size": 1 })
+        };
+        for (dir, entries) in [
+            (
+                "pkg",
+                json!([entry("pkg/build.rs", "file"), entry("pkg/src", "dir")]),
+            ),
+            (
+                "pkg/src",
+                json!([
+                    entry("pkg/src/lib.rs", "file"),
+                    entry("pkg/src/util", "dir")
+                ]),
+            ),
+            ("pkg/src/util", json!([entry("pkg/src/util/io.rs", "file")])),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/contents/{dir}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(entries))
+                .mount(&server)
+                .await;
+        }
+        let github = mock_server(&server);
+        let tree = |include_paths: bool| {
+            github.tree(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": "pkg",
+                    "depth": 3,
+                    "include_paths": include_paths,
+                }))
+                .unwrap(),
+            ))
+        };
+
+        let Json(response) = tree(true).await.unwrap();
+        let output = serde_json::to_value(&response).unwrap();
+        // Directories are listed before files.
+        let src = &output["entries"][0];
+        assert_eq!(src["rel_path"], "src");
+        assert_eq!(output["entries"][1]["rel_path"], "build.rs");
+        assert_eq!(src["children"][1]["rel_path"], "src/lib.rs");
+        assert_eq!(
+            src["children"][0]["children"][0]["rel_path"],
+            "src/util/io.rs"
+        );
+        assert_eq!(src["children"][0]["children"][0]["name"], "io.rs");
+
+        let Json(response) = tree(false).await.unwrap();
+        let output = serde_json::to_value(&response).unwrap();
+        assert!(output["entries"][0].get("rel_path").is_none());
+    }
+
+    #[tokio::test]
+    async fn get_file_with_imports_fetches_local_modules() {
+        let server = MockServer::start().await;
+        let lib = "mod parser;\nmod missing;\nuse crate::util::helper;\nuse std::fmt;\n";
+        for (file, content) in [
+            ("src/lib.rs", lib),
+            ("src/parser.rs", "pub fn parse() {}\n"),
+            ("src/util.rs", "pub fn helper() {}\n"),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/contents/{}", file)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(file_body(file, content)))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "lib.rs", "path": "src/lib.rs", "type": "file", "size": 1 },
+                { "name": "parser.rs", "path": "src/parser.rs", "type": "file", "size": 1 },
+                { "name": "util.rs", "path": "src/util.rs", "type": "file", "size": 1 },
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_file_with_imports(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": "src/lib.rs",
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, lib);
+        assert_eq!(response.language.as_deref(), Some("Rust"));
+        let imported: Vec<&str> = response.imports.keys().map(String::as_str).collect();
+        assert_eq!(imported, vec!["src/parser.rs", "src/util.rs"]);
+        assert_eq!(response.imports["src/util.rs"], "pub fn helper() {}\n");
+        assert_eq!(response.unresolved, vec!["self::missing"]);
+        assert!(!response.truncated);
+    }
+
+    #[tokio::test]
+    async fn tree_returns_partial_entries_when_the_deadline_passes() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "docs", "path": "docs", "type": "dir" },
+                { "name": "slow", "path": "slow", "type": "dir" },
+                { "name": "README.md", "path": "README.md", "type": "file", "size": 1 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "guide.md", "path": "docs/guide.md", "type": "file", "size": 1 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/slow"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([]))
+                    .set_delay(std::time::Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .tree(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "depth": 3,
+                    "deadline_secs": 1,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(response.timed_out);
+        assert!(response.truncated);
+        let listed: Vec<(&str, usize)> = response
+            .entries
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.children.len()))
+            .collect();
+        assert_eq!(listed, vec![("docs", 1), ("slow", 0), ("README.md", 0)]);
+    }
+
+    #[tokio::test]
+    async fn tree_deadline_also_bounds_reading_the_gitignore() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "target", "path": "target", "type": "file", "size": 1 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/.gitignore"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(file_body(".gitignore", "target\n"))
+                    .set_delay(std::time::Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .tree(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "respect_gitignore": true,
+                    "deadline_secs": 1,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        // The listing finished, but without the filter it cannot be returned as the tree.
+        assert!(response.timed_out);
+        assert!(response.truncated);
+        assert!(response.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn tree_treats_an_unrepresentable_deadline_as_none() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "README.md", "path": "README.md", "type": "file", "size": 1 },
+            ])))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .tree(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "deadline_secs": usize::MAX,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(!response.timed_out);
+        assert_eq!(response.entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn dir_profile_counts_files_per_extension_at_every_depth() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "src", "path": "src", "type": "dir" },
+                { "name": "README.md", "path": "README.md", "type": "file", "size": 10 },
+                { "name": "Makefile", "path": "Makefile", "type": "file", "size": 4 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "lib.rs", "path": "src/lib.rs", "type": "file", "size": 100 },
+                { "name": "main.RS", "path": "src/main.RS", "type": "file", "size": 20 },
+                { "name": "notes.md", "path": "src/notes.md", "type": "file", "size": 5 },
+                { "name": "LICENSE", "path": "src/LICENSE", "type": "file", "size": 1 },
+            ])))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .dir_profile(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo" })).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        let counts: Vec<(Option<&str>, usize, u64)> = response
+            .by_extension
+            .iter()
+            .map(|ext| (ext.ext.as_deref(), ext.count, ext.total_bytes))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(None, 2, 5), (Some("md"), 2, 15), (Some("rs"), 2, 120)]
+        );
+        assert_eq!(response.total_files, 6);
+        assert_eq!(response.total_dirs, 1);
+    }
+
+    #[tokio::test]
+    async fn get_dependencies_reads_the_root_manifest() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "package.json", "path": "package.json", "type": "file", "size": 1 },
+                { "name": "README.md", "path": "README.md", "type": "file", "size": 1 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/package.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body(
+                "package.json",
+                r#"{ "devDependencies": { "vitest": "^1.6.0" } }"#,
+            )))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_dependencies(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo" })).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.manifest, "package.json");
+        assert_eq!(response.dependencies.len(), 1);
+        assert_eq!(response.dependencies[0].name, "vitest");
+        assert_eq!(
+            response.dependencies[0].kind,
+            crate::models::DependencyKind::Dev
+        );
+    }
+
+    #[tokio::test]
+    async fn get_dependencies_names_unsupported_manifests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "pyproject.toml", "path": "pyproject.toml", "type": "file", "size": 1 },
+            ])))
+            .mount(&server)
+            .await;
+
+        let Err(err) = mock_server(&server)
+            .get_dependencies(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo" })).unwrap(),
+            ))
+            .await
+        else {
+            panic!("a pyproject.toml-only root should be rejected");
+        };
+
+        assert_eq!(err.code, "404");
+        assert!(
+            err.message
+                .contains("found pyproject.toml, which is not supported")
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_repo_matches_lines_across_included_files() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "src", "path": "src", "type": "dir" },
+                { "name": "README.md", "path": "README.md", "type": "file", "size": 20 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "a.rs", "path": "src/a.rs", "type": "file", "size": 30 },
+                { "name": "b.rs", "path": "src/b.rs", "type": "file", "size": 30 },
+            ])))
+            .mount(&server)
+            .await;
+        for (file, content) in [
+            ("src/a.rs", "// TODO: split\nfn a() {}\n"),
+            ("src/b.rs", "fn b() {}\n// todo later\n"),
+            ("README.md", "TODO: docs\n"),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/contents/{}", file)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(file_body(file, content)))
+                .mount(&server)
+                .await;
+        }
+
+        let Json(response) = mock_server(&server)
+            .grep_repo(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "pattern": "(?i)todo",
+                    "include": ["**/*.rs"],
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        let found: Vec<(&str, usize, &str)> = response
+            .matches
+            .iter()
+            .map(|m| (m.path.as_str(), m.line_no, m.line.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("src/a.rs", 1, "// TODO: split"),
+                ("src/b.rs", 2, "// todo later"),
+            ]
+        );
+        assert_eq!(response.files_searched, 2);
+        assert!(!response.truncated);
+    }
+
+    #[test]
+    fn collapses_single_directory_chains() {
+        let mut entries = vec![TreeEntry::stub_dir(
+            "src",
+            vec![TreeEntry::stub_dir(
+                "src/main",
+                vec![TreeEntry::stub_dir(
+                    "src/main/java",
+                    vec![TreeEntry::stub_file("src/main/java/App.java")],
+                )],
+            )],
+        )];
+
+        collapse_chains(&mut entries);
+
+        assert_eq!(entries[0].name, "src/main/java");
+        assert_eq!(entries[0].path, "src/main/java");
+        assert_eq!(entries[0].children[0].name, "App.java");
+    }
+
+    #[test]
+    fn keeps_directories_that_also_hold_files() {
+        let mut entries = vec![TreeEntry::stub_dir(
+            "src",
+            vec![
+                TreeEntry::stub_dir(
+                    "src/main",
+                    vec![TreeEntry::stub_dir(
+                        "src/main/java",
+                        vec![TreeEntry::stub_file("src/main/java/A.java")],
+                    )],
+                ),
+                TreeEntry::stub_file("src/build.gradle"),
+            ],
+        )];
+
+        collapse_chains(&mut entries);
+
+        assert_eq!(entries[0].name, "src");
+        assert_eq!(entries[0].children.len(), 2);
+        assert_eq!(entries[0].children[0].name, "main/java");
+        assert_eq!(entries[0].children[0].path, "src/main/java");
+    }
+
+    fn sample_tree() -> Vec<TreeEntry> {
+        vec![
+            TreeEntry::stub_dir(
+                "docs/src",
+                vec![
+                    TreeEntry::stub_dir(
+                        "docs/src/api",
+                        vec![TreeEntry::stub_file("docs/src/api/index.md")],
+                    ),
+                    TreeEntry::stub_file("docs/src/intro.md"),
+                ],
+            ),
+            TreeEntry::stub_file("docs/README.md"),
+        ]
+    }
+
+    fn flat_names(kind: EntryKind) -> Vec<String> {
+        let mut flat = Vec::new();
+        flatten_kind(sample_tree(), kind, "docs", &mut flat);
+        assert!(flat.iter().all(|entry| entry.children.is_empty()));
+        flat.into_iter().map(|entry| entry.name).collect()
+    }
+
+    #[test]
+    fn only_files_flattens_to_file_paths() {
+        assert_eq!(
+            flat_names(EntryKind::Files),
+            vec!["src/api/index.md", "src/intro.md", "README.md"]
+        );
+    }
+
+    #[test]
+    fn only_dirs_flattens_to_directory_paths() {
+        assert_eq!(flat_names(EntryKind::Dirs), vec!["src", "src/api"]);
+    }
+
+    #[tokio::test]
+    async fn path_prefix_is_prepended_upstream_and_stripped_from_results() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/packages/foo/src/lib.rs"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(file_body("packages/foo/src/lib.rs", "pub fn lib() {}\n")),
+            )
+            .mount(&server)
+            .await;
+        let github = server_with(
+            &server,
+            ServerOptions {
+                path_prefix: Some("/packages/foo/".to_string()),
+                ..ServerOptions::default()
+            },
+        );
+
+        let Json(file) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "src/lib.rs",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(file.content, "pub fn lib() {}\n");
+
+        let Json(stats) = github
+            .get_stats(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": "/src/lib.rs",
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+        let item = stats.item.unwrap();
+        assert_eq!(item.path, "src/lib.rs");
+        assert_eq!(item.name, "lib.rs");
+    }
+
+    #[tokio::test]
+    async fn path_prefix_rejects_parent_segments() {
+        let server = MockServer::start().await;
+        let github = server_with(
+            &server,
+            ServerOptions {
+                path_prefix: Some("packages/foo".to_string()),
+                ..ServerOptions::default()
+            },
+        );
+
+        let Err(err) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "../bar/secret.txt",
+            })))
+            .await
+        else {
+            panic!("expected escaping path to be rejected");
+        };
+        assert_eq!(err.code, "400");
+    }
+
+    #[tokio::test]
+    async fn get_commit_diff_truncates_to_max_chars() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits/abc123"))
+            .and(wiremock::matchers::header(
+                "accept",
+                "application/vnd.github.patch",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string("From abc123 Mon Sep 17\n"))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_commit_diff(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "ref": "abc123",
+                    "format": "patch",
+                    "max_chars": 11,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "From abc123");
+        assert!(response.truncated);
+    }
+
+    #[tokio::test]
+    async fn list_pull_files_drops_patches_unless_requested() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/pulls/7/files"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "filename": "README.md",
+                "status": "modified",
+                "additions": 1,
+                "deletions": 0,
+                "patch": "@@ -1 +1,2 @@\n a\n+b",
+            }])))
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+        let args = |include_patch: bool| {
+            Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "number": 7,
+                    "page": 2,
+                    "include_patch": include_patch,
+                }))
+                .unwrap(),
+            )
+        };
+
+        let Json(without) = github.list_pull_files(args(false)).await.unwrap();
+        let Json(with) = github.list_pull_files(args(true)).await.unwrap();
+
+        assert_eq!(without.files[0].filename, "README.md");
+        assert!(without.files[0].patch.is_none());
+        assert_eq!(
+            with.files[0].patch.as_deref(),
+            Some("@@ -1 +1,2 @@\n a\n+b")
+        );
+    }
+
+    #[tokio::test]
+    async fn list_commits_rejects_malformed_dates_without_calling_github() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        for (field, value) in [
+            ("since", "last week"),
+            ("until", "2024-13-01"),
+            ("since", "2024-05-01 10:00"),
+        ] {
+            let Err(err) = github
+                .list_commits(Parameters(
+                    serde_json::from_value(json!({
+                        "owner": "octo",
+                        "repo": "repo",
+                        field: value,
+                    }))
+                    .unwrap(),
+                ))
+                .await
+            else {
+                panic!("expected {} to be rejected", value);
+            };
+            assert_eq!(err.code, "400");
+            assert!(err.message.contains(field));
+        }
+    }
+
+    #[tokio::test]
+    async fn list_commits_order_asc_returns_oldest_first() {
+        let server = MockServer::start().await;
+        let commit = |sha: &str, date: &str| {
+            json!({
+                "sha": sha,
+                "commit": { "message": sha, "author": { "name": "Octo", "date": date } },
+                "html_url": format!("https://github.com/octo/repo/commit/{}", sha),
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                commit("c3", "2024-05-03T00:00:00Z"),
+                commit("c2", "2024-05-02T00:00:00Z"),
+                commit("c1", "2024-05-01T00:00:00Z"),
+            ])))
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let shas = |order: &str| {
+            let github = &github;
+            let args = json!({ "owner": "octo", "repo": "repo", "order": order });
+            async move {
+                let Ok(Json(response)) = github
+                    .list_commits(Parameters(serde_json::from_value(args).unwrap()))
+                    .await
+                else {
+                    panic!("expected commits");
+                };
+                response
+                    .commits
+                    .into_iter()
+                    .map(|commit| commit.sha)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(shas("asc").await, vec!["c1", "c2", "c3"]);
+        assert_eq!(shas("desc").await, vec!["c3", "c2", "c1"]);
+    }
+
+    #[tokio::test]
+    async fn search_rejects_reserved_extra_params_without_calling_github() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        for key in ["q", "per_page", ""] {
+            let Err(err) = github
+                .search(Parameters(
+                    serde_json::from_value(json!({
+                        "query": "fn parse",
+                        "extra_params": { key: "1", "sort": "indexed" },
+                    }))
+                    .unwrap(),
+                ))
+                .await
+            else {
+                panic!("expected `{}` to be rejected", key);
+            };
+            assert_eq!(err.code, "400");
+            assert!(
+                err.message.contains(&format!("`{}`", key)),
+                "{}",
+                err.message
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn web_url_links_files_as_blobs_and_directories_as_trees() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src/lib.rs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body("src/lib.rs", "")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "lib.rs", "path": "src/lib.rs", "type": "file", "size": 0 },
+            ])))
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let url = |path: &str| {
+            let args = json!({ "owner": "octo", "repo": "repo", "path": path, "ref": "main" });
+            let github = &github;
+            async move {
+                let Ok(Json(response)) = github
+                    .web_url(Parameters(serde_json::from_value(args).unwrap()))
+                    .await
+                else {
+                    panic!("expected a web url");
+                };
+                response.url
+            }
+        };
+
+        assert_eq!(
+            url("src/lib.rs").await,
+            format!("{}/octo/repo/blob/main/src/lib.rs", server.uri())
+        );
+        assert_eq!(
+            url("src").await,
+            format!("{}/octo/repo/tree/main/src", server.uri())
+        );
+    }
+
+    #[tokio::test]
+    async fn tree_estimate_counts_top_level_directories() {
+        let server = MockServer::start().await;
+        let entry =
+            |name: &str, kind: &str| json!({ "name": name, "path": name, "type": kind, "size": 0 });
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                entry("src", "dir"),
+                entry("docs", "dir"),
+                entry("tests", "dir"),
+                entry("README.md", "file"),
+                entry("vendor", "submodule"),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .tree_estimate(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo", "depth": 3 }))
+                    .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.directories, 3);
+        assert_eq!(response.files, 1);
+        let calls: Vec<u64> = response.estimates.iter().map(|e| e.calls).collect();
+        assert_eq!(calls, vec![1, 4, 13]);
+    }
+
+    async fn mount_nested_tree(server: &MockServer, nested_calls: u64) {
+        let entry = |path: &str, kind: &str, size: u64| {
+            let name = path.rsplit('/').next().unwrap();
+            json!({ "name": name, "path": path, "type": kind, "size": size })
+        };
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                entry("README.md", "file", 10),
+                entry("src", "dir", 0),
+                entry("vendor", "submodule", 0),
+            ])))
+            .expect(1)
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                entry("src/lib.rs", "file", 100),
+                entry("src/util", "dir", 0),
+            ])))
+            .expect(1)
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src/util"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                entry("src/util/a.rs", "file", 5),
+                entry("src/util/b.rs", "file", 7),
+            ])))
+            .expect(nested_calls)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn dir_size_sums_files_across_nested_directories() {
+        let server = MockServer::start().await;
+        mount_nested_tree(&server, 1).await;
+
+        let Json(response) = mock_server(&server)
+            .dir_size(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo" })).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.total_bytes, 122);
+        assert_eq!(response.file_count, 4);
+        assert_eq!(response.dir_count, 2);
+    }
+
+    #[tokio::test]
+    async fn dir_size_stops_at_max_depth() {
+        let server = MockServer::start().await;
+        mount_nested_tree(&server, 0).await;
+
+        let Json(response) = mock_server(&server)
+            .dir_size(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo", "max_depth": 2 }))
+                    .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.depth, 2);
+        assert_eq!(response.total_bytes, 110);
+        assert_eq!(response.file_count, 2);
+        assert_eq!(response.dir_count, 2);
+    }
+
+    #[test]
+    fn estimate_tree_calls_saturates_instead_of_overflowing() {
+        let estimates = estimate_tree_calls(usize::MAX, 4);
+
+        assert_eq!(estimates[0].calls, 1);
+        assert_eq!(estimates[3].calls, u64::MAX);
+    }
+
+    #[test]
+    fn validate_owner_repo_trims_valid_names() {
+        let (mut owner, mut repo) = (" Rust-Lang ".to_string(), "cargo\n".to_string());
+        validate_owner_repo(&mut owner, &mut repo).unwrap();
+
+        assert_eq!(owner, "Rust-Lang");
+        assert_eq!(repo, "cargo");
+    }
+
+    #[test]
+    fn validate_owner_repo_rejects_invalid_names() {
+        for (owner, repo, field) in [
+            ("", "cargo", "owner"),
+            ("rust-lang", "   ", "repo"),
+            ("rust-lang/cargo", "cargo", "owner"),
+            ("rust-lang", "rust lang", "repo"),
+        ] {
+            let err = validate_owner_repo(&mut owner.to_string(), &mut repo.to_string())
+                .expect_err(&format!("{}/{} should be rejected", owner, repo));
+
+            assert_eq!(err.code, "400");
+            assert!(err.message.starts_with(&format!("Invalid {}", field)));
+        }
+    }
+
+    #[test]
+    fn validate_owner_repo_splits_a_combined_repo() {
+        let (mut owner, mut repo) = (String::new(), " octo/repo ".to_string());
+        validate_owner_repo(&mut owner, &mut repo).unwrap();
+
+        assert_eq!(owner, "octo");
+        assert_eq!(repo, "repo");
+    }
+
+    #[test]
+    fn validate_owner_repo_rejects_malformed_combined_repos() {
+        for repo in ["octo/", "/repo", "octo/repo/extra", "/"] {
+            let err = validate_owner_repo(&mut String::new(), &mut repo.to_string())
+                .expect_err(&format!("{} should be rejected", repo));
+
+            assert_eq!(err.code, "400");
+            assert!(err.message.contains("must be `owner/repo`"));
+        }
+
+        let err = validate_owner_repo(&mut "octo".to_string(), &mut "octo/repo".to_string())
+            .expect_err("a combined repo alongside an owner should be rejected");
+        assert!(err.message.starts_with("Invalid repo"));
+    }
+
+    #[tokio::test]
+    async fn tools_reject_invalid_names_without_calling_github() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let Err(err) = mock_server(&server)
+            .list_tags(Parameters(
+                serde_json::from_value(json!({ "owner": "octo/repo", "repo": "" })).unwrap(),
+            ))
+            .await
+        else {
+            panic!("expected octo/repo to be rejected as an owner");
+        };
+
+        assert!(
+            err.message
+                .contains("pass owner and repo as separate arguments")
+        );
+    }
+
+    #[test]
+    fn validate_timestamp_accepts_dates_and_timestamps() {
+        for value in [
+            "2024-05-01",
+            "2024-05-01T12:00:00",
+            "2024-05-01T12:00:00Z",
+            "2024-05-01T12:00:00+02:00",
+        ] {
+            assert!(validate_timestamp("since", value).is_ok(), "{}", value);
+        }
+    }
+
+    fn repos_body() -> serde_json::Value {
+        let repo = |name: &str, fork: bool, archived: bool| {
+            json!({
+                "name": name,
+                "full_name": format!("octo/{}", name),
+                "private": false,
+                "html_url": format!("https://github.com/octo/{}", name),
+                "description": null,
+                "fork": fork,
+                "archived": archived,
+            })
+        };
+
+        json!([
+            repo("source", false, false),
+            repo("forked", true, false),
+            repo("retired", false, true),
+        ])
+    }
+
+    async fn list_repo_names(github: &GithubServer, args: serde_json::Value) -> Vec<String> {
+        let Json(response) = github
+            .list_repos(Parameters(serde_json::from_value(args).unwrap()))
+            .await
+            .unwrap();
+        response.repos.into_iter().map(|repo| repo.name).collect()
+    }
+
+    #[tokio::test]
+    async fn list_repos_filters_forks_and_archived_when_requested() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/octo/repos"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(repos_body()))
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        assert_eq!(
+            list_repo_names(&github, json!({ "owner": "octo" })).await,
+            vec!["source", "forked", "retired"]
+        );
+        assert_eq!(
+            list_repo_names(&github, json!({ "owner": "octo", "exclude_forks": true })).await,
+            vec!["source", "retired"]
+        );
+        assert_eq!(
+            list_repo_names(
+                &github,
+                json!({ "owner": "octo", "exclude_forks": true, "exclude_archived": true })
+            )
+            .await,
+            vec!["source"]
+        );
+    }
+
+    #[tokio::test]
+    async fn list_repos_with_owner_type_skips_the_other_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/octo/repos"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/orgs/octo/repos"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(repos_body()))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        for args in [
+            json!({ "owner": "octo", "owner_type": "org" }),
+            json!({ "owner": "octo", "owner_type": "org", "all_pages": true }),
+        ] {
+            assert_eq!(
+                list_repo_names(&github, args).await,
+                vec!["source", "forked", "retired"]
+            );
+        }
+    }
+
+    fn symlink_body(path: &str, target: &str) -> serde_json::Value {
+        json!({
+            "path": path,
+            "type": "symlink",
+            "size": target.len(),
+            "target": target,
+        })
+    }
+
+    #[test]
+    fn resolves_link_targets_relative_to_the_link() {
+        assert_eq!(
+            resolve_link_target("docs/link", "../README.md").as_deref(),
+            Some("README.md")
+        );
+        assert_eq!(
+            resolve_link_target("a/b/link", "./c/../d.txt").as_deref(),
+            Some("a/b/d.txt")
+        );
+        assert_eq!(resolve_link_target("link", "../outside"), None);
+        assert_eq!(resolve_link_target("link", "/etc/passwd"), None);
+    }
+
+    #[tokio::test]
+    async fn get_file_follows_symlinks_when_requested() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/docs/current"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(symlink_body("docs/current", "v2/guide.md")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/docs/v2/guide.md"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(file_body("docs/v2/guide.md", "# Guide\n")),
+            )
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let Json(response) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "docs/current",
+                "resolve_symlink": true,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.content, "# Guide\n");
+        assert_eq!(response.resolved_path.as_deref(), Some("docs/v2/guide.md"));
+        assert_eq!(response.language.as_deref(), Some("Markdown"));
+
+        let Err(err) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "docs/current",
+            })))
+            .await
+        else {
+            panic!("expected symlink without resolve_symlink to fail");
+        };
+        assert!(err.message.contains("v2/guide.md"), "{}", err.message);
+    }
+
+    async fn stats_target_type(server: &MockServer, link: &str) -> Option<EntryType> {
+        let Json(response) = mock_server(server)
+            .get_stats(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": link,
+                    "resolve_target": true,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+        response.item.unwrap().target_type
+    }
+
+    #[tokio::test]
+    async fn get_stats_resolves_symlinks_to_files() {
+        let server = MockServer::start().await;
+        for (link, target) in [("latest", "docs/current"), ("docs/current", "v2/guide.md")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/contents/{}", link)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(symlink_body(link, target)))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/docs/v2/guide.md"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(file_body("docs/v2/guide.md", "# Guide\n")),
+            )
+            .mount(&server)
+            .await;
+
+        assert!(matches!(
+            stats_target_type(&server, "latest").await,
+            Some(EntryType::File)
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_stats_resolves_symlinks_to_directories_and_stops_on_loops() {
+        let server = MockServer::start().await;
+        for (link, target) in [("docs", "site/docs"), ("a", "b"), ("b", "a")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/contents/{}", link)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(symlink_body(link, target)))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/site/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "index.md", "path": "site/docs/index.md", "type": "file", "size": 3 },
+            ])))
+            .mount(&server)
+            .await;
+
+        assert!(matches!(
+            stats_target_type(&server, "docs").await,
+            Some(EntryType::Dir)
+        ));
+        assert!(stats_target_type(&server, "a").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn get_repo_round_trips_the_callers_etag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .and(|request: &wiremock::Request| {
+                request
+                    .headers
+                    .get("if-none-match")
+                    .is_some_and(|value| value == "W/\"abc\"")
+            })
+            .respond_with(ResponseTemplate::new(304))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "W/\"def\"")
+                    .set_body_json(json!({
+                        "description": "cool",
+                        "full_name": "octo/repo",
+                        "default_branch": "main",
+                        "stargazers_count": 8,
+                        "forks_count": 2,
+                        "license": null,
+                    })),
+            )
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+        let args = |etag: &str| {
+            Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo", "etag": etag }))
+                    .unwrap(),
+            )
+        };
+
+        let Ok(Json(unchanged)) = github.get_repo(args("W/\"abc\"")).await else {
+            panic!("get_repo failed");
+        };
+        assert!(unchanged.not_modified);
+        assert!(unchanged.repo.is_none());
+        assert_eq!(unchanged.etag.as_deref(), Some("W/\"abc\""));
+
+        let Ok(Json(changed)) = github.get_repo(args("W/\"old\"")).await else {
+            panic!("get_repo failed");
+        };
+        assert!(!changed.not_modified);
+        assert_eq!(changed.repo.map(|repo| repo.stars), Some(8));
+        assert_eq!(changed.etag.as_deref(), Some("W/\"def\""));
+    }
+
+    #[tokio::test]
+    async fn get_repos_isolates_failures_per_repository() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "description": "cool",
+                "full_name": "octo/repo",
+                "default_branch": "main",
+                "stargazers_count": 7,
+                "forks_count": 2,
+                "license": null,
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/gone"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })),
+            )
+            .mount(&server)
+            .await;
+
+        let Ok(Json(response)) = mock_server(&server)
+            .get_repos(Parameters(
+                serde_json::from_value(json!({
+                    "repos": ["octo/repo", "octo/gone", "not-a-repo", "octo/repo/extra"],
+                }))
+                .unwrap(),
+            ))
+            .await
+        else {
+            panic!("get_repos failed");
+        };
+
+        let found: Vec<(&str, Option<u64>, Option<&str>)> = response
+            .repos
+            .iter()
+            .map(|result| {
+                (
+                    result.repo.as_str(),
+                    result.info.as_ref().map(|info| info.stars),
+                    result.error.as_ref().map(|err| err.code.as_str()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("octo/repo", Some(7), None),
+                ("octo/gone", None, Some("404")),
+                ("not-a-repo", None, Some("400")),
+                ("octo/repo/extra", None, Some("400")),
+            ]
+        );
+    }
+
+    async fn path_exists(server: &MockServer, path: &str) -> PathExistsResponse {
+        let Ok(Json(response)) = mock_server(server)
+            .path_exists(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo", "path": path }))
+                    .unwrap(),
+            ))
+            .await
+        else {
+            panic!("path_exists failed for {}", path);
+        };
+        response
+    }
+
+    #[tokio::test]
+    async fn path_exists_reports_files_directories_and_missing_paths() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/README.md"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("README.md", "# Repo\n")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "lib.rs", "path": "src/lib.rs", "type": "file", "size": 3 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/missing.txt"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })),
+            )
+            .mount(&server)
+            .await;
+
+        let file = path_exists(&server, "README.md").await;
+        assert!(file.exists);
+        assert!(matches!(file.r#type, Some(EntryType::File)));
+
+        let dir = path_exists(&server, "src").await;
+        assert!(dir.exists);
+        assert!(matches!(dir.r#type, Some(EntryType::Dir)));
+
+        let missing = path_exists(&server, "missing.txt").await;
+        assert!(!missing.exists);
+        assert!(missing.r#type.is_none());
+    }
+
+    #[tokio::test]
+    async fn get_file_flags_lfs_pointers() {
+        let pointer = "version https://git-lfs.github.com/spec/v1\n\
+                       oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+                       size 2097152\n";
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/assets/logo.psd"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("assets/logo.psd", pointer)),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/README.md"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("README.md", "# Repo\n")),
+            )
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let Json(response) = github
+            .get_file(file_args(
+                json!({ "owner": "octo", "repo": "repo", "path": "assets/logo.psd" }),
+            ))
+            .await
+            .unwrap();
+        assert!(response.is_lfs);
+        let lfs = response.lfs.unwrap();
+        assert!(lfs.oid.starts_with("sha256:4d7a2146"));
+        assert_eq!(lfs.size, 2097152);
+        assert_eq!(response.content, pointer);
+
+        let Json(response) = github
+            .get_file(file_args(
+                json!({ "owner": "octo", "repo": "repo", "path": "README.md" }),
+            ))
+            .await
+            .unwrap();
+        assert!(!response.is_lfs);
+        assert!(response.lfs.is_none());
+    }
+
+    #[tokio::test]
+    async fn get_file_reports_dangling_symlinks() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/latest"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(symlink_body("latest", "gone.txt")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/gone.txt"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })),
+            )
+            .mount(&server)
+            .await;
+
+        let Err(err) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "latest",
+                "resolve_symlink": true,
+            })))
+            .await
+        else {
+            panic!("expected dangling symlink to fail");
+        };
+        assert_eq!(err.code, "404");
+        assert!(err.message.contains("dangling"), "{}", err.message);
+        assert!(err.message.contains("gone.txt"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn get_file_detects_symlink_loops() {
+        let server = MockServer::start().await;
+        for (link, target) in [("a", "b"), ("b", "a")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/contents/{}", link)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(symlink_body(link, target)))
+                .mount(&server)
+                .await;
+        }
+
+        let Err(err) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "a",
+                "resolve_symlink": true,
+            })))
+            .await
+        else {
+            panic!("expected symlink loop to fail");
+        };
+        assert!(err.message.contains("loop"), "{}", err.message);
+    }
+
+    const SLOW_FILE: Duration = Duration::from_millis(100);
+
+    /// Serves a root listing of eight files and each file slowly, tracking the most file
+    /// requests open at once. Raw HTTP, since wiremock cannot tell when a delayed response
+    /// has gone out.
+    struct SlowFiles {
+        uri: String,
+        peak: Arc<AtomicUsize>,
+    }
+
+    impl SlowFiles {
+        async fn start() -> Self {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let uri = format!("http://{}", listener.local_addr().unwrap());
+            let in_flight = Arc::new(AtomicUsize::new(0));
+            let peak = Arc::new(AtomicUsize::new(0));
+            tokio::spawn({
+                let peak = peak.clone();
+                async move {
+                    loop {
+                        let (socket, _) = listener.accept().await.unwrap();
+                        tokio::spawn(Self::serve(socket, in_flight.clone(), peak.clone()));
+                    }
+                }
+            });
+            Self { uri, peak }
+        }
+
+        async fn serve(
+            mut socket: tokio::net::TcpStream,
+            in_flight: Arc<AtomicUsize>,
+            peak: Arc<AtomicUsize>,
+        ) {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+            let request = String::from_utf8_lossy(&request);
+            let target = request.split(' ').nth(1).unwrap_or_default();
+            let path = target.split('?').next().unwrap_or_default();
+
+            let body = match path.strip_prefix("/repos/octo/repo/contents/") {
+                Some(name) => {
+                    let open = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(open, Ordering::SeqCst);
+                    tokio::time::sleep(SLOW_FILE).await;
+                    // Released before replying, so the client can never start another
+                    // request while this one still counts.
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    file_body(name, "text\n")
+                }
+                None => Value::Array(
+                    (0..8)
+                        .map(|i| json!({ "name": format!("{i}.txt"), "path": format!("{i}.txt"), "type": "file", "size": 5 }))
+                        .collect(),
+                ),
+            };
+            let body = body.to_string();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            );
+            socket.write_all(head.as_bytes()).await.unwrap();
+            socket.write_all(body.as_bytes()).await.unwrap();
+        }
+    }
+
+    /// Most file fetches in flight at once while `calls` bundles ran side by side.
+    async fn max_simultaneous_fetches(
+        batch_concurrency: usize,
+        concurrency: Option<usize>,
+        calls: usize,
+    ) -> usize {
+        let files = SlowFiles::start().await;
+        let github = GithubServer::with_options(
+            GithubClient::new(files.uri.clone(), None).unwrap(),
+            ToolSelection::ALL.into_iter().collect(),
+            ServerOptions {
+                batch_concurrency,
+                ..ServerOptions::default()
+            },
+        );
+
+        let bundles = (0..calls).map(|_| {
+            github.bundle(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "concurrency": concurrency,
+                }))
+                .unwrap(),
+            ))
+        });
+        for result in futures::future::join_all(bundles).await {
+            let Ok(Json(response)) = result else {
+                panic!("bundle failed");
+            };
+            assert_eq!(response.files.len(), 8);
+        }
+
+        files.peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn batch_fetches_stay_within_the_concurrency_bound() {
+        // The server-wide bound holds across calls running side by side, and is used in full.
+        assert_eq!(max_simultaneous_fetches(3, None, 2).await, 3);
+        // A call may ask for less, but not more.
+        assert_eq!(max_simultaneous_fetches(3, Some(1), 1).await, 1);
+        assert_eq!(max_simultaneous_fetches(2, Some(16), 1).await, 2);
+    }
+
+    #[tokio::test]
+    async fn bundle_budget_counts_only_bundled_text() {
+        let server = MockServer::start().await;
+        let big = "x".repeat(200);
+        let files: [(&str, &[u8]); 4] = [
+            ("a.png", &[0x89, b'P', b'N', b'G', 0xff, 0xfe, 0xff, 0xfe]),
+            ("b.txt", big.as_bytes()),
+            ("c.txt", b"small\n"),
+            ("d.txt", b"tiny\n"),
+        ];
+        let listing: Vec<_> = files
+            .iter()
+            .map(|(name, bytes)| json!({ "name": name, "path": name, "type": "file", "size": bytes.len() }))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(listing))
+            .mount(&server)
+            .await;
+        for (name, bytes) in files {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/contents/{}", name)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "path": name,
+                    "type": "file",
+                    "size": bytes.len(),
+                    "content": STANDARD.encode(bytes),
+                    "encoding": "base64",
+                })))
+                .mount(&server)
+                .await;
+        }
+
+        // Room for the two small files only; the image must not use any of it.
+        let budget = bundle::bundled_size("c.txt", 6) + bundle::bundled_size("d.txt", 5);
+        let Json(response) = mock_server(&server)
+            .bundle(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "max_total_bytes": budget,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.skipped_binary, vec!["a.png"]);
+        assert_eq!(response.files, vec!["c.txt", "d.txt"]);
+        assert!(response.truncated);
+    }
+
+    #[tokio::test]
+    async fn bundle_skips_binary_files_and_stops_at_the_budget() {
+        let server = MockServer::start().await;
+        let files: [(&str, &[u8]); 4] = [
+            ("a.txt", b"alpha\n"),
+            ("b.bin", &[0x89, b'P', b'N', b'G', 0xff, 0xfe]),
+            ("c.txt", b"gamma"),
+            ("d.txt", b"delta, which no longer fits\n"),
+        ];
+        let listing: Vec<_> = files
+            .iter()
+            .map(|(name, bytes)| json!({ "name": name, "path": name, "type": "file", "size": bytes.len() }))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(listing))
+            .mount(&server)
+            .await;
+        for (name, bytes) in files {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/contents/{}", name)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "path": name,
+                    "type": "file",
+                    "size": bytes.len(),
+                    "content": STANDARD.encode(bytes),
+                    "encoding": "base64",
+                })))
+                .mount(&server)
+                .await;
+        }
+
+        let budget = files[..3]
+            .iter()
+            .map(|(name, bytes)| bundle::bundled_size(name, bytes.len() as u64))
+            .sum::<u64>();
+        let Json(response) = mock_server(&server)
+            .bundle(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "exclude": ["*.md"],
+                    "max_total_bytes": budget,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.content,
+            "==== a.txt ====\nalpha\n==== c.txt ====\ngamma\n"
+        );
+        assert_eq!(response.files, vec!["a.txt", "c.txt"]);
+        assert_eq!(response.skipped_binary, vec!["b.bin"]);
+        assert!(response.truncated);
+    }
+
+    #[tokio::test]
+    async fn issue_counts_reads_search_totals() {
+        let server = MockServer::start().await;
+        for (state, total) in [("open", 12), ("closed", 340)] {
+            Mock::given(method("GET"))
+                .and(path("/search/issues"))
+                .and(query_param(
+                    "q",
+                    format!("repo:octo/repo is:issue is:{state}").as_str(),
+                ))
+                .and(query_param("per_page", "1"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "total_count": total,
+                    "incomplete_results": false,
+                    "items": [{ "number": 1, "title": "An issue" }],
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let client = GithubClient::with_options(
+            server.uri(),
+            None,
+            client::ClientOptions {
+                search_interval: std::time::Duration::ZERO,
+                ..client::ClientOptions::default()
+            },
+        )
+        .unwrap();
+
+        let Json(response) = GithubServer::new(client, ToolSelection::ALL.into_iter().collect())
+            .issue_counts(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo" })).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.open, 12);
+        assert_eq!(response.closed, 340);
+    }
+
+    #[tokio::test]
+    async fn list_issues_excludes_pull_requests_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/issues"))
+            .and(query_param("labels", "bug,help wanted"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(issues_body()))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .list_issues(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "labels": ["bug", "help wanted"],
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.issues.len(), 1);
+        assert_eq!(response.issues[0].number, 1);
+        assert_eq!(response.issues[0].labels, vec!["bug".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn list_issues_keeps_pull_requests_when_requested() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/issues"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(issues_body()))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .list_issues(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "include_prs": true,
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.issues.len(), 2);
+        assert!(response.issues[1].is_pull_request);
+    }
+
+    #[tokio::test]
+    async fn tree_of_empty_repository_is_empty_not_an_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/empty/contents"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "message": "This repository is empty.",
+                "documentation_url": "https://docs.github.com/rest/repos/contents#get-repository-content"
+            })))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .tree(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "empty" })).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(response.entries.is_empty());
+        assert!(response.empty);
+    }
+
+    #[tokio::test]
+    async fn describe_tools_includes_argument_schemas_of_enabled_tools() {
+        let client = GithubClient::new("https://api.github.com".to_string(), None).unwrap();
+        let selected = [ToolSelection::GetFile, ToolSelection::DescribeTools];
+        let server = GithubServer::new(client, selected.into_iter().collect());
+
+        let Json(response) = server.describe_tools().await.unwrap();
+
+        let names: Vec<&str> = response
+            .tools
+            .iter()
+            .map(|tool| tool.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["describe_tools", "get_file"]);
+        let get_file = &response.tools[1];
+        assert!(get_file.description.is_some());
+        assert!(get_file.output_schema.is_some());
+        let properties = get_file.input_schema["properties"].as_object().unwrap();
+        assert!(properties.contains_key("line_range"));
+        assert!(properties.contains_key("path"));
+    }
+
+    #[tokio::test]
+    async fn tree_graph_roots_the_diagram_at_the_requested_directory() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "guide", "path": "docs/guide", "type": "dir" },
+                { "name": "index.md", "path": "docs/index.md", "type": "file", "size": 3 },
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/docs/guide"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "setup.md", "path": "docs/guide/setup.md", "type": "file", "size": 3 },
+            ])))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .tree_graph(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": "docs/",
+                    "format": "mermaid",
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.depth, 2);
+        assert_eq!(response.nodes, 4);
+        assert!(!response.truncated);
+        assert_eq!(
+            response.graph,
+            "graph TD\n    n0[\"docs\"]\n    n1[\"guide\"]\n    n2[\"index.md\"]\n    n3[\"setup.md\"]\n    n0 --> n1\n    n0 --> n2\n    n1 --> n3\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn tree_graph_stops_crawling_at_the_node_cap() {
+        let server = MockServer::start().await;
+        let mut listing = vec![json!({ "name": "sub", "path": "sub", "type": "dir" })];
+        listing.extend((0..MAX_GRAPH_NODES).map(|i| {
+            json!({ "name": format!("{i}.md"), "path": format!("{i}.md"), "type": "file", "size": 1 })
+        }));
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(listing))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/sub"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .tree_graph(Parameters(
+                serde_json::from_value(json!({ "owner": "octo", "repo": "repo", "format": "dot" }))
+                    .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.nodes, MAX_GRAPH_NODES);
+        assert!(response.truncated);
+    }
+
+    #[tokio::test]
+    async fn get_file_at_merge_base_reads_the_common_ancestor() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/compare/main...feature"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "status": "diverged",
+                "merge_base_commit": { "sha": "b4se" },
+                "commits": [],
+                "files": [],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/src/lib.rs"))
+            .and(query_param("ref", "b4se"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(file_body("src/lib.rs", "pub fn old() {}\n")),
+            )
+            .mount(&server)
+            .await;
+        let args = |path: &str| {
+            Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "base": "refs/heads/main",
+                    "head": "feature",
+                    "path": path,
+                }))
+                .unwrap(),
+            )
+        };
+
+        let Json(response) = mock_server(&server)
+            .get_file_at_merge_base(args("src/lib.rs"))
+            .await
+            .unwrap();
+        assert_eq!(response.merge_base, "b4se");
+        assert!(response.exists);
+        assert_eq!(response.content.as_deref(), Some("pub fn old() {}\n"));
+
+        // Added on the branch, so absent from the common ancestor.
+        let Json(response) = mock_server(&server)
+            .get_file_at_merge_base(args("src/new.rs"))
+            .await
+            .unwrap();
+        assert_eq!(response.merge_base, "b4se");
+        assert!(!response.exists);
+        assert_eq!(response.content, None);
+    }
+
+    #[tokio::test]
+    async fn diff_files_compares_paths_across_repositories() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/upstream/lib/contents/src/lib.rs"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("src/lib.rs", "a\nb\n")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/fork/lib/contents/src/lib.rs"))
+            .and(query_param("ref", "feature"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("src/lib.rs", "a\nc\n")),
+            )
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .diff_files(Parameters(
+                serde_json::from_value(json!({
+                    "left": { "owner": "upstream", "repo": "lib", "path": "src/lib.rs" },
+                    "right": { "owner": "fork", "repo": "lib", "path": "src/lib.rs", "ref": "feature" },
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, crate::models::DiffStatus::Modified);
+        assert!(response.diff.contains("--- a/upstream/lib/src/lib.rs"));
+        assert!(response.diff.contains("+++ b/fork/lib/src/lib.rs"));
+        assert!(response.diff.contains("-b\n+c\n"));
+        assert_eq!(response.left.lines, 2);
+        assert_eq!(response.right.r#ref.as_deref(), Some("feature"));
+    }
+
+    #[tokio::test]
+    async fn diff_files_treats_missing_side_as_added_or_removed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/upstream/lib/contents/NEW.md"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "message": "Not Found"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/fork/lib/contents/NEW.md"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body("NEW.md", "hi\n")))
+            .mount(&server)
+            .await;
+
+        let server = mock_server(&server);
+        let args = |left: &str, right: &str| {
+            Parameters(
+                serde_json::from_value::<DiffFilesArgs>(json!({
+                    "left": { "owner": left, "repo": "lib", "path": "NEW.md" },
+                    "right": { "owner": right, "repo": "lib", "path": "NEW.md" },
+                }))
+                .unwrap(),
+            )
+        };
+
+        let Json(added) = server.diff_files(args("upstream", "fork")).await.unwrap();
+        assert_eq!(added.status, crate::models::DiffStatus::Added);
+        assert!(!added.left.exists);
+        assert!(added.diff.contains("+hi"));
+
+        let Json(removed) = server.diff_files(args("fork", "upstream")).await.unwrap();
+        assert_eq!(removed.status, crate::models::DiffStatus::Removed);
+        assert!(!removed.right.exists);
+        assert!(removed.diff.contains("-hi"));
+
+        assert!(
+            server
+                .diff_files(args("upstream", "upstream"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_refs_mixes_existing_and_missing_refs() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits/main"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("1111"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits/v1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("2222"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/commits/gone"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .resolve_refs(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "refs": ["main", "gone", "v1.0"],
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        let summary: Vec<_> = response
+            .refs
+            .iter()
+            .map(|r| (r.r#ref.as_str(), r.sha.as_deref(), r.exists))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("main", Some("1111"), true),
+                ("gone", None, false),
+                ("v1.0", Some("2222"), true),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_refs_caps_the_number_of_refs() {
+        let server = MockServer::start().await;
+        let refs: Vec<String> = (0..=MAX_RESOLVE_REFS).map(|i| format!("r{i}")).collect();
+
+        let result = mock_server(&server)
+            .resolve_refs(Parameters(
+                serde_json::from_value(json!({ "owner": "o", "repo": "r", "refs": refs })).unwrap(),
+            ))
+            .await;
+
+        assert!(result.is_err());
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn detailed_list_tags_sorts_by_commit_date() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "v1.0", "commit": { "sha": "aaa" } },
+                { "name": "v3.0", "commit": { "sha": "ccc" } },
+                { "name": "v2.0", "commit": { "sha": "bbb" } },
+            ])))
+            .mount(&server)
+            .await;
+        for (sha, date) in [
+            ("aaa", "2023-01-05T10:00:00Z"),
+            ("bbb", "2024-06-01T09:30:00Z"),
+            ("ccc", "2025-02-11T16:45:00Z"),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/octo/repo/commits/{sha}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "sha": sha,
+                    "html_url": format!("https://github.com/octo/repo/commit/{sha}"),
+                    "commit": {
+                        "message": "Release",
+                        "author": { "name": "Octo", "date": "2020-01-01T00:00:00Z" },
+                        "committer": { "name": "Octo", "date": date },
+                    },
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let Json(response) = mock_server(&server)
+            .list_tags(Parameters(
+                serde_json::from_value(
+                    json!({ "owner": "octo", "repo": "repo", "detailed": true }),
+                )
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.tags, vec!["v1.0", "v3.0", "v2.0"]);
+        let details = response.details.unwrap();
+        let sorted: Vec<(&str, &str)> = details
+            .iter()
+            .map(|tag| (tag.name.as_str(), tag.date.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            sorted,
+            vec![
+                ("v3.0", "2025-02-11T16:45:00Z"),
+                ("v2.0", "2024-06-01T09:30:00Z"),
+                ("v1.0", "2023-01-05T10:00:00Z"),
+            ]
+        );
+        assert_eq!(details[0].commit_sha, "ccc");
+        assert!(!response.details_truncated);
+    }
+
+    #[tokio::test]
+    async fn default_ref_applies_only_when_ref_is_omitted() {
+        let server = MockServer::start().await;
+        for r#ref in ["v1.2", "main"] {
+            Mock::given(method("GET"))
+                .and(path("/repos/octo/repo/contents/README.md"))
+                .and(query_param("ref", r#ref))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(file_body("README.md", r#ref)),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let github = server_with(
+            &server,
+            ServerOptions {
+                default_ref: Some("v1.2".to_string()),
+                ..ServerOptions::default()
+            },
+        );
+
+        let Json(pinned) = github
+            .get_file(file_args(
+                json!({ "owner": "octo", "repo": "repo", "path": "README.md" }),
+            ))
+            .await
+            .unwrap();
+        let Json(explicit) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "README.md",
+                "ref": "main",
+            })))
+            .await
+            .unwrap();
+
+        assert_eq!(pinned.content, "v1.2");
+        assert_eq!(explicit.content, "main");
+    }
+
+    #[tokio::test]
+    async fn lossy_get_file_replaces_invalid_utf8() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/latin1.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "path": "latin1.txt",
+                "type": "file",
+                "size": 9,
+                "content": STANDARD.encode(b"caf\xe9 ok\n"),
+                "encoding": "base64",
+            })))
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+        let args = |lossy: bool| {
+            file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "latin1.txt",
+                "lossy": lossy,
+            }))
+        };
+
+        let Json(response) = github.get_file(args(true)).await.unwrap();
+        assert_eq!(response.content, "caf\u{FFFD} ok\n");
+        assert!(response.had_invalid_utf8);
+
+        let Err(err) = github.get_file(args(false)).await else {
+            panic!("strict decoding accepted invalid UTF-8");
+        };
+        assert_eq!(err.kind, Some(ErrorKind::Binary));
+    }
+
+    #[tokio::test]
+    async fn refresh_repo_cache_refetches_a_renamed_default_branch() {
+        let server = MockServer::start().await;
+        let repo = |branch: &str, etag: &str| {
+            ResponseTemplate::new(200)
+                .insert_header("etag", etag)
+                .set_body_json(json!({
+                    "description": null,
+                    "full_name": "octo/repo",
+                    "default_branch": branch,
+                    "stargazers_count": 1,
+                    "forks_count": 0,
+                    "license": null,
+                }))
+        };
+        let unconditional =
+            |request: &wiremock::Request| !request.headers.contains_key("if-none-match");
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .and(unconditional)
+            .respond_with(repo("master", "\"v1\""))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .and(unconditional)
+            .respond_with(repo("main", "\"v2\""))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // GitHub keeps validating the old ETag, so only a refresh sees the rename.
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .and(wiremock::matchers::header_exists("if-none-match"))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        assert_eq!(
+            github.client.default_branch("octo", "repo").await.unwrap(),
+            "master"
+        );
+        assert_eq!(
+            github.client.default_branch("octo", "repo").await.unwrap(),
+            "master"
+        );
+
+        let Json(refreshed) = github
+            .refresh_repo_cache(Parameters(RepoArgs {
+                owner: "octo".to_string(),
+                repo: "repo".to_string(),
+                api_base: None,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(refreshed.cleared, 1);
+        assert_eq!(refreshed.default_branch.as_deref(), Some("main"));
+
+        assert_eq!(
+            github.client.default_branch("octo", "repo").await.unwrap(),
+            "main"
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_default_branch_when_ref_is_missing() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/README.md"))
+            .and(query_param("ref", "stale"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "message": "No commit found for the ref stale"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "description": null,
+                "full_name": "octo/repo",
+                "default_branch": "main",
+                "stargazers_count": 1,
+                "forks_count": 0,
+                "license": null,
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/README.md"))
+            .and(query_param("ref", "main"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body("README.md", "hi")))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "README.md",
+                "ref": "stale",
+                "ref_fallback": true,
+            })))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "hi");
+        assert!(response.used_fallback);
+        assert_eq!(response.used_ref.as_deref(), Some("main"));
+    }
+
+    #[tokio::test]
+    async fn skips_fallback_when_ref_resolves() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/README.md"))
+            .and(query_param("ref", "v1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body("README.md", "v1")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "README.md",
+                "ref": "v1",
+                "ref_fallback": true,
+            })))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "v1");
+        assert!(!response.used_fallback);
+        assert!(response.used_ref.is_none());
+    }
+
+    #[tokio::test]
+    async fn does_not_fall_back_on_other_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/README.md"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "message": "Forbidden"
+            })))
+            .mount(&server)
+            .await;
+
+        let Err(err) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "README.md",
+                "ref": "stale",
+                "ref_fallback": true,
+            })))
+            .await
+        else {
+            panic!("expected the 403 to be returned");
+        };
+
+        assert_eq!(err.code, "403");
+    }
+
+    #[test]
+    fn normalizes_crlf_and_lone_cr_line_endings() {
+        let content = "one\r\ntwo\rthree\n".to_string();
+
+        assert_eq!(normalize_content(content, true, false), "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn slices_lines_consistently_after_eol_normalization() {
+        let content = normalize_content("a\r\nb\rc\r\nd".to_string(), true, false);
+        let limited =
+            apply_content_limits(&content, Some(LineRange::Range { start: 2, end: 3 }), None);
+
+        assert_eq!(limited, "b\nc\n");
+    }
+
+    #[test]
+    fn trims_trailing_whitespace_but_keeps_line_endings() {
+        let content = "fn main() {  \r\n\tbody();\t\n}   ".to_string();
+
+        assert_eq!(
+            normalize_content(content.clone(), false, true),
+            "fn main() {\r\n\tbody();\n}"
+        );
+        assert_eq!(
+            normalize_content(content, true, true),
+            "fn main() {\n\tbody();\n}"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_file_rejects_chunk_index_without_chunk_size() {
+        let server = MockServer::start().await;
+
+        let Err(err) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "README.md",
+                "chunk_index": 1,
+            })))
+            .await
+        else {
+            panic!("chunk_index without chunk_size was accepted");
+        };
+
+        assert_eq!(err.code, "400");
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn chunks_split_at_char_boundaries() {
+        let (chunk, info) = take_chunk("héllo wörld", 1, 4).unwrap();
+
+        assert_eq!(chunk, "o wö");
+        assert_eq!(
+            info,
+            ChunkInfo {
+                chunk_index: 1,
+                total_chunks: 3,
+                has_more: true,
+            }
+        );
+    }
+
+    #[test]
+    fn last_chunk_may_be_partial() {
+        let (chunk, info) = take_chunk("abcdefghij", 2, 4).unwrap();
+
+        assert_eq!(chunk, "ij");
+        assert_eq!(info.total_chunks, 3);
+        assert!(!info.has_more);
+    }
+
+    #[test]
+    fn rejects_out_of_range_chunks() {
+        assert!(take_chunk("abcd", 1, 4).is_err());
+        assert!(take_chunk("abcd", 0, 0).is_err());
+
+        let (chunk, info) = take_chunk("", 0, 4).unwrap();
+        assert_eq!(chunk, "");
+        assert_eq!(info.total_chunks, 1);
+    }
+
+    #[test]
+    fn chunks_apply_after_line_range() {
+        let limited = apply_content_limits(
+            "skip\nkeep one\nkeep two\n",
+            Some(LineRange::Start(2)),
+            None,
+        );
+        let (chunk, info) = take_chunk(&limited, 0, 9).unwrap();
+
+        assert_eq!(chunk, "keep one\n");
+        assert_eq!(info.total_chunks, 2);
+    }
+
+    #[test]
+    fn enforces_character_limit_without_splitting_codepoints() {
+        let content = "héllo";
+        let limited = apply_content_limits(content, None, Some(3));
+
+        assert_eq!(limited, "hél");
+    }
+
+    #[test]
+    fn trims_to_requested_number_of_lines() {
+        let content = "one\ntwo\nthree\nfour\n";
+        let limited = apply_content_limits(content, Some(LineRange::End(2)), None);
+
+        assert_eq!(limited, "one\ntwo\n");
+    }
+
+    #[test]
+    fn applies_both_limits_when_set() {
+        let content = "1\n2\n3\n4";
+        let limited = apply_content_limits(content, Some(LineRange::End(2)), Some(5));
+
+        assert_eq!(limited, "1\n2\n");
+    }
+
+    #[test]
+    fn returns_empty_when_line_limit_is_zero() {
+        let content = "content";
+        let limited = apply_content_limits(content, Some(LineRange::End(0)), Some(10));
+
         assert_eq!(limited, "");
     }
 
@@ -277,4 +5802,540 @@ mod tests {
 
         assert_eq!(limited, "b\nc\n");
     }
+
+    #[test]
+    fn extracts_several_line_ranges_in_file_order() {
+        let content = "a\nb\nc\nd\ne\nf\n";
+
+        let (text, ranges) = extract_line_ranges(
+            content,
+            &[
+                LineRange::Range { start: 5, end: 6 },
+                LineRange::Range { start: 1, end: 2 },
+            ],
+        );
+
+        assert_eq!(text, "a\nb\ne\nf\n");
+        assert_eq!(
+            ranges,
+            vec![
+                LineRange::Range { start: 1, end: 2 },
+                LineRange::Range { start: 5, end: 6 },
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_overlapping_and_adjacent_line_ranges() {
+        let content = "a\nb\nc\nd\ne\nf\n";
+
+        let (text, ranges) = extract_line_ranges(
+            content,
+            &[
+                LineRange::Range { start: 2, end: 4 },
+                LineRange::Range { start: 3, end: 5 },
+                LineRange::End(1),
+                LineRange::Start(9),
+            ],
+        );
+
+        assert_eq!(text, "a\nb\nc\nd\ne\n");
+        assert_eq!(ranges, vec![LineRange::Range { start: 1, end: 5 }]);
+
+        let (text, ranges) = extract_line_ranges(content, &[LineRange::Start(4)]);
+        assert_eq!(text, "d\ne\nf\n");
+        assert_eq!(ranges, vec![LineRange::Range { start: 4, end: 6 }]);
+    }
+
+    fn community_args(kind: &str) -> Parameters<CommunityFileArgs> {
+        Parameters(
+            serde_json::from_value(json!({ "owner": "octo", "repo": "repo", "kind": kind }))
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn community_file_falls_back_through_conventional_locations() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/SECURITY.md"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/.github/SECURITY.md"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(file_body(".github/SECURITY.md", "Email security@")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/docs/SECURITY.md"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("docs/SECURITY.md", "stale")),
+            )
+            .expect(0)
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let Json(response) = github
+            .get_community_file(community_args("security"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.path, ".github/SECURITY.md");
+        assert_eq!(response.content, "Email security@");
+    }
+
+    #[tokio::test]
+    async fn community_file_reports_when_no_location_has_it() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(json!({ "message": "Not Found" })),
+            )
+            .expect(3)
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let Err(err) = github
+            .get_community_file(community_args("code_of_conduct"))
+            .await
+        else {
+            panic!("expected a missing code of conduct to be an error");
+        };
+
+        assert_eq!(err.code, "404");
+        assert!(err.message.contains("CODE_OF_CONDUCT.md"));
+    }
+
+    #[tokio::test]
+    async fn search_flags_incomplete_results() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search/code"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "total_count": 1,
+                "incomplete_results": true,
+                "items": [{
+                    "name": "lib.rs",
+                    "path": "src/lib.rs",
+                    "repository": { "full_name": "octo/repo" },
+                }],
+            })))
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let Json(response) = github
+            .search(Parameters(
+                serde_json::from_value(json!({ "query": "fn main" })).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(response.incomplete);
+        assert!(response.note.unwrap().contains("narrower query"));
+        assert_eq!(response.collected, 1);
+    }
+
+    #[tokio::test]
+    async fn get_file_hashes_the_whole_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/abc.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body("abc.txt", "abc")))
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let Json(response) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "abc.txt",
+                "max_chars": 1,
+                "with_hash": true,
+            })))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "a");
+        assert_eq!(
+            response.content_sha256.as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+    }
+
+    #[tokio::test]
+    async fn get_file_hashes_the_raw_bytes_before_lossy_decoding() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/bad.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "path": "bad.txt",
+                "type": "file",
+                "size": 3,
+                "content": STANDARD.encode(b"ab\xff"),
+                "encoding": "base64",
+            })))
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "bad.txt",
+                "lossy": true,
+                "with_hash": true,
+            })))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "ab\u{fffd}");
+        assert!(response.had_invalid_utf8);
+        assert_eq!(
+            response.content_sha256.as_deref(),
+            Some("2a40b10d4bc66af94cac46438fc1c5d9e11fda03dac8f86304ea8604a3eae1fa")
+        );
+    }
+
+    #[tokio::test]
+    async fn get_file_maps_a_304_to_not_modified() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            // The stock header matcher splits values on commas, which HTTP dates contain.
+            .and(|request: &wiremock::Request| {
+                request
+                    .headers
+                    .get("if-modified-since")
+                    .is_some_and(|value| value == "Wed, 21 Oct 2015 07:28:00 GMT")
+            })
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body("notes.txt", "new")))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "notes.txt",
+                "if_modified_since": "2015-10-21T07:28:00Z",
+            })))
+            .await
+            .unwrap();
+
+        assert!(response.not_modified);
+        assert!(response.content.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_stats_reads_the_path_when_it_changed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(file_body("notes.txt", "new")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_stats(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": "notes.txt",
+                    "if_modified_since": "Wed, 21 Oct 2015 07:28:00 GMT",
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(!response.not_modified);
+        assert_eq!(response.item.unwrap().name, "notes.txt");
+    }
+
+    #[tokio::test]
+    async fn trim_final_newline_drops_one_newline_from_sliced_content() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("notes.txt", "a\nb\nc\n")),
+            )
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        for (line_range, trim, content, truncated) in [
+            ("2:2", false, "b\n", true),
+            ("2:2", true, "b", true),
+            ("2:", false, "b\nc\n", true),
+            ("1:", true, "a\nb\nc", false),
+        ] {
+            let Json(response) = github
+                .get_file(file_args(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": "notes.txt",
+                    "line_range": line_range,
+                    "trim_final_newline": trim,
+                })))
+                .await
+                .unwrap();
+
+            assert_eq!(response.content, content, "{} trim={}", line_range, trim);
+            assert_eq!(
+                response.truncated, truncated,
+                "{} trim={}",
+                line_range, trim
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn get_file_returns_content_and_the_new_etag_when_it_changed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v2\"")
+                    .set_body_json(file_body("notes.txt", "new")),
+            )
+            // The content comes from the same response as the ETag, not a second read.
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "notes.txt",
+                "etag": "\"v1\"",
+            })))
+            .await
+            .unwrap();
+
+        assert!(!response.not_modified);
+        assert_eq!(response.content, "new");
+        assert_eq!(response.etag.as_deref(), Some("\"v2\""));
+    }
+
+    #[tokio::test]
+    async fn get_stats_maps_a_matching_etag_to_not_modified() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            .and(|request: &wiremock::Request| {
+                request
+                    .headers
+                    .get("if-none-match")
+                    .is_some_and(|value| value == "\"v1\"")
+            })
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let Json(response) = mock_server(&server)
+            .get_stats(Parameters(
+                serde_json::from_value(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": "notes.txt",
+                    "etag": "\"v1\"",
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(response.not_modified);
+        assert!(response.item.is_none());
+        assert_eq!(response.etag.as_deref(), Some("\"v1\""));
+    }
+
+    #[test]
+    fn http_date_normalizes_supported_formats() {
+        for value in [
+            "2015-10-21T07:28:00Z",
+            "2015-10-21T09:28:00+02:00",
+            "Wed, 21 Oct 2015 07:28:00 GMT",
+        ] {
+            assert_eq!(
+                http_date("if_modified_since", value).unwrap(),
+                "Wed, 21 Oct 2015 07:28:00 GMT"
+            );
+        }
+        assert_eq!(
+            http_date("if_modified_since", "2015-10-21").unwrap(),
+            "Wed, 21 Oct 2015 00:00:00 GMT"
+        );
+
+        let err = http_date("if_modified_since", "yesterday").unwrap_err();
+        assert_eq!(err.code, "400");
+    }
+
+    #[tokio::test]
+    async fn truncation_marker_is_appended_only_when_content_was_cut() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("notes.txt", "0123456789")),
+            )
+            .mount(&server)
+            .await;
+        let github = server_with(
+            &server,
+            ServerOptions {
+                truncation_marker: Some("\n... [truncated {n} chars]".to_string()),
+                ..ServerOptions::default()
+            },
+        );
+
+        let Json(cut) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "notes.txt",
+                "max_chars": 4,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(cut.content, "0123\n... [truncated 6 chars]");
+        assert_eq!(cut.returned_length, 4);
+
+        let Json(whole) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "notes.txt",
+                "max_chars": 10,
+            })))
+            .await
+            .unwrap();
+        assert_eq!(whole.content, "0123456789");
+        assert!(!whole.truncated);
+    }
+
+    #[tokio::test]
+    async fn truncation_marker_is_never_added_to_chunks() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(file_body("notes.txt", "0123456789")),
+            )
+            .mount(&server)
+            .await;
+        let github = server_with(
+            &server,
+            ServerOptions {
+                truncation_marker: Some("\n... [truncated {n} chars]".to_string()),
+                ..ServerOptions::default()
+            },
+        );
+
+        let mut joined = String::new();
+        for chunk_index in 0..3 {
+            let Json(page) = github
+                .get_file(file_args(json!({
+                    "owner": "octo",
+                    "repo": "repo",
+                    "path": "notes.txt",
+                    "chunk_size": 4,
+                    "chunk_index": chunk_index,
+                })))
+                .await
+                .unwrap();
+            assert_eq!(page.chunk.unwrap().has_more, chunk_index < 2);
+            joined.push_str(&page.content);
+        }
+
+        assert_eq!(joined, "0123456789");
+    }
+
+    #[tokio::test]
+    async fn get_file_reports_original_and_returned_lengths() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/octo/repo/contents/notes.txt"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(file_body("notes.txt", "one\ntwo\nthree\n")),
+            )
+            .mount(&server)
+            .await;
+        let github = mock_server(&server);
+
+        let Json(whole) = github
+            .get_file(file_args(json!({
+                "owner": "octo",
+                "repo": "repo",
+                "path": "notes.txt",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(whole.original_length, 14);
+        assert_eq!(whole.returned_length, 14);
+        assert!(!whole.truncated);
+
+        for limits in [json!({ "max_chars": 5 }), json!({ "line_range": "2:2" })] {
+            let mut args = json!({ "owner": "octo", "repo": "repo", "path": "notes.txt" });
+            args.as_object_mut()
+                .unwrap()
+                .extend(limits.as_object().unwrap().clone());
+            let Json(partial) = github.get_file(file_args(args)).await.unwrap();
+
+            assert_eq!(partial.original_length, 14);
+            assert_eq!(partial.returned_length, partial.content.chars().count());
+            assert!(partial.returned_length < 14);
+            assert!(partial.truncated);
+        }
+    }
+
+    #[tokio::test]
+    async fn api_base_overrides_reuse_one_client_per_base() {
+        let server = MockServer::start().await;
+        let github = server_with(
+            &server,
+            ServerOptions {
+                allow_base_override: true,
+                ..ServerOptions::default()
+            },
+        );
+
+        github
+            .with_api_base("https://ghe.example.com/api/v3")
+            .unwrap();
+        github
+            .with_api_base("https://ghe.example.com/api/v3/")
+            .unwrap();
+        github
+            .with_api_base("https://other.example.com/api/v3")
+            .unwrap();
+
+        assert_eq!(github.override_clients.lock().unwrap().len(), 2);
+    }
 }
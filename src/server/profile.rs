@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::models::{EntryType, ExtensionCount, TreeEntry};
+
+/// File and directory totals of a tree, with files grouped by extension.
+#[derive(Debug, Default)]
+pub struct Profile {
+    pub by_extension: Vec<ExtensionCount>,
+    pub files: usize,
+    pub dirs: usize,
+}
+
+/// Lowercased extension of a file name; dotfiles like `.gitignore` have none.
+fn extension(name: &str) -> Option<String> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    (!stem.is_empty() && !ext.is_empty()).then(|| ext.to_ascii_lowercase())
+}
+
+/// Tallies a tree at every depth, counting symlinks as files like `dir_size` does and
+/// skipping submodules. Extensions are ordered by file count, then by name.
+pub fn profile(entries: &[TreeEntry]) -> Profile {
+    let mut by_extension: HashMap<Option<String>, (usize, u64)> = HashMap::new();
+    let mut profile = Profile::default();
+    tally(entries, &mut by_extension, &mut profile);
+
+    profile.by_extension = by_extension
+        .into_iter()
+        .map(|(ext, (count, total_bytes))| ExtensionCount {
+            ext,
+            count,
+            total_bytes,
+        })
+        .collect();
+    profile
+        .by_extension
+        .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.ext.cmp(&b.ext)));
+    profile
+}
+
+fn tally(
+    entries: &[TreeEntry],
+    by_extension: &mut HashMap<Option<String>, (usize, u64)>,
+    profile: &mut Profile,
+) {
+    for entry in entries {
+        match entry.r#type {
+            EntryType::File | EntryType::Symlink => {
+                profile.files += 1;
+                let totals = by_extension.entry(extension(&entry.name)).or_default();
+                totals.0 += 1;
+                totals.1 += entry.size.unwrap_or(0);
+            }
+            EntryType::Dir => profile.dirs += 1,
+            EntryType::Submodule | EntryType::Unknown => {}
+        }
+        tally(&entry.children, by_extension, profile);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extension;
+
+    #[test]
+    fn reads_extensions_from_file_names() {
+        assert_eq!(extension("lib.rs").as_deref(), Some("rs"));
+        assert_eq!(extension("archive.tar.GZ").as_deref(), Some("gz"));
+        assert_eq!(extension("Makefile"), None);
+        assert_eq!(extension(".gitignore"), None);
+        assert_eq!(extension("trailing."), None);
+    }
+}
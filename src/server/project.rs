@@ -0,0 +1,165 @@
+/// Root manifest files and the project type each one indicates.
+const BY_NAME: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust"),
+    ("package.json", "Node"),
+    ("deno.json", "Deno"),
+    ("deno.jsonc", "Deno"),
+    ("pyproject.toml", "Python"),
+    ("setup.py", "Python"),
+    ("setup.cfg", "Python"),
+    ("requirements.txt", "Python"),
+    ("Pipfile", "Python"),
+    ("go.mod", "Go"),
+    ("pom.xml", "Java"),
+    ("build.gradle", "Java"),
+    ("build.gradle.kts", "Kotlin"),
+    ("build.sbt", "Scala"),
+    ("Gemfile", "Ruby"),
+    ("composer.json", "PHP"),
+    ("mix.exs", "Elixir"),
+    ("Package.swift", "Swift"),
+    ("pubspec.yaml", "Dart"),
+    ("stack.yaml", "Haskell"),
+    ("CMakeLists.txt", "C/C++"),
+    ("meson.build", "C/C++"),
+    ("flake.nix", "Nix"),
+];
+
+/// Manifests recognized by extension, since their names vary per project.
+const BY_EXTENSION: &[(&str, &str)] = &[
+    ("csproj", ".NET"),
+    ("fsproj", ".NET"),
+    ("sln", ".NET"),
+    ("cabal", "Haskell"),
+    ("gemspec", "Ruby"),
+];
+
+/// Conventional entry files, most specific first; paths are relative to the project root.
+const ENTRY_FILES: &[&str] = &[
+    "src/main.rs",
+    "src/lib.rs",
+    "src/index.ts",
+    "src/index.js",
+    "index.ts",
+    "index.js",
+    "main.py",
+    "__main__.py",
+    "app.py",
+    "__init__.py",
+    "main.go",
+];
+
+/// Directory whose listing is needed, besides the root, to detect entry files.
+pub const ENTRY_SOURCE_DIR: &str = "src";
+
+fn project_type(name: &str) -> Option<&'static str> {
+    BY_NAME
+        .iter()
+        .find(|(file_name, _)| *file_name == name)
+        .or_else(|| {
+            let (stem, extension) = name.rsplit_once('.')?;
+            if stem.is_empty() {
+                return None;
+            }
+            BY_EXTENSION.iter().find(|(ext, _)| *ext == extension)
+        })
+        .map(|(_, kind)| *kind)
+}
+
+/// Classifies a project from the file names at its root.
+///
+/// Returns the detected project types (deduplicated, in listing order) and the
+/// manifest files that triggered them.
+pub fn classify<'a>(names: impl IntoIterator<Item = &'a str>) -> (Vec<String>, Vec<String>) {
+    let mut detected = Vec::new();
+    let mut manifests = Vec::new();
+
+    for name in names {
+        let Some(kind) = project_type(name) else {
+            continue;
+        };
+
+        manifests.push(name.to_string());
+        if !detected.iter().any(|known| known == kind) {
+            detected.push(kind.to_string());
+        }
+    }
+
+    (detected, manifests)
+}
+
+/// Picks the conventional entry file among `paths` (relative to the project root).
+pub fn entry_file<'a>(paths: impl IntoIterator<Item = &'a str>) -> Option<&'static str> {
+    let paths: Vec<&str> = paths.into_iter().collect();
+    ENTRY_FILES
+        .iter()
+        .find(|candidate| paths.contains(candidate))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, entry_file};
+
+    #[test]
+    fn detects_single_language_projects() {
+        let (detected, manifests) = classify(["README.md", "Cargo.toml", "Cargo.lock", "src"]);
+
+        assert_eq!(detected, vec!["Rust"]);
+        assert_eq!(manifests, vec!["Cargo.toml"]);
+    }
+
+    #[test]
+    fn reports_every_manifest_but_each_type_once() {
+        let (detected, manifests) =
+            classify(["pyproject.toml", "package.json", "setup.py", "go.mod"]);
+
+        assert_eq!(detected, vec!["Python", "Node", "Go"]);
+        assert_eq!(
+            manifests,
+            vec!["pyproject.toml", "package.json", "setup.py", "go.mod"]
+        );
+    }
+
+    #[test]
+    fn recognizes_manifests_by_extension() {
+        let (detected, manifests) = classify(["App.sln", "App.csproj", ".csproj"]);
+
+        assert_eq!(detected, vec![".NET"]);
+        assert_eq!(manifests, vec!["App.sln", "App.csproj"]);
+    }
+
+    #[test]
+    fn unknown_layouts_detect_nothing() {
+        let (detected, manifests) = classify(["LICENSE", "docs", "index.html"]);
+
+        assert!(detected.is_empty());
+        assert!(manifests.is_empty());
+    }
+
+    #[test]
+    fn finds_entry_files_across_ecosystems() {
+        assert_eq!(
+            entry_file(["Cargo.toml", "src/lib.rs", "src/main.rs"]),
+            Some("src/main.rs")
+        );
+        assert_eq!(
+            entry_file(["package.json", "src/index.ts", "src/util.ts"]),
+            Some("src/index.ts")
+        );
+        assert_eq!(entry_file(["package.json", "index.js"]), Some("index.js"));
+        assert_eq!(
+            entry_file(["pyproject.toml", "__init__.py", "main.py"]),
+            Some("main.py")
+        );
+        assert_eq!(entry_file(["go.mod", "main.go"]), Some("main.go"));
+    }
+
+    #[test]
+    fn ignores_entry_names_outside_their_conventional_place() {
+        assert_eq!(
+            entry_file(["lib/main.rs", "docs/index.js", "README.md"]),
+            None
+        );
+    }
+}
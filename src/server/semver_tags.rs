@@ -0,0 +1,79 @@
+use semver::{Version, VersionReq};
+
+use crate::error::ApiErrorBody;
+
+pub fn parse_range(range: &str) -> Result<VersionReq, ApiErrorBody> {
+    VersionReq::parse(range).map_err(|err| {
+        ApiErrorBody::new(
+            format!(
+                "Invalid range `{}`: {}; separate comparators with commas, e.g. `>=1.2.0, <2.0.0`.",
+                range, err
+            ),
+            "400",
+        )
+    })
+}
+
+/// Tags whose name, minus a leading `v`, is a full semver version matching `range`, sorted
+/// from lowest to highest version. Pre-releases only match a comparator naming a pre-release
+/// of the same `major.minor.patch`, as in Cargo.
+pub fn matching(tags: Vec<String>, range: &VersionReq) -> Vec<String> {
+    let mut versions: Vec<(Version, String)> = tags
+        .into_iter()
+        .filter_map(|tag| {
+            let version = Version::parse(tag.strip_prefix('v').unwrap_or(&tag)).ok()?;
+            range.matches(&version).then_some((version, tag))
+        })
+        .collect();
+    versions.sort();
+    versions.into_iter().map(|(_, tag)| tag).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matching, parse_range};
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn filters_and_sorts_by_version_stripping_a_v_prefix() {
+        let all = tags(&[
+            "v1.10.0",
+            "1.2.0",
+            "v2.0.0",
+            "v1.9.1",
+            "v1.1.9",
+            "release-1",
+            "v1.5",
+            "nightly",
+        ]);
+
+        let found = matching(all, &parse_range(">=1.2.0, <2.0.0").unwrap());
+
+        assert_eq!(found, tags(&["1.2.0", "v1.9.1", "v1.10.0"]));
+    }
+
+    #[test]
+    fn pre_releases_need_a_pre_release_comparator() {
+        let all = tags(&["v2.0.0-rc.1", "v2.0.0", "v1.3.0-beta.1", "v1.3.0"]);
+
+        assert_eq!(
+            matching(all.clone(), &parse_range(">=1.0.0").unwrap()),
+            tags(&["v1.3.0", "v2.0.0"])
+        );
+        assert_eq!(
+            matching(all, &parse_range(">=2.0.0-rc.1").unwrap()),
+            tags(&["v2.0.0-rc.1", "v2.0.0"])
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_ranges() {
+        let err = parse_range(">=1.2.0 <<2").unwrap_err();
+
+        assert_eq!(err.code, "400");
+        assert!(err.message.contains("separate comparators with commas"));
+    }
+}
@@ -0,0 +1,222 @@
+use github_fetcher_mcp::{
+    cli::ToolSelection,
+    client::GithubClient,
+    oneshot,
+    server::{GithubServer, ServerOptions},
+};
+use serde_json::json;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+fn server_for(mock: &MockServer, tools: &[ToolSelection]) -> GithubServer {
+    let client = GithubClient::new(mock.uri(), None).unwrap();
+    GithubServer::new(client, tools.iter().copied().collect())
+}
+
+#[tokio::test]
+async fn oneshot_get_repo_prints_structured_result() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/repo"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "description": "cool repo",
+            "full_name": "octo/repo",
+            "default_branch": "main",
+            "stargazers_count": 7,
+            "forks_count": 2,
+            "license": null,
+        })))
+        .mount(&mock)
+        .await;
+
+    let output = oneshot::call_tool(
+        server_for(&mock, &ToolSelection::ALL),
+        "get_repo",
+        r#"{"owner":"octo","repo":"repo"}"#,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(output["repo"]["description"], "cool repo");
+    assert_eq!(output["repo"]["stars"], 7);
+    assert_eq!(output["repo"]["forks"], 2);
+    assert!(output.get("meta").is_none());
+}
+
+#[tokio::test]
+async fn fields_trims_tree_entries() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/repo/contents"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "name": "README.md", "path": "README.md", "type": "file", "size": 42 },
+        ])))
+        .mount(&mock)
+        .await;
+
+    let output = oneshot::call_tool(
+        server_for(&mock, &[ToolSelection::Tree]),
+        "tree",
+        r#"{"owner":"octo","repo":"repo","fields":["name","type"]}"#,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        output["entries"],
+        json!([{ "type": "file", "name": "README.md" }])
+    );
+    assert_eq!(output["depth"], 1);
+}
+
+#[tokio::test]
+async fn include_meta_adds_provenance_to_results() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/repo/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "name": "v1.0.0" }])))
+        .mount(&mock)
+        .await;
+    let client = GithubClient::new(mock.uri(), None).unwrap();
+    let server = GithubServer::with_options(
+        client,
+        ToolSelection::ALL.into_iter().collect(),
+        ServerOptions {
+            include_meta: true,
+            ..ServerOptions::default()
+        },
+    );
+
+    let output = oneshot::call_tool(server, "list_tags", r#"{"owner":"octo","repo":"repo"}"#)
+        .await
+        .unwrap();
+
+    assert_eq!(output["tags"], json!(["v1.0.0"]));
+    let meta = &output["meta"];
+    assert_eq!(meta["version"], env!("CARGO_PKG_VERSION"));
+    assert!(meta["api_base"].as_str().unwrap().starts_with(&mock.uri()));
+    assert!(meta.get("ref").is_none());
+    assert!(meta["timestamp"].as_str().unwrap().ends_with('Z'));
+    assert_eq!(meta["retries"], 0);
+}
+
+#[tokio::test]
+async fn include_meta_reports_retries() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/repo/tags"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .mount(&mock)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/repo/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "name": "v1.0.0" }])))
+        .mount(&mock)
+        .await;
+    let client = GithubClient::new(mock.uri(), None).unwrap();
+    let server = GithubServer::with_options(
+        client,
+        ToolSelection::ALL.into_iter().collect(),
+        ServerOptions {
+            include_meta: true,
+            ..ServerOptions::default()
+        },
+    );
+
+    let output = oneshot::call_tool(server, "list_tags", r#"{"owner":"octo","repo":"repo"}"#)
+        .await
+        .unwrap();
+
+    assert_eq!(output["tags"], json!(["v1.0.0"]));
+    assert_eq!(output["meta"]["retries"], 1);
+    assert_eq!(output["meta"]["backoff_ms"], 0);
+}
+
+#[tokio::test]
+async fn api_base_override_targets_another_host_when_allowed() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/repos/octo/repo/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "name": "v1.0.0" }])))
+        .mount(&mock)
+        .await;
+    // Nothing listens here, so only the overridden base can answer.
+    let client = GithubClient::new("http://127.0.0.1:9".to_string(), None).unwrap();
+    let options = ServerOptions {
+        allow_base_override: true,
+        ..ServerOptions::default()
+    };
+    let server =
+        GithubServer::with_options(client, ToolSelection::ALL.into_iter().collect(), options);
+    let arguments = json!({
+        "owner": "octo",
+        "repo": "repo",
+        "api_base": format!("{}/api/v3", mock.uri()),
+    });
+
+    let output = oneshot::call_tool(server, "list_tags", &arguments.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(output["tags"], json!(["v1.0.0"]));
+}
+
+#[tokio::test]
+async fn api_base_override_is_refused_by_default() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .expect(0)
+        .mount(&mock)
+        .await;
+    let arguments = json!({ "owner": "octo", "repo": "repo", "api_base": mock.uri() });
+
+    let err = oneshot::call_tool(
+        server_for(&mock, &ToolSelection::ALL),
+        "list_tags",
+        &arguments.to_string(),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.to_string().contains("--allow-base-override"));
+}
+
+#[tokio::test]
+async fn oneshot_reports_tool_errors() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+            "message": "Forbidden"
+        })))
+        .mount(&mock)
+        .await;
+
+    let err = oneshot::call_tool(
+        server_for(&mock, &ToolSelection::ALL),
+        "list_tags",
+        r#"{"owner":"octo","repo":"repo"}"#,
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.to_string().contains("Forbidden"));
+}
+
+#[tokio::test]
+async fn oneshot_rejects_non_object_args() {
+    let mock = MockServer::start().await;
+
+    let err = oneshot::call_tool(
+        server_for(&mock, &[ToolSelection::GetRepo]),
+        "get_repo",
+        "[]",
+    )
+    .await
+    .unwrap_err();
+
+    assert!(err.to_string().contains("JSON object"));
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use github_fetcher_mcp::{
+    cli::ToolSelection, client::GithubClient, serve_until, server::GithubServer,
+};
+use rmcp::{ServiceExt, model::CallToolRequestParam};
+use serde_json::json;
+use tokio::sync::oneshot;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+#[tokio::test]
+async fn shutdown_lets_in_flight_calls_finish() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/repo/tags"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_delay(Duration::from_millis(300))
+                .set_body_json(json!([{ "name": "v1.0.0" }])),
+        )
+        .mount(&mock)
+        .await;
+
+    let client = GithubClient::new(mock.uri(), None).unwrap();
+    let server = GithubServer::new(client, ToolSelection::ALL.into_iter().collect());
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    let (trigger, shutdown) = oneshot::channel::<()>();
+    let serving = tokio::spawn(serve_until(server, server_io, async move {
+        shutdown.await?;
+        Ok(())
+    }));
+
+    let client = ().serve(client_io).await.unwrap();
+    let call = client.call_tool(CallToolRequestParam {
+        name: "list_tags".into(),
+        arguments: Some(
+            json!({ "owner": "octo", "repo": "repo" })
+                .as_object()
+                .cloned()
+                .unwrap(),
+        ),
+    });
+    let signal = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        trigger.send(()).unwrap();
+    };
+    let (result, ()) = tokio::join!(call, signal);
+
+    let result = result.unwrap();
+    assert_eq!(result.is_error, Some(false));
+    assert_eq!(
+        result.structured_content.unwrap()["tags"],
+        json!(["v1.0.0"])
+    );
+
+    tokio::time::timeout(Duration::from_secs(5), serving)
+        .await
+        .expect("server did not shut down")
+        .unwrap()
+        .unwrap();
+}